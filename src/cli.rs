@@ -0,0 +1,81 @@
+use std::{fs, path::Path};
+
+use mosaic::internals::MosaicIO;
+
+use crate::core::structures::{diff::diff_files, merge::merge_files};
+
+const USAGE: &str = "usage: grasp diff [--by-label] BEFORE.mos AFTER.mos
+       grasp merge BASE.mos OURS.mos THEIRS.mos -o OUT.mos";
+
+// returns the exit code when the arguments name a command, the editor starts otherwise
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    match args.first().map(|a| a.as_str()) {
+        Some("diff") => Some(run_diff(&args[1..])),
+        Some("merge") => Some(run_merge(&args[1..])),
+        _ => None,
+    }
+}
+
+// exits like diff(1): 0 when the documents match, 1 when they differ, 2 on errors
+fn run_diff(args: &[String]) -> i32 {
+    let by_label = args.iter().any(|a| a == "--by-label");
+    let files = args
+        .iter()
+        .filter(|a| !a.starts_with("--"))
+        .collect::<Vec<_>>();
+    let [before, after] = files.as_slice() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+
+    match diff_files(Path::new(before), Path::new(after), by_label) {
+        Ok(diff) => {
+            println!("{}", diff.to_text(before, after));
+            if diff.is_empty() {
+                0
+            } else {
+                1
+            }
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            2
+        }
+    }
+}
+
+// exits like a git merge driver: 0 for a clean merge, 1 when conflicts were recorded, 2 on errors
+fn run_merge(args: &[String]) -> i32 {
+    let [base, ours, theirs, flag, out] = args else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+
+    if flag != "-o" {
+        eprintln!("{}", USAGE);
+        return 2;
+    }
+
+    let (merged, plan) = match merge_files(Path::new(base), Path::new(ours), Path::new(theirs)) {
+        Ok(merge) => merge,
+        Err(error) => {
+            eprintln!("{}", error);
+            return 2;
+        }
+    };
+
+    if let Err(error) = fs::write(out, merged.save()) {
+        eprintln!("Cannot write {}: {}", out, error);
+        return 2;
+    }
+
+    for conflict in &plan.conflicts {
+        eprintln!("CONFLICT: {}", conflict.message);
+    }
+
+    if plan.conflicts.is_empty() {
+        0
+    } else {
+        1
+    }
+}
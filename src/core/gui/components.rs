@@ -2,6 +2,8 @@ use crate::{
     core::structures::{on_list_element_deleted, on_pair_element_deleted},
     editor_state::{
         foundation::GraspEditorState,
+        network::on_dot_deleted,
+        query::{on_query_deleted, query_highlight_renderer},
         selection::{pick_n_renderer, selection_renderer},
         view::{color_property_renderer, two_float_property_xy_renderer},
     },
@@ -39,6 +41,26 @@ pub fn setup_component_renderers(instance: &mut GraspEditorState) {
         .hidden_property_renderers
         .insert("ProcedureArgument".to_string());
 
+    for name in [
+        "Query",
+        "QueryRoot",
+        "QueryNode",
+        "QueryOperand",
+        "QueryComponent",
+        "QueryPredicate",
+        "QueryHighlight",
+    ] {
+        instance.hidden_property_renderers.insert(name.to_string());
+    }
+
+    instance
+        .component_delete_reactions
+        .insert("Query".into(), Box::new(on_query_deleted));
+
+    instance
+        .component_delete_reactions
+        .insert("Dot".into(), Box::new(on_dot_deleted));
+
     instance
         .hidden_property_renderers
         .insert("Selected".to_string());
@@ -97,6 +119,10 @@ pub fn setup_component_renderers(instance: &mut GraspEditorState) {
         .component_entity_renderers
         .insert("PatternMatch".into(), Box::new(pattern_match_renderer));
 
+    instance
+        .component_entity_renderers
+        .insert("QueryHighlight".into(), Box::new(query_highlight_renderer));
+
     instance
         .component_entity_renderers
         .insert("HasComponent".into(), Box::new(has_component_renderer));
@@ -1,7 +1,7 @@
 use std::{
     collections::HashMap,
-    fs::{self, File},
-    io::{BufWriter, Read, Write},
+    fs::File,
+    io::Read,
     path::Path,
     sync::{Arc, Mutex},
     time::Instant,
@@ -17,7 +17,7 @@ use log::info;
 use gl::types::GLvoid;
 use sdl2::video::GLProfile;
 
-use crate::{core::math::Vec2, seq::SeqWriter};
+use crate::{core::math::Vec2, logging::init_logging};
 
 pub const LOAD_TEXTURE_EVENT: u32 = 10101;
 
@@ -172,27 +172,11 @@ pub fn gui_draw_image(
 }
 
 pub fn run_main_forever<F: FnMut(&Ui, &mut bool)>(mut update: F) {
-    let writer = SeqWriter::new();
+    init_logging();
 
     let sdl_context = sdl2::init().unwrap();
     let video = sdl_context.video().unwrap();
 
-    let log = fs::File::create("grasp.log").unwrap();
-
-    env_logger::builder()
-        .format(move |_buf, record| {
-            let mut log_writer = BufWriter::new(log.try_clone().unwrap());
-            writer.send(record);
-            log_writer
-                .write_all((record.args().to_string() + "\n").as_bytes())
-                .unwrap();
-
-            let _ = log_writer.flush();
-            Ok(())
-        })
-        .filter_level(log::LevelFilter::Warn)
-        .init();
-
     let app_name = "GRASP";
     let window = video
         .window(app_name, 100, 100)
@@ -88,6 +88,7 @@ pub fn gui_draw_bezier(
     points: [Vec2; 3],
     thickness: f32,
     quality: u32,
+    color: ImColor32,
 ) {
     let ctrlp = gui_bezier_control_point(points[0], points[1], points[2]);
 
@@ -99,7 +100,7 @@ pub fn gui_draw_bezier(
     }
 
     draw_list
-        .add_polyline(ps, ImColor32::WHITE)
+        .add_polyline(ps, color)
         .thickness(thickness)
         .filled(false)
         .build();
@@ -1,7 +1,11 @@
+pub mod diff;
 pub mod errors;
 pub mod grasp_queues;
 pub mod lists;
+pub mod merge;
+pub mod owners;
 pub mod pairs;
+pub mod subgraph;
 
 pub use errors::*;
 pub use grasp_queues::*;
@@ -0,0 +1,395 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::Path,
+    sync::Arc,
+};
+
+use itertools::Itertools;
+use mosaic::internals::{EntityId, Mosaic, MosaicIO};
+
+use crate::{editor_state::network::TileJson, querying::query_tiles::is_query_tile};
+
+use super::{owners::TileOwner, subgraph::load_document};
+
+pub struct FieldDiff {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+pub struct TileChange {
+    pub before: EntityId,
+    pub after: EntityId,
+    pub fields: Vec<FieldDiff>,
+}
+
+pub struct DocumentDiff {
+    pub before: BTreeMap<EntityId, TileJson>,
+    pub after: BTreeMap<EntityId, TileJson>,
+    // before id -> after id
+    pub matched: BTreeMap<EntityId, EntityId>,
+    pub changed: Vec<TileChange>,
+}
+
+fn labels(tiles: &BTreeMap<EntityId, TileJson>) -> BTreeMap<EntityId, String> {
+    tiles
+        .values()
+        .filter(|t| t.component == "Label" && t.kind != "object")
+        .filter_map(|t| Some((t.owner_id(), t.fields.get("self")?.clone())))
+        .collect()
+}
+
+// objects with the same label, component and attached components are taken to be the same
+fn object_signature(
+    tile: &TileJson,
+    tiles: &BTreeMap<EntityId, TileJson>,
+    labels: &BTreeMap<EntityId, String>,
+) -> Option<String> {
+    let label = labels.get(&tile.id).filter(|l| !l.is_empty())?;
+    let parts = tiles
+        .values()
+        .filter(|t| t.kind != "object" && t.kind != "arrow" && t.owner_id() == tile.id)
+        .map(|t| t.component.as_str())
+        .sorted()
+        .join(",");
+
+    Some(format!("{}|{}|{}", tile.component, label, parts))
+}
+
+fn match_by_label(
+    before: &BTreeMap<EntityId, TileJson>,
+    after: &BTreeMap<EntityId, TileJson>,
+    matched: &mut BTreeMap<EntityId, EntityId>,
+) {
+    let (before_labels, after_labels) = (labels(before), labels(after));
+    let unmatched_objects = |tiles: &BTreeMap<EntityId, TileJson>,
+                             labels: &BTreeMap<EntityId, String>,
+                             taken: &HashSet<EntityId>| {
+        tiles
+            .values()
+            .filter(|t| t.kind == "object" && !taken.contains(&t.id))
+            .filter_map(|t| Some((object_signature(t, tiles, labels)?, t.id)))
+            .into_group_map()
+    };
+
+    let taken_before: HashSet<EntityId> = matched.keys().copied().collect();
+    let taken_after: HashSet<EntityId> = matched.values().copied().collect();
+    let after_objects = unmatched_objects(after, &after_labels, &taken_after);
+    for (signature, ids) in unmatched_objects(before, &before_labels, &taken_before) {
+        if let Some(others) = after_objects.get(&signature) {
+            matched.extend(ids.into_iter().zip(others.iter().copied()));
+        }
+    }
+
+    // arrows follow their endpoints, descriptors and extensions follow their owners
+    loop {
+        let count = matched.len();
+        let mut taken_after: HashSet<EntityId> = matched.values().copied().collect();
+        let pending = before
+            .values()
+            .filter(|t| !matched.contains_key(&t.id))
+            .collect_vec();
+
+        for tile in pending {
+            let endpoints = (matched.get(&tile.source), matched.get(&tile.target));
+            let found = after.values().find(|other| {
+                !taken_after.contains(&other.id)
+                    && other.kind == tile.kind
+                    && other.component == tile.component
+                    && match (endpoints, tile.kind.as_str()) {
+                        ((Some(s), Some(t)), "arrow") => other.source == *s && other.target == *t,
+                        ((_, Some(t)), "descriptor") => other.target == *t,
+                        ((Some(s), _), "extension") => other.source == *s,
+                        _ => false,
+                    }
+            });
+
+            if let Some(other) = found {
+                taken_after.insert(other.id);
+                matched.insert(tile.id, other.id);
+            }
+        }
+
+        if matched.len() == count {
+            break;
+        }
+    }
+}
+
+fn field_changes(
+    before: &TileJson,
+    after: &TileJson,
+    matched: &BTreeMap<EntityId, EntityId>,
+) -> Vec<FieldDiff> {
+    let mut changes = before
+        .fields
+        .keys()
+        .chain(after.fields.keys())
+        .unique()
+        .filter_map(|field| {
+            let (old, new) = (before.fields.get(field), after.fields.get(field));
+            (old != new).then(|| FieldDiff {
+                field: field.clone(),
+                before: old.cloned().unwrap_or_default(),
+                after: new.cloned().unwrap_or_default(),
+            })
+        })
+        .collect_vec();
+
+    if before.kind != "object" {
+        for (field, old, new) in [
+            ("source", before.source, after.source),
+            ("target", before.target, after.target),
+        ] {
+            if matched.get(&old) != Some(&new) {
+                changes.push(FieldDiff {
+                    field: field.to_string(),
+                    before: format!("#{}", old),
+                    after: format!("#{}", new),
+                });
+            }
+        }
+    }
+
+    changes
+}
+
+// tiles are matched by id first, and optionally by label and structure after that
+pub fn diff_documents(before: Vec<TileJson>, after: Vec<TileJson>, by_label: bool) -> DocumentDiff {
+    let before: BTreeMap<EntityId, TileJson> = before.into_iter().map(|t| (t.id, t)).collect();
+    let after: BTreeMap<EntityId, TileJson> = after.into_iter().map(|t| (t.id, t)).collect();
+
+    let mut matched: BTreeMap<EntityId, EntityId> = before
+        .values()
+        .filter(|t| {
+            after
+                .get(&t.id)
+                .is_some_and(|o| o.kind == t.kind && o.component == t.component)
+        })
+        .map(|t| (t.id, t.id))
+        .collect();
+
+    if by_label {
+        match_by_label(&before, &after, &mut matched);
+    }
+
+    let changed = matched
+        .iter()
+        .map(|(b, a)| TileChange {
+            before: *b,
+            after: *a,
+            fields: field_changes(&before[b], &after[a], &matched),
+        })
+        .filter(|c| !c.fields.is_empty())
+        .collect_vec();
+
+    DocumentDiff {
+        before,
+        after,
+        matched,
+        changed,
+    }
+}
+
+pub fn document_tiles(mosaic: &Arc<Mosaic>) -> Vec<TileJson> {
+    mosaic
+        .get_all()
+        .filter(|t| !is_query_tile(t))
+        .map(|t| TileJson::from(&t))
+        .collect_vec()
+}
+
+pub fn diff_files(before: &Path, after: &Path, by_label: bool) -> Result<DocumentDiff, String> {
+    Ok(diff_documents(
+        document_tiles(&load_document(before)?),
+        document_tiles(&load_document(after)?),
+        by_label,
+    ))
+}
+
+impl DocumentDiff {
+    pub fn removed(&self) -> Vec<&TileJson> {
+        self.before
+            .values()
+            .filter(|t| !self.matched.contains_key(&t.id))
+            .collect_vec()
+    }
+
+    pub fn added(&self) -> Vec<&TileJson> {
+        let matched: HashSet<&EntityId> = self.matched.values().collect();
+        self.after
+            .values()
+            .filter(|t| !matched.contains(&t.id))
+            .collect_vec()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.removed().is_empty() && self.added().is_empty()
+    }
+
+    pub fn change_of(&self, after: EntityId) -> Option<&TileChange> {
+        self.changed.iter().find(|c| c.after == after)
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} added, {} removed, {} changed",
+            self.added().len(),
+            self.removed().len(),
+            self.changed.len()
+        )
+    }
+
+    pub fn to_text(&self, before_name: &str, after_name: &str) -> String {
+        let describe =
+            |tile: &TileJson, labels: &BTreeMap<EntityId, String>| match tile.kind.as_str() {
+                "object" => match labels.get(&tile.id).filter(|l| !l.is_empty()) {
+                    Some(label) => format!("object #{} {} \"{}\"", tile.id, tile.component, label),
+                    None => format!("object #{} {}", tile.id, tile.component),
+                },
+                "arrow" => format!(
+                    "arrow #{} {} #{} -> #{}",
+                    tile.id, tile.component, tile.source, tile.target
+                ),
+                kind => format!(
+                    "{} #{} {} on #{}",
+                    kind,
+                    tile.id,
+                    tile.component,
+                    tile.owner_id()
+                ),
+            };
+
+        let (before_labels, after_labels) = (labels(&self.before), labels(&self.after));
+        let mut lines = vec![
+            format!("--- {}", before_name),
+            format!("+++ {}", after_name),
+        ];
+
+        for tile in self.removed() {
+            lines.push(format!("- {}", describe(tile, &before_labels)));
+        }
+
+        for tile in self.added() {
+            lines.push(format!("+ {}", describe(tile, &after_labels)));
+        }
+
+        for change in &self.changed {
+            let tile = describe(&self.after[&change.after], &after_labels);
+            if change.before == change.after {
+                lines.push(format!("~ {}", tile));
+            } else {
+                lines.push(format!("~ {} (was #{})", tile, change.before));
+            }
+
+            for field in &change.fields {
+                lines.push(format!(
+                    "    {}: {} -> {}",
+                    field.field, field.before, field.after
+                ));
+            }
+        }
+
+        lines.push(self.summary());
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod diff_tests {
+    use std::collections::BTreeMap;
+
+    use mosaic::internals::EntityId;
+
+    use crate::editor_state::network::TileJson;
+
+    use super::diff_documents;
+
+    fn tile(
+        id: EntityId,
+        kind: &str,
+        component: &str,
+        source: EntityId,
+        target: EntityId,
+        fields: &[(&str, &str)],
+    ) -> TileJson {
+        TileJson {
+            id,
+            kind: kind.to_string(),
+            component: component.to_string(),
+            source,
+            target,
+            fields: fields
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<BTreeMap<_, _>>(),
+        }
+    }
+
+    fn labeled_pair(offset: EntityId, first: &str, second: &str) -> Vec<TileJson> {
+        let (a, b) = (offset, offset + 1);
+        vec![
+            tile(a, "object", "Node", a, a, &[]),
+            tile(b, "object", "Node", b, b, &[]),
+            tile(a + 10, "descriptor", "Label", a, a, &[("self", first)]),
+            tile(b + 10, "descriptor", "Label", b, b, &[("self", second)]),
+            tile(a + 20, "arrow", "Arrow", a, b, &[]),
+        ]
+    }
+
+    #[test]
+    fn test_identical_documents() {
+        let diff = diff_documents(labeled_pair(0, "a", "b"), labeled_pair(0, "a", "b"), false);
+        assert!(diff.is_empty());
+        assert_eq!(5, diff.matched.len());
+    }
+
+    #[test]
+    fn test_added_removed_and_changed() {
+        let before = labeled_pair(0, "a", "b");
+        let mut after = labeled_pair(0, "a", "c");
+        after.retain(|t| t.id != 20);
+        after.push(tile(30, "arrow", "Arrow", 1, 0, &[]));
+
+        let diff = diff_documents(before, after, false);
+        assert_eq!(
+            vec![20],
+            diff.removed().iter().map(|t| t.id).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![30],
+            diff.added().iter().map(|t| t.id).collect::<Vec<_>>()
+        );
+        assert_eq!(1, diff.changed.len());
+        assert_eq!(11, diff.changed[0].after);
+        assert_eq!("b", diff.changed[0].fields[0].before);
+        assert_eq!("c", diff.changed[0].fields[0].after);
+    }
+
+    #[test]
+    fn test_label_fallback_follows_structure() {
+        let before = labeled_pair(0, "a", "b");
+        let after = labeled_pair(100, "a", "b");
+
+        let by_id = diff_documents(before.clone(), after.clone(), false);
+        assert_eq!(5, by_id.removed().len());
+        assert_eq!(5, by_id.added().len());
+
+        let by_label = diff_documents(before, after, true);
+        assert!(by_label.is_empty());
+        assert_eq!(Some(&120), by_label.matched.get(&20));
+    }
+
+    #[test]
+    fn test_text_output() {
+        let before = labeled_pair(0, "a", "b");
+        let mut after = labeled_pair(0, "a", "b");
+        after.push(tile(5, "object", "Node", 5, 5, &[]));
+
+        let text = diff_documents(before, after, false).to_text("old.mos", "new.mos");
+        assert_eq!(
+            "--- old.mos\n+++ new.mos\n+ object #5 Node\n1 added, 0 removed, 0 changed",
+            text
+        );
+    }
+}
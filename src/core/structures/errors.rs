@@ -1,20 +1,82 @@
 use std::sync::Arc;
 
+use iso8601_timestamp::Timestamp;
 use mosaic::internals::{pars, ComponentValuesBuilderSetter, Mosaic, MosaicIO, Tile};
 
+pub const ERROR_TYPE: &str =
+    "Error: { message: str, target: u64, window: u64, severity: s32, source: str, timestamp: str };";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Info, Severity::Warning, Severity::Error];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "Info",
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        }
+    }
+
+    pub fn of(error: &Tile) -> Self {
+        match error.get("severity").as_s32().to_string().as_str() {
+            "Info" => Severity::Info,
+            "Warning" => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
 pub trait ErrorCapability {
-    fn make_error(&self, message: &str, window: Option<Tile>, target: Option<Tile>);
+    fn make_diagnostic(
+        &self,
+        severity: Severity,
+        source: &str,
+        message: &str,
+        window: Option<Tile>,
+        target: Option<Tile>,
+    ) -> Tile;
+
+    fn make_error(&self, source: &str, message: &str, window: Option<Tile>, target: Option<Tile>) {
+        self.make_diagnostic(Severity::Error, source, message, window, target);
+    }
+
+    fn make_warning(
+        &self,
+        source: &str,
+        message: &str,
+        window: Option<Tile>,
+        target: Option<Tile>,
+    ) {
+        self.make_diagnostic(Severity::Warning, source, message, window, target);
+    }
 }
 
 impl ErrorCapability for Arc<Mosaic> {
-    fn make_error(&self, message: &str, window: Option<Tile>, target: Option<Tile>) {
+    fn make_diagnostic(
+        &self,
+        severity: Severity,
+        source: &str,
+        message: &str,
+        window: Option<Tile>,
+        target: Option<Tile>,
+    ) -> Tile {
         self.new_object(
             "Error",
             pars()
                 .set("message", message.to_string())
                 .set("window", window.map(|t| t.id as u64).unwrap_or(0u64))
                 .set("target", target.map(|t| t.id as u64).unwrap_or(0u64))
+                .set("severity", severity.as_str())
+                .set("source", source.to_string())
+                .set("timestamp", Timestamp::now_utc().to_string())
                 .ok(),
-        );
+        )
     }
 }
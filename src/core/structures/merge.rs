@@ -0,0 +1,355 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+    sync::Arc,
+};
+
+use itertools::Itertools;
+use mosaic::internals::{EntityId, Mosaic, MosaicCRUD, MosaicIO, MosaicTypelevelCRUD, Tile, Value};
+
+use crate::{
+    editor_state::network::{has_component_type, TileJson},
+    utilities::set_field_from_str,
+};
+
+use super::{
+    diff::document_tiles,
+    errors::{ErrorCapability, ERROR_TYPE},
+    owners::TileOwner,
+    subgraph::{create_tile, is_reference_field, load_document, references, remap_references},
+};
+
+pub const MERGE_SOURCE: &str = "Merge";
+
+pub struct MergeConflict {
+    // the tile in the merged document, when it is still there
+    pub tile: Option<EntityId>,
+    pub message: String,
+}
+
+#[derive(Default)]
+pub struct MergePlan {
+    pub deleted: Vec<EntityId>,
+    pub fields: Vec<(EntityId, String, String)>,
+    pub added: Vec<TileJson>,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+fn index(tiles: Vec<TileJson>) -> BTreeMap<EntityId, TileJson> {
+    tiles.into_iter().map(|t| (t.id, t)).collect()
+}
+
+// a tile lives on as long as the same kind of tile keeps its id
+fn kept<'a>(side: &'a BTreeMap<EntityId, TileJson>, tile: &TileJson) -> Option<&'a TileJson> {
+    side.get(&tile.id)
+        .filter(|t| t.kind == tile.kind && t.component == tile.component)
+}
+
+fn dependencies(tile: &TileJson) -> Vec<EntityId> {
+    let mut ids = references(tile);
+    if tile.kind != "object" {
+        ids.push(tile.source);
+        ids.push(tile.target);
+    }
+
+    ids
+}
+
+fn describe(tile: &TileJson) -> String {
+    format!("{} #{}", tile.component, tile.owner_id())
+}
+
+impl MergePlan {
+    fn conflict(&mut self, tile: Option<EntityId>, message: String) {
+        self.conflicts.push(MergeConflict { tile, message });
+    }
+}
+
+// our document is the starting point, the plan holds what theirs changed since base
+pub fn plan_merge(base: Vec<TileJson>, ours: Vec<TileJson>, theirs: Vec<TileJson>) -> MergePlan {
+    let (base, ours, theirs) = (index(base), index(ours), index(theirs));
+    let mut plan = MergePlan::default();
+
+    for b in base.values() {
+        match (kept(&ours, b), kept(&theirs, b)) {
+            (Some(o), Some(t)) => {
+                for field in b.fields.keys().chain(t.fields.keys()).unique() {
+                    let (bv, ov, tv) = (
+                        b.fields.get(field),
+                        o.fields.get(field),
+                        t.fields.get(field),
+                    );
+                    if tv == bv || tv == ov {
+                        continue;
+                    }
+
+                    match tv {
+                        Some(value) if ov == bv => {
+                            plan.fields.push((b.id, field.clone(), value.clone()))
+                        }
+                        _ => plan.conflict(
+                            Some(b.id),
+                            format!(
+                                "{}.{} was changed to '{}' here but to '{}' in theirs, kept '{}'",
+                                describe(b),
+                                field,
+                                ov.cloned().unwrap_or_default(),
+                                tv.cloned().unwrap_or_default(),
+                                ov.cloned().unwrap_or_default()
+                            ),
+                        ),
+                    }
+                }
+            }
+            (Some(o), None) => {
+                let extended = ours
+                    .values()
+                    .any(|t| !base.contains_key(&t.id) && dependencies(t).contains(&b.id));
+
+                if o.fields != b.fields {
+                    plan.conflict(
+                        Some(b.id),
+                        format!(
+                            "{} was changed here but deleted in theirs, kept it",
+                            describe(b)
+                        ),
+                    );
+                } else if extended {
+                    plan.conflict(
+                        Some(b.id),
+                        format!(
+                            "{} was deleted in theirs but has new tiles attached here, kept it",
+                            describe(b)
+                        ),
+                    );
+                } else {
+                    plan.deleted.push(b.id);
+                }
+            }
+            (None, Some(t)) if t.fields != b.fields => plan.conflict(
+                None,
+                format!(
+                    "{} was deleted here but changed in theirs to {:?}, left it deleted",
+                    describe(b),
+                    t.fields
+                ),
+            ),
+            _ => {}
+        }
+    }
+
+    let deleted: HashSet<EntityId> = plan.deleted.iter().copied().collect();
+    let present: HashSet<EntityId> = base
+        .values()
+        .filter(|b| kept(&ours, b).is_some() && !deleted.contains(&b.id))
+        .map(|b| b.id)
+        .collect();
+
+    // added tiles may only hang on each other or on tiles we still have
+    let mut added = theirs
+        .values()
+        .filter(|t| kept(&base, t).is_none())
+        .cloned()
+        .collect_vec();
+
+    loop {
+        let ids: HashSet<EntityId> = added.iter().map(|t| t.id).collect();
+        let (keep, dangling): (Vec<TileJson>, Vec<TileJson>) = added.into_iter().partition(|t| {
+            dependencies(t)
+                .iter()
+                .all(|d| ids.contains(d) || present.contains(d))
+        });
+
+        added = keep;
+        if dangling.is_empty() {
+            break;
+        }
+
+        for tile in dangling {
+            plan.conflict(
+                None,
+                format!(
+                    "{} was added in theirs onto tiles that are gone here, left it out",
+                    describe(&tile)
+                ),
+            );
+        }
+    }
+
+    plan.added = added;
+    plan
+}
+
+// applies the plan onto our document and records every conflict as an Error tile in it,
+// fields that cannot be set become conflicts too
+pub fn apply_merge(mosaic: &Arc<Mosaic>, plan: &mut MergePlan) -> Result<(), String> {
+    let added: HashSet<EntityId> = plan.added.iter().map(|t| t.id).collect();
+    let mut created: HashMap<EntityId, Tile> = HashMap::new();
+
+    for tile in plan.added.iter().sorted_by_key(|t| t.id) {
+        let endpoint = |id: EntityId| {
+            if added.contains(&id) {
+                created.get(&id).cloned()
+            } else {
+                mosaic.get(id)
+            }
+        };
+
+        if let Some(new) = create_tile(mosaic, tile, &tile.fields, endpoint) {
+            created.insert(tile.id, new);
+        }
+    }
+
+    remap_references(&created);
+
+    let mut failed = vec![];
+    for (id, field, value) in &plan.fields {
+        if let Some(mut tile) = mosaic.get(*id) {
+            let reference = is_reference_field(&tile.component.to_string(), field);
+            let value = match (tile.get(field), value.parse::<EntityId>()) {
+                (Value::U64(_), Ok(old)) if reference && added.contains(&old) => created
+                    .get(&old)
+                    .map(|t| t.id.to_string())
+                    .unwrap_or_else(|| value.clone()),
+                _ => value.clone(),
+            };
+
+            if let Err(error) = set_field_from_str(&mut tile, field, &value) {
+                failed.push(MergeConflict {
+                    tile: Some(*id),
+                    message: format!(
+                        "{}.{} of #{} could not be merged: {}",
+                        tile.component, field, id, error
+                    ),
+                });
+            }
+        }
+    }
+
+    plan.conflicts.extend(failed);
+
+    for id in &plan.deleted {
+        if mosaic.is_tile_valid(id) {
+            mosaic.delete_tile(*id);
+        }
+    }
+
+    if plan.conflicts.is_empty() {
+        return Ok(());
+    }
+
+    if !has_component_type(mosaic, "Error") {
+        mosaic.new_type(ERROR_TYPE).map_err(|error| {
+            format!("Cannot record merge conflicts in the document: {:?}", error)
+        })?;
+    }
+
+    for conflict in &plan.conflicts {
+        let target = conflict.tile.and_then(|id| mosaic.get(id));
+        mosaic.make_error(MERGE_SOURCE, &conflict.message, None, target);
+    }
+
+    Ok(())
+}
+
+pub fn merge_files(
+    base: &Path,
+    ours: &Path,
+    theirs: &Path,
+) -> Result<(Arc<Mosaic>, MergePlan), String> {
+    let merged = load_document(ours)?;
+    let mut plan = plan_merge(
+        document_tiles(&load_document(base)?),
+        document_tiles(&merged),
+        document_tiles(&load_document(theirs)?),
+    );
+
+    apply_merge(&merged, &mut plan)?;
+    Ok((merged, plan))
+}
+
+#[cfg(test)]
+mod merge_tests {
+    use std::collections::BTreeMap;
+
+    use mosaic::internals::EntityId;
+
+    use crate::editor_state::network::TileJson;
+
+    use super::plan_merge;
+
+    fn tile(id: EntityId, kind: &str, component: &str, owner: EntityId, text: &str) -> TileJson {
+        TileJson {
+            id,
+            kind: kind.to_string(),
+            component: component.to_string(),
+            source: owner,
+            target: owner,
+            fields: BTreeMap::from([("self".to_string(), text.to_string())]),
+        }
+    }
+
+    fn document(labels: &[(EntityId, &str)]) -> Vec<TileJson> {
+        let mut tiles = vec![
+            tile(1, "object", "Node", 1, ""),
+            tile(2, "object", "Node", 2, ""),
+        ];
+        for (id, text) in labels {
+            tiles.push(tile(*id, "descriptor", "Label", id - 10, text));
+        }
+
+        tiles
+    }
+
+    #[test]
+    fn test_changes_from_both_sides() {
+        let base = document(&[(11, "a"), (12, "b")]);
+        let ours = document(&[(11, "x"), (12, "b")]);
+        let theirs = document(&[(11, "a"), (12, "y")]);
+
+        let plan = plan_merge(base, ours, theirs);
+        assert!(plan.conflicts.is_empty());
+        assert_eq!(vec![(12, "self".to_string(), "y".to_string())], plan.fields);
+    }
+
+    #[test]
+    fn test_same_field_conflict() {
+        let base = document(&[(11, "a")]);
+        let ours = document(&[(11, "x")]);
+        let theirs = document(&[(11, "y")]);
+
+        let plan = plan_merge(base, ours, theirs);
+        assert!(plan.fields.is_empty());
+        assert_eq!(1, plan.conflicts.len());
+        assert_eq!(Some(11), plan.conflicts[0].tile);
+    }
+
+    #[test]
+    fn test_deleted_against_modified() {
+        let base = document(&[(11, "a"), (12, "b")]);
+        let ours = document(&[(11, "x"), (12, "b")]);
+        let theirs = document(&[]);
+
+        let plan = plan_merge(base, ours, theirs);
+        assert_eq!(vec![12], plan.deleted);
+        assert_eq!(1, plan.conflicts.len());
+        assert_eq!(Some(11), plan.conflicts[0].tile);
+    }
+
+    #[test]
+    fn test_added_onto_deleted_tile() {
+        let base = document(&[]);
+        let mut ours = document(&[]);
+        ours.retain(|t| t.id != 2);
+        let mut theirs = document(&[(11, "new"), (12, "orphan")]);
+        theirs.push(tile(30, "object", "Node", 30, ""));
+
+        let plan = plan_merge(base, ours, theirs);
+        assert_eq!(
+            vec![11, 30],
+            plan.added.iter().map(|t| t.id).collect::<Vec<_>>()
+        );
+        assert_eq!(1, plan.conflicts.len());
+        assert_eq!(None, plan.conflicts[0].tile);
+    }
+}
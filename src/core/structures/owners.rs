@@ -0,0 +1,30 @@
+use mosaic::internals::{EntityId, Tile};
+
+use crate::editor_state::network::TileJson;
+
+// the tile a descriptor or extension is attached to, objects and arrows own themselves
+pub trait TileOwner {
+    fn owner_id(&self) -> EntityId;
+}
+
+impl TileOwner for Tile {
+    fn owner_id(&self) -> EntityId {
+        if self.is_object() || self.is_arrow() {
+            self.id
+        } else if self.is_descriptor() {
+            self.target_id()
+        } else {
+            self.source_id()
+        }
+    }
+}
+
+impl TileOwner for TileJson {
+    fn owner_id(&self) -> EntityId {
+        match self.kind.as_str() {
+            "descriptor" => self.target,
+            "extension" => self.source,
+            _ => self.id,
+        }
+    }
+}
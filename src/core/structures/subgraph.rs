@@ -0,0 +1,558 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::Path,
+    sync::Arc,
+};
+
+use itertools::Itertools;
+use log::warn;
+use mosaic::{
+    internals::{void, EntityId, Mosaic, MosaicCRUD, MosaicIO, Tile, Value},
+    iterators::tile_getters::TileGetters,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{math::Vec2, structures::errors::ErrorCapability},
+    editor_state::{
+        network::{has_component_type, TileJson},
+        windows::GraspEditorWindow,
+    },
+    querying::query_tiles::is_query_tile,
+    utilities::set_field_from_str,
+    GuiState,
+};
+
+const SUBGRAPH_VERSION: u32 = 1;
+
+// fields holding the id of another tile, a copied tile pointing outside of the copy is left out
+const REFERENCE_FIELDS: [(&str, &str); 9] = [
+    ("ListMember", "self"),
+    ("ListElement", "self"),
+    ("PairElement", "self"),
+    ("Pair", "first"),
+    ("Pair", "second"),
+    ("ProcedureArgument", "value"),
+    ("Selection", "self"),
+    ("Selected", "self"),
+    ("PatternMatchElement", "self"),
+];
+
+#[derive(Serialize, Deserialize)]
+pub struct Subgraph {
+    pub grasp_subgraph: u32,
+    pub tiles: Vec<TileJson>,
+    #[serde(default)]
+    pub types: BTreeMap<String, String>,
+    #[serde(skip)]
+    pub stripped: Vec<TileJson>,
+}
+
+impl Subgraph {
+    pub fn new(mosaic: &Arc<Mosaic>, tiles: Vec<TileJson>) -> Subgraph {
+        let mut subgraph = Subgraph {
+            grasp_subgraph: SUBGRAPH_VERSION,
+            tiles,
+            types: BTreeMap::new(),
+            stripped: vec![],
+        };
+
+        subgraph.stripped = subgraph.retain(|_| true);
+        subgraph.types = subgraph
+            .components()
+            .into_iter()
+            .filter_map(|c| Some((c.clone(), component_signature(mosaic, &c)?)))
+            .collect();
+        subgraph
+    }
+
+    // every tile of a document but its saved queries, e.g. one loaded from a file
+    pub fn from_mosaic(mosaic: &Arc<Mosaic>) -> Subgraph {
+        Subgraph::new(
+            mosaic,
+            mosaic
+                .get_all()
+                .filter(|t| !is_query_tile(t))
+                .map(|t| TileJson::from(&t))
+                .collect_vec(),
+        )
+    }
+
+    // also drops whatever was attached to, or pointing at, a removed tile, and returns all of them
+    pub fn retain<F: Fn(&TileJson) -> bool>(&mut self, keep: F) -> Vec<TileJson> {
+        let (mut tiles, mut removed): (Vec<TileJson>, Vec<TileJson>) =
+            self.tiles.drain(..).partition(&keep);
+
+        loop {
+            let ids: HashSet<EntityId> = tiles.iter().map(|t| t.id).collect();
+            let (kept, dangling): (Vec<TileJson>, Vec<TileJson>) =
+                tiles.into_iter().partition(|t| {
+                    references(t).iter().all(|r| ids.contains(r))
+                        && (t.kind == "object"
+                            || (ids.contains(&t.target) && ids.contains(&t.source)))
+                });
+
+            tiles = kept;
+            if dangling.is_empty() {
+                break;
+            }
+
+            removed.extend(dangling);
+        }
+
+        self.tiles = tiles;
+        removed
+    }
+
+    pub fn components(&self) -> Vec<String> {
+        self.tiles
+            .iter()
+            .map(|t| t.component.clone())
+            .unique()
+            .sorted()
+            .collect_vec()
+    }
+
+    pub fn bounds(&self) -> Option<(Vec2, Vec2)> {
+        bounds(
+            &self
+                .tiles
+                .iter()
+                .filter(|t| t.component == "Position")
+                .filter_map(position)
+                .collect_vec(),
+        )
+    }
+
+    pub fn to_text(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn from_text(text: &str) -> Option<Subgraph> {
+        serde_json::from_str::<Subgraph>(text)
+            .ok()
+            .filter(|s| s.grasp_subgraph == SUBGRAPH_VERSION)
+    }
+}
+
+// the field names and datatypes of a component, as registered in the mosaic
+pub fn component_signature(mosaic: &Arc<Mosaic>, component: &str) -> Option<String> {
+    if !has_component_type(mosaic, component) {
+        return None;
+    }
+
+    mosaic
+        .component_registry
+        .get_component_type(component.into())
+        .into_iter()
+        .next()
+        .map(|comp| {
+            comp.get_fields()
+                .iter()
+                .map(|f| format!("{}: {:?}", f.name, f.datatype))
+                .join(", ")
+        })
+}
+
+pub fn is_reference_field(component: &str, field: &str) -> bool {
+    REFERENCE_FIELDS.contains(&(component, field))
+}
+
+pub fn references(tile: &TileJson) -> Vec<EntityId> {
+    REFERENCE_FIELDS
+        .iter()
+        .filter(|(component, _)| *component == tile.component)
+        .filter_map(|(_, field)| tile.fields.get(*field)?.parse::<EntityId>().ok())
+        .collect_vec()
+}
+
+// the selected objects, the arrows among them and everything attached to both
+pub fn capture_subgraph(selected: &[Tile]) -> Subgraph {
+    let mut tiles: BTreeMap<EntityId, Tile> = selected
+        .iter()
+        .filter(|t| t.is_object() && !is_query_tile(t))
+        .map(|t| (t.id, t.clone()))
+        .collect();
+
+    let arrows = selected
+        .iter()
+        .flat_map(|t| t.iter().get_arrows_from().collect_vec())
+        .collect_vec();
+
+    loop {
+        let count = tiles.len();
+        for arrow in &arrows {
+            if tiles.contains_key(&arrow.source_id()) && tiles.contains_key(&arrow.target_id()) {
+                tiles.insert(arrow.id, arrow.clone());
+            }
+        }
+
+        if tiles.len() == count {
+            break;
+        }
+    }
+
+    let mut pending = tiles.values().cloned().collect_vec();
+    while let Some(tile) = pending.pop() {
+        for part in tile
+            .iter()
+            .get_descriptors()
+            .chain(tile.iter().get_extensions())
+        {
+            if !tiles.contains_key(&part.id) {
+                tiles.insert(part.id, part.clone());
+                pending.push(part);
+            }
+        }
+    }
+
+    match selected.first() {
+        Some(first) => Subgraph::new(
+            &first.mosaic,
+            tiles.values().map(TileJson::from).collect_vec(),
+        ),
+        None => Subgraph {
+            grasp_subgraph: SUBGRAPH_VERSION,
+            tiles: vec![],
+            types: BTreeMap::new(),
+            stripped: vec![],
+        },
+    }
+}
+
+// a .mos file carries its own type definitions, so it loads into a bare mosaic
+pub fn load_document(file: &Path) -> Result<Arc<Mosaic>, String> {
+    let content = fs::read(file).map_err(|e| format!("Cannot read {}: {}", file.display(), e))?;
+
+    let mosaic = Mosaic::new();
+    mosaic
+        .load(&content)
+        .map_err(|_| format!("Cannot read {}: invalid format", file.display()))?;
+
+    Ok(mosaic)
+}
+
+pub fn load_subgraph(file: &Path) -> Result<Subgraph, String> {
+    load_document(file).map(|mosaic| Subgraph::from_mosaic(&mosaic))
+}
+
+fn position(tile: &TileJson) -> Option<Vec2> {
+    Some(Vec2::new(
+        tile.fields.get("x")?.parse().ok()?,
+        tile.fields.get("y")?.parse().ok()?,
+    ))
+}
+
+pub fn bounds(points: &[Vec2]) -> Option<(Vec2, Vec2)> {
+    let first = *points.first()?;
+    Some(points.iter().fold((first, first), |(min, max), p| {
+        (
+            Vec2::new(min.x.min(p.x), min.y.min(p.y)),
+            Vec2::new(max.x.max(p.x), max.y.max(p.y)),
+        )
+    }))
+}
+
+// returns the pasted objects, positions are moved so that their center lands on `at`
+pub fn paste_subgraph(mosaic: &Arc<Mosaic>, subgraph: &Subgraph, at: Vec2) -> Vec<Tile> {
+    let positions = subgraph
+        .tiles
+        .iter()
+        .filter(|t| t.component == "Position")
+        .filter_map(position)
+        .collect_vec();
+
+    let offset = if positions.is_empty() {
+        Vec2::ZERO
+    } else {
+        let sum = positions.iter().fold(Vec2::ZERO, |acc, p| acc + *p);
+        at - sum * (1.0 / positions.len() as f32)
+    };
+
+    place_subgraph(mosaic, subgraph, offset)
+}
+
+// returns the created objects, every position is moved by `offset`
+pub fn place_subgraph(mosaic: &Arc<Mosaic>, subgraph: &Subgraph, offset: Vec2) -> Vec<Tile> {
+    let mut created: HashMap<EntityId, Tile> = HashMap::new();
+    let mut objects = vec![];
+
+    for copied in subgraph.tiles.iter().sorted_by_key(|t| t.id) {
+        let mut fields = copied.fields.clone();
+        if copied.component == "Position" {
+            if let Some(p) = position(copied) {
+                fields.insert("x".to_string(), (p.x + offset.x).to_string());
+                fields.insert("y".to_string(), (p.y + offset.y).to_string());
+            }
+        }
+
+        let Some(tile) = create_tile(mosaic, copied, &fields, |id| created.get(&id).cloned())
+        else {
+            continue;
+        };
+
+        if tile.is_object() {
+            objects.push(tile.clone());
+        }
+
+        created.insert(copied.id, tile);
+    }
+
+    remap_references(&created);
+    objects
+}
+
+// `endpoint` tells where the copied tile's source or target ended up
+pub fn create_tile<F: Fn(EntityId) -> Option<Tile>>(
+    mosaic: &Arc<Mosaic>,
+    copied: &TileJson,
+    fields: &BTreeMap<String, String>,
+    endpoint: F,
+) -> Option<Tile> {
+    if !has_component_type(mosaic, &copied.component) {
+        warn!(
+            "Cannot create tile {}: unknown component '{}'",
+            copied.id, copied.component
+        );
+        return None;
+    }
+
+    let component = copied.component.as_str();
+    let tile = match copied.kind.as_str() {
+        "object" => mosaic.new_object(component, void()),
+        "arrow" => mosaic.new_arrow(
+            &endpoint(copied.source)?,
+            &endpoint(copied.target)?,
+            component,
+            void(),
+        ),
+        "descriptor" => mosaic.new_descriptor(&endpoint(copied.target)?, component, void()),
+        _ => mosaic.new_extension(&endpoint(copied.source)?, component, void()),
+    };
+
+    for (field, value) in fields {
+        if let Err(error) = set_field_from_str(&mut tile.clone(), field, value) {
+            warn!("Cannot create tile {}: {}", copied.id, error);
+        }
+    }
+
+    Some(tile)
+}
+
+// references to copied tiles now have to point at the tiles created for them
+pub fn remap_references(created: &HashMap<EntityId, Tile>) {
+    for tile in created.values() {
+        for (_, field) in REFERENCE_FIELDS
+            .iter()
+            .filter(|(component, _)| tile.component.is(component))
+        {
+            if let Value::U64(old) = tile.get(field) {
+                if let Some(new) = created.get(&(old as EntityId)) {
+                    let _ = set_field_from_str(&mut tile.clone(), field, &new.id.to_string());
+                }
+            }
+        }
+    }
+}
+
+impl GraspEditorWindow {
+    // tiles of a component that is unknown here, or has other fields here, are reported and left out
+    pub fn drop_conflicting_types(&self, subgraph: &mut Subgraph, source: &str) {
+        let conflicts = subgraph
+            .types
+            .iter()
+            .filter_map(|(component, theirs)| {
+                match component_signature(&self.document_mosaic, component) {
+                    None => Some((
+                        component.clone(),
+                        format!("Component '{}' is not defined in this document", component),
+                    )),
+                    Some(ours) if &ours != theirs => Some((
+                        component.clone(),
+                        format!(
+                            "Component '{}' is {{ {} }} here but {{ {} }} in the inserted tiles",
+                            component, ours, theirs
+                        ),
+                    )),
+                    _ => None,
+                }
+            })
+            .collect_vec();
+
+        for (_, message) in &conflicts {
+            self.editor_mosaic
+                .make_error(source, message, Some(self.window_tile.clone()), None);
+        }
+
+        let conflicting: HashSet<String> = conflicts.into_iter().map(|(c, _)| c).collect();
+        subgraph.retain(|t| !conflicting.contains(&t.component));
+    }
+
+    pub fn copy_selection(&self, s: &GuiState) {
+        let subgraph = capture_subgraph(&self.editor_data.selected);
+        if !subgraph.tiles.is_empty() {
+            s.ui.set_clipboard_text(subgraph.to_text());
+        }
+    }
+
+    pub fn cut_selection(&mut self, s: &GuiState) {
+        self.copy_selection(s);
+        let selected = self.editor_data.selected.drain(..).collect_vec();
+        self.delete_tiles(&selected);
+        self.mark_changed();
+    }
+
+    pub fn paste_clipboard(&mut self, s: &GuiState) {
+        let Some(mut subgraph) = s.ui.clipboard_text().and_then(|t| Subgraph::from_text(&t)) else {
+            return;
+        };
+
+        self.drop_conflicting_types(&mut subgraph, "Paste");
+
+        let cursor =
+            self.editor_data.cursor - self.editor_data.window_offset - self.editor_data.pan;
+        self.editor_data.selected = paste_subgraph(&self.document_mosaic, &subgraph, cursor);
+        self.mark_changed();
+        self.request_quadtree_update();
+    }
+}
+
+#[cfg(test)]
+mod subgraph_tests {
+    use itertools::Itertools;
+    use mosaic::{
+        internals::{
+            par, pars, void, ComponentValuesBuilderSetter, Mosaic, MosaicCRUD, MosaicIO,
+            MosaicTypelevelCRUD, Tile,
+        },
+        iterators::tile_getters::TileGetters,
+    };
+
+    use crate::core::math::Vec2;
+
+    use super::{capture_subgraph, load_document, place_subgraph, Subgraph};
+
+    fn graph_mosaic() -> std::sync::Arc<Mosaic> {
+        let mosaic = Mosaic::new();
+        for definition in [
+            "Node: unit;",
+            "Arrow: unit;",
+            "List: unit;",
+            "ListMember: u64;",
+            "Pair: { first: u64, second: u64 };",
+        ] {
+            mosaic.new_type(definition).unwrap();
+        }
+        mosaic
+    }
+
+    fn list_of(mosaic: &std::sync::Arc<Mosaic>, members: &[&Tile]) -> Tile {
+        let list = mosaic.new_object("List", void());
+        for member in members {
+            mosaic.new_extension(&list, "ListMember", par(member.id as u64));
+        }
+        list
+    }
+
+    #[test]
+    fn test_copy_remaps_list_and_pair() {
+        let mosaic = graph_mosaic();
+        let a = mosaic.new_object("Node", void());
+        let b = mosaic.new_object("Node", void());
+        let list = list_of(&mosaic, &[&a, &b]);
+        let pair = mosaic.new_object(
+            "Pair",
+            pars()
+                .set("first", a.id as u64)
+                .set("second", b.id as u64)
+                .ok(),
+        );
+
+        let subgraph = capture_subgraph(&[a.clone(), b.clone(), list.clone(), pair.clone()]);
+        assert!(subgraph.stripped.is_empty());
+
+        let created = place_subgraph(&mosaic, &subgraph, Vec2::ZERO);
+        assert_eq!(4, created.len());
+
+        let old = [a.id, b.id, list.id, pair.id];
+        assert!(created.iter().all(|t| !old.contains(&t.id)));
+
+        let (new_a, new_b, new_list, new_pair) =
+            (&created[0], &created[1], &created[2], &created[3]);
+        let members = new_list
+            .iter()
+            .get_extensions()
+            .map(|m| m.get("self").as_u64() as usize)
+            .sorted()
+            .collect_vec();
+        assert_eq!(vec![new_a.id, new_b.id], members);
+        assert_eq!(new_a.id as u64, new_pair.get("first").as_u64());
+        assert_eq!(new_b.id as u64, new_pair.get("second").as_u64());
+    }
+
+    #[test]
+    fn test_member_outside_selection_is_stripped() {
+        let mosaic = graph_mosaic();
+        let a = mosaic.new_object("Node", void());
+        let b = mosaic.new_object("Node", void());
+        let list = list_of(&mosaic, &[&a, &b]);
+
+        let subgraph = capture_subgraph(&[b.clone(), list.clone()]);
+        let members = subgraph
+            .tiles
+            .iter()
+            .filter(|t| t.component == "ListMember")
+            .map(|t| t.fields["self"].clone())
+            .collect_vec();
+        assert_eq!(vec![b.id.to_string()], members);
+        assert_eq!(1, subgraph.stripped.len());
+        assert_eq!(a.id.to_string(), subgraph.stripped[0].fields["self"]);
+    }
+
+    #[test]
+    fn test_arrow_without_endpoint_is_dropped() {
+        let mosaic = graph_mosaic();
+        let a = mosaic.new_object("Node", void());
+        let b = mosaic.new_object("Node", void());
+        let arrow = mosaic.new_arrow(&a, &b, "Arrow", void());
+
+        let mut subgraph = Subgraph::from_mosaic(&mosaic);
+        let removed = subgraph.retain(|t| t.id != b.id);
+
+        assert_eq!(
+            vec![a.id],
+            subgraph.tiles.iter().map(|t| t.id).collect_vec()
+        );
+        assert!(removed.iter().any(|t| t.id == arrow.id));
+        assert!(capture_subgraph(&[a])
+            .tiles
+            .iter()
+            .all(|t| t.id != arrow.id));
+    }
+
+    #[test]
+    fn test_saved_document_loads_with_its_types() {
+        let mosaic = Mosaic::new();
+        mosaic
+            .new_type("Custom: { weight: u64, name: str };")
+            .unwrap();
+        let tile = mosaic.new_object(
+            "Custom",
+            pars()
+                .set("weight", 7u64)
+                .set("name", "custom".to_string())
+                .ok(),
+        );
+
+        let file = std::env::temp_dir().join(format!("grasp-document-{}.mos", std::process::id()));
+        std::fs::write(&file, mosaic.save()).unwrap();
+        let loaded = load_document(&file);
+        let _ = std::fs::remove_file(&file);
+
+        let loaded = loaded.unwrap().get(tile.id).unwrap();
+        assert!(loaded.component.is("Custom"));
+        assert_eq!(7, loaded.get("weight").as_u64());
+        assert_eq!("custom", loaded.get("name").as_str());
+    }
+}
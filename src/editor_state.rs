@@ -1,10 +1,24 @@
+pub mod api;
 pub mod categories;
+pub mod collaboration;
+pub mod commands;
+pub mod diff;
+pub mod errors;
 pub mod file_operations;
+pub mod find;
 pub mod foundation;
 pub mod helpers;
+pub mod keymap;
+pub mod logs;
+pub mod metrics;
 pub mod network;
+pub mod outline;
+pub mod palette;
+pub mod query;
+pub mod recovery;
 pub mod selection;
 pub mod sense;
 pub mod snapshot;
+pub mod undo;
 pub mod view;
 pub mod windows;
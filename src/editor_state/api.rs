@@ -0,0 +1,369 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use mosaic::internals::{EntityId, MosaicIO, TileFieldEmptyQuery};
+use mosaic::iterators::component_selectors::ComponentSelectors;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use warp::http::StatusCode;
+use warp::{Filter, Rejection, Reply};
+
+use super::foundation::GraspEditorState;
+use super::network::{tile_map, Networked, TileJson};
+use crate::querying::{
+    collage::{Collage, MosaicCollage},
+    collage_parser::parse_collage,
+};
+
+lazy_static! {
+    static ref API_REQUESTS: Mutex<VecDeque<ApiRequest>> = Mutex::new(VecDeque::new());
+    static ref API_SNAPSHOT: RwLock<ApiSnapshot> = RwLock::new(ApiSnapshot::default());
+}
+
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    fn not_found(id: usize) -> Self {
+        ApiError {
+            status: StatusCode::NOT_FOUND,
+            message: format!("mosaic {} is not open", id),
+        }
+    }
+}
+
+// only queries need the live mosaic, the UI thread answers them with the matched ids
+pub struct ApiRequest {
+    pub mosaic: usize,
+    pub collage: Box<Collage>,
+    pub reply: oneshot::Sender<Result<Vec<EntityId>, ApiError>>,
+}
+
+// taken by the UI thread when something changed, the handlers serialize it on their own
+#[derive(Default)]
+struct ApiSnapshot {
+    windows: Arc<Vec<WindowJson>>,
+    mosaics: HashMap<usize, MosaicSnapshot>,
+    errors: Arc<Vec<ErrorJson>>,
+}
+
+struct MosaicSnapshot {
+    // the revision of a document window, the tile count of the editor's own mosaics
+    version: u64,
+    tiles: Arc<Vec<TileJson>>,
+}
+
+#[derive(Serialize, PartialEq)]
+struct WindowJson {
+    id: usize,
+    name: String,
+    mosaic: usize,
+    path: String,
+}
+
+#[derive(Serialize)]
+struct ErrorJson {
+    id: usize,
+    message: String,
+    window: u64,
+    target: u64,
+    severity: String,
+    source: String,
+    timestamp: String,
+}
+
+#[derive(Deserialize)]
+struct QueryParams {
+    q: String,
+}
+
+#[derive(Serialize)]
+struct ErrorReply {
+    error: String,
+}
+
+fn error_reply(status: StatusCode, message: String) -> warp::reply::Response {
+    warp::reply::with_status(warp::reply::json(&ErrorReply { error: message }), status)
+        .into_response()
+}
+
+fn dequeue_api_request() -> Option<ApiRequest> {
+    API_REQUESTS.lock().unwrap().pop_front()
+}
+
+// the lock is only held to clone the Arc, never while serializing
+fn read_snapshot<T>(read: impl FnOnce(&ApiSnapshot) -> T) -> T {
+    read(&API_SNAPSHOT.read().unwrap())
+}
+
+fn snapshot_tiles(id: usize) -> Option<Arc<Vec<TileJson>>> {
+    read_snapshot(|snapshot| snapshot.mosaics.get(&id).map(|m| Arc::clone(&m.tiles)))
+}
+
+async fn windows() -> Result<warp::reply::Response, Rejection> {
+    let windows = read_snapshot(|snapshot| Arc::clone(&snapshot.windows));
+    Ok(warp::reply::json(windows.as_ref()).into_response())
+}
+
+async fn errors() -> Result<warp::reply::Response, Rejection> {
+    let errors = read_snapshot(|snapshot| Arc::clone(&snapshot.errors));
+    Ok(warp::reply::json(errors.as_ref()).into_response())
+}
+
+async fn tiles(id: usize) -> Result<warp::reply::Response, Rejection> {
+    Ok(match snapshot_tiles(id) {
+        Some(tiles) => warp::reply::json(tiles.as_ref()).into_response(),
+        None => error_reply(StatusCode::NOT_FOUND, ApiError::not_found(id).message),
+    })
+}
+
+// answered by the UI thread between frames, the handler only waits on the matched ids
+async fn query(id: usize, text: String) -> Result<warp::reply::Response, Rejection> {
+    let collage = match parse_collage(&text) {
+        Ok(collage) => collage,
+        Err(error) => return Ok(error_reply(StatusCode::BAD_REQUEST, error.to_string())),
+    };
+
+    let (reply, response) = oneshot::channel();
+    API_REQUESTS.lock().unwrap().push_back(ApiRequest {
+        mosaic: id,
+        collage,
+        reply,
+    });
+
+    Ok(match response.await {
+        Ok(Ok(ids)) => match snapshot_tiles(id) {
+            Some(tiles) => {
+                let ids: HashSet<EntityId> = ids.into_iter().collect();
+                let matched = tiles.iter().filter(|t| ids.contains(&t.id)).collect_vec();
+                warp::reply::json(&matched).into_response()
+            }
+            None => error_reply(StatusCode::NOT_FOUND, ApiError::not_found(id).message),
+        },
+        Ok(Err(error)) => error_reply(error.status, error.message),
+        Err(_) => error_reply(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "the editor is shutting down".to_string(),
+        ),
+    })
+}
+
+// browsers cannot put headers on a websocket, so the token may also come as ?token=
+pub fn authorized(token: String) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and(warp::query::<HashMap<String, String>>())
+        .and_then(
+            move |header: Option<String>, query: HashMap<String, String>| {
+                let token = token.clone();
+                async move {
+                    let given = header
+                        .as_deref()
+                        .and_then(|h| h.strip_prefix("Bearer "))
+                        .or(query.get("token").map(|t| t.as_str()))
+                        .unwrap_or_default();
+
+                    if token.is_empty() || given == token {
+                        Ok(())
+                    } else {
+                        Err(warp::reject::custom(Unauthorized))
+                    }
+                }
+            },
+        )
+        .untuple_one()
+}
+
+// native clients such as a joined Grasp send no origin, browsers always do
+pub fn is_same_origin(origin: Option<&str>, host: Option<&str>) -> bool {
+    match origin {
+        None => true,
+        Some(origin) => {
+            let origin = origin.split_once("://").map_or(origin, |(_, rest)| rest);
+            host.is_some_and(|host| origin.eq_ignore_ascii_case(host))
+        }
+    }
+}
+
+pub fn same_origin() -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>("origin")
+        .and(warp::header::optional::<String>("host"))
+        .and_then(|origin: Option<String>, host: Option<String>| async move {
+            if is_same_origin(origin.as_deref(), host.as_deref()) {
+                Ok(())
+            } else {
+                Err(warp::reject::custom(ForeignOrigin))
+            }
+        })
+        .untuple_one()
+}
+
+#[derive(Debug)]
+struct Unauthorized;
+
+impl warp::reject::Reject for Unauthorized {}
+
+#[derive(Debug)]
+struct ForeignOrigin;
+
+impl warp::reject::Reject for ForeignOrigin {}
+
+pub async fn recover_api(rejection: Rejection) -> Result<warp::reply::Response, Rejection> {
+    if rejection.find::<Unauthorized>().is_some() {
+        Ok(error_reply(
+            StatusCode::UNAUTHORIZED,
+            "missing or wrong api token".to_string(),
+        ))
+    } else if rejection.find::<ForeignOrigin>().is_some() {
+        Ok(error_reply(
+            StatusCode::FORBIDDEN,
+            "requests are only accepted from the server's own pages".to_string(),
+        ))
+    } else {
+        Err(rejection)
+    }
+}
+
+pub fn api_routes(
+    token: String,
+) -> impl Filter<Extract = (warp::reply::Response,), Error = Rejection> + Clone {
+    let windows = warp::path!("windows").and_then(windows);
+
+    let tiles = warp::path!("mosaic" / usize / "tiles").and_then(tiles);
+
+    let query = warp::path!("mosaic" / usize / "query")
+        .and(warp::query::<QueryParams>())
+        .and_then(|id, params: QueryParams| query(id, params.q));
+
+    let errors = warp::path!("errors").and_then(errors);
+
+    warp::path("api")
+        .and(warp::get())
+        .and(same_origin())
+        .and(authorized(token))
+        .and(
+            windows
+                .or(tiles)
+                .unify()
+                .or(query)
+                .unify()
+                .or(errors)
+                .unify(),
+        )
+}
+
+impl GraspEditorState {
+    pub fn process_api_request_queue(&mut self) {
+        // taken first so every id a query matches is in the snapshot it is read from
+        self.refresh_api_snapshot();
+
+        while let Some(request) = dequeue_api_request() {
+            let matched = self
+                .networked_mosaics()
+                .into_iter()
+                .find(|(_, mosaic)| mosaic.id == request.mosaic)
+                .map(|(_, mosaic)| {
+                    mosaic
+                        .apply_collage(&request.collage, None)
+                        .map(|t| t.id)
+                        .collect_vec()
+                })
+                .ok_or(ApiError::not_found(request.mosaic));
+            let _ = request.reply.send(matched);
+        }
+    }
+
+    fn refresh_api_snapshot(&self) {
+        let revisions: HashMap<usize, u64> = self
+            .window_list
+            .windows
+            .iter()
+            .map(|w| (w.document_mosaic.id, w.revision))
+            .collect();
+
+        let windows = self
+            .window_list
+            .windows
+            .iter()
+            .sorted_by_key(|w| w.window_list_index)
+            .map(|w| WindowJson {
+                id: w.window_tile.id,
+                name: w.name.clone(),
+                mosaic: w.document_mosaic.id,
+                path: w.document_mosaic.get_key().path(),
+            })
+            .collect_vec();
+
+        let mosaics = self.networked_mosaics();
+        let (stale, windows_changed) = {
+            let snapshot = API_SNAPSHOT.read().unwrap();
+            let stale = mosaics
+                .iter()
+                .filter_map(|(_, mosaic)| {
+                    let version = revisions
+                        .get(&mosaic.id)
+                        .copied()
+                        .unwrap_or_else(|| mosaic.get_all().len() as u64);
+                    let current = snapshot.mosaics.get(&mosaic.id).map(|m| m.version);
+                    (current != Some(version)).then(|| (Arc::clone(mosaic), version))
+                })
+                .collect_vec();
+            (stale, *snapshot.windows != windows)
+        };
+
+        if stale.is_empty() && !windows_changed {
+            return;
+        }
+
+        let errors = stale
+            .iter()
+            .any(|(mosaic, _)| mosaic.id == self.editor_mosaic.id)
+            .then(|| self.error_json());
+        let refreshed = stale
+            .into_iter()
+            .map(|(mosaic, version)| {
+                let tiles = tile_map(&mosaic)
+                    .into_values()
+                    .sorted_by_key(|t| t.id)
+                    .collect_vec();
+                (
+                    mosaic.id,
+                    MosaicSnapshot {
+                        version,
+                        tiles: Arc::new(tiles),
+                    },
+                )
+            })
+            .collect_vec();
+
+        let mut snapshot = API_SNAPSHOT.write().unwrap();
+        snapshot.windows = Arc::new(windows);
+        snapshot
+            .mosaics
+            .retain(|id, _| mosaics.iter().any(|(_, mosaic)| mosaic.id == *id));
+        snapshot.mosaics.extend(refreshed);
+        if let Some(errors) = errors {
+            snapshot.errors = Arc::new(errors);
+        }
+    }
+
+    fn error_json(&self) -> Vec<ErrorJson> {
+        self.editor_mosaic
+            .get_all()
+            .include_component("Error")
+            .sorted_by_key(|e| e.id)
+            .map(|e| ErrorJson {
+                id: e.id,
+                message: e.get("message").as_str(),
+                window: e.get("window").as_u64(),
+                target: e.get("target").as_u64(),
+                severity: e.get("severity").as_s32().to_string(),
+                source: e.get("source").as_str(),
+                timestamp: e.get("timestamp").as_str(),
+            })
+            .collect_vec()
+    }
+}
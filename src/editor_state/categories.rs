@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Default, Debug, Clone, Deserialize, Serialize)]
@@ -5,4 +7,6 @@ pub struct ComponentCategory {
     pub name: String,
     pub components: Vec<String>,
     pub hidden: bool,
+    #[serde(default)]
+    pub bounds: HashMap<String, (f32, f32)>,
 }
@@ -0,0 +1,774 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use futures::{SinkExt, StreamExt};
+use imgui::ImColor32;
+use itertools::Itertools;
+use log::warn;
+use mosaic::internals::{void, EntityId, Mosaic, MosaicCRUD, MosaicIO, MosaicTypelevelCRUD, Tile};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use super::foundation::GraspEditorState;
+use super::network::{
+    has_component_type, publish_event, tile_map, ClientMessage, NetworkKey, Networked,
+    ServerMessage, TileJson,
+};
+use super::query::{is_on_canvas, tile_screen_position};
+use super::windows::GraspEditorWindow;
+use crate::core::math::Vec2;
+use crate::transformers::palette_color;
+use crate::utilities::set_field_from_str;
+use crate::GuiState;
+
+pub const HOST_CLIENT: usize = 0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Participant {
+    pub client: usize,
+    pub name: String,
+    pub color: [f32; 4],
+    pub cursor: [f32; 2],
+    pub selection: Vec<EntityId>,
+}
+
+#[derive(Default)]
+pub struct HostedSession {
+    pub participants: BTreeMap<usize, Participant>,
+    dirty: bool,
+}
+
+enum SessionEvent {
+    Message(ServerMessage),
+    Closed(String),
+}
+
+pub struct JoinedSession {
+    pub url: String,
+    pub status: String,
+    pub client: Option<usize>,
+    pub participants: Vec<Participant>,
+    outgoing: mpsc::UnboundedSender<String>,
+    incoming: Arc<Mutex<VecDeque<SessionEvent>>>,
+    synced: bool,
+    remote_to_local: HashMap<EntityId, EntityId>,
+    local_to_remote: HashMap<EntityId, EntityId>,
+    // the last state of every bound tile that the host is known to have
+    known: HashMap<EntityId, TileJson>,
+    // every message but subscribe is answered in order, creations remember what they sent
+    awaiting: VecDeque<Option<TileJson>>,
+    ignored: HashSet<EntityId>,
+    presence: Option<([f32; 2], Vec<EntityId>)>,
+    // local changes are only looked for when the window revision moved on,
+    // or when a reply may have unblocked tiles waiting for their endpoints
+    scanned_revision: Option<u64>,
+    rescan: bool,
+}
+
+#[derive(Default)]
+pub struct Collaboration {
+    pub hosted: HashMap<NetworkKey, HostedSession>,
+    pub joined: HashMap<EntityId, JoinedSession>,
+    pub join_address: String,
+}
+
+fn participant_name() -> String {
+    whoami::username()
+}
+
+fn canvas_cursor(window: &GraspEditorWindow) -> [f32; 2] {
+    let cursor =
+        window.editor_data.cursor - window.editor_data.window_offset - window.editor_data.pan;
+    [cursor.x, cursor.y]
+}
+
+// a host with an api token is joined as 192.168.0.10:9000/mosaic/3?token=...
+fn join_url(address: &str) -> String {
+    let (address, query) = match address.trim().split_once('?') {
+        Some((address, query)) => (address, format!("?{}", query)),
+        None => (address.trim(), String::new()),
+    };
+
+    let address = address.trim_end_matches('/');
+    let address = if address.contains("://") {
+        address.to_string()
+    } else {
+        format!("ws://{}", address)
+    };
+
+    if address.ends_with("/ws") {
+        format!("{}{}", address, query)
+    } else {
+        format!("{}/ws{}", address, query)
+    }
+}
+
+fn connect(
+    url: String,
+    incoming: Arc<Mutex<VecDeque<SessionEvent>>>,
+) -> mpsc::UnboundedSender<String> {
+    let (outgoing, mut to_send) = mpsc::unbounded_channel::<String>();
+
+    tokio::spawn(async move {
+        let push = |event| incoming.lock().unwrap().push_back(event);
+
+        let socket = match connect_async(url.as_str()).await {
+            Ok((socket, _)) => socket,
+            Err(error) => {
+                push(SessionEvent::Closed(error.to_string()));
+                return;
+            }
+        };
+
+        let (mut sender, mut receiver) = socket.split();
+        loop {
+            tokio::select! {
+                message = to_send.recv() => match message {
+                    Some(text) => {
+                        if sender.send(Message::Text(text)).await.is_err() {
+                            break;
+                        }
+                    }
+                    None => break,
+                },
+                message = receiver.next() => match message {
+                    // plain DOT text arrives before subscribing and is not needed here
+                    Some(Ok(Message::Text(text))) => {
+                        if let Ok(message) = serde_json::from_str::<ServerMessage>(&text) {
+                            push(SessionEvent::Message(message));
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+
+        let _ = sender.close().await;
+        push(SessionEvent::Closed("connection closed".to_string()));
+    });
+
+    outgoing
+}
+
+impl JoinedSession {
+    pub fn connect(url: String) -> Self {
+        let incoming = Arc::new(Mutex::new(VecDeque::new()));
+        let outgoing = connect(url.clone(), Arc::clone(&incoming));
+        let _ = outgoing.send(serde_json::to_string(&ClientMessage::Subscribe).unwrap());
+
+        Self::new(url, outgoing, incoming)
+    }
+
+    fn new(
+        url: String,
+        outgoing: mpsc::UnboundedSender<String>,
+        incoming: Arc<Mutex<VecDeque<SessionEvent>>>,
+    ) -> Self {
+        JoinedSession {
+            url,
+            status: "Connecting".to_string(),
+            client: None,
+            participants: vec![],
+            outgoing,
+            incoming,
+            synced: false,
+            remote_to_local: HashMap::new(),
+            local_to_remote: HashMap::new(),
+            known: HashMap::new(),
+            awaiting: VecDeque::new(),
+            ignored: HashSet::new(),
+            presence: None,
+            scanned_revision: None,
+            rescan: false,
+        }
+    }
+
+    fn send(&mut self, message: ClientMessage, created: Option<TileJson>) {
+        self.awaiting.push_back(created);
+        let _ = self.outgoing.send(serde_json::to_string(&message).unwrap());
+    }
+
+    fn bind(&mut self, local: EntityId, remote: EntityId) {
+        self.local_to_remote.insert(local, remote);
+        self.remote_to_local.insert(remote, local);
+    }
+
+    fn receive(&mut self, window: &mut GraspEditorWindow) {
+        let events = self.incoming.lock().unwrap().drain(..).collect_vec();
+        for event in events {
+            match event {
+                SessionEvent::Message(message) => self.apply(window, message),
+                SessionEvent::Closed(reason) => {
+                    self.status = format!("Disconnected: {}", reason);
+                    self.synced = false;
+                    self.client = None;
+                    self.participants.clear();
+                }
+            }
+        }
+    }
+
+    fn apply(&mut self, window: &mut GraspEditorWindow, message: ServerMessage) {
+        match message {
+            ServerMessage::Welcome { client } => {
+                self.client = Some(client);
+                self.status = format!("Connected as #{}", client);
+            }
+            ServerMessage::Snapshot { tiles } => {
+                let remote_ids = tiles.iter().map(|t| t.id).collect::<HashSet<_>>();
+                let stale = self
+                    .remote_to_local
+                    .keys()
+                    .filter(|id| !remote_ids.contains(id))
+                    .copied()
+                    .collect_vec();
+
+                for remote in stale {
+                    self.delete_local(window, remote);
+                }
+
+                self.apply_remote_tiles(window, tiles);
+                self.synced = true;
+                self.rescan = true;
+            }
+            ServerMessage::Created { tile } | ServerMessage::Updated { tile } => {
+                self.apply_remote_tiles(window, vec![tile]);
+            }
+            ServerMessage::Deleted { id } => self.delete_local(window, id),
+            ServerMessage::Done { tile } => {
+                if let (Some(Some(sent)), Some(remote)) = (self.awaiting.pop_front(), tile) {
+                    self.bind(sent.id, remote);
+                    self.known.insert(sent.id, sent);
+                    self.rescan = true;
+                }
+            }
+            ServerMessage::Error { message } => {
+                if let Some(Some(sent)) = self.awaiting.pop_front() {
+                    warn!("Host rejected tile {}: {}", sent.id, message);
+                    self.ignored.insert(sent.id);
+                }
+                self.status = message;
+            }
+            ServerMessage::Presence { participants } => self.participants = participants,
+            ServerMessage::Dot { .. } => {}
+        }
+    }
+
+    fn apply_remote_tiles(&mut self, window: &mut GraspEditorWindow, tiles: Vec<TileJson>) {
+        let mut pending = tiles.into_iter().sorted_by_key(|t| t.id).collect_vec();
+        loop {
+            let count = pending.len();
+            pending.retain(|tile| !self.apply_remote_tile(window, tile));
+            if pending.is_empty() || pending.len() == count {
+                break;
+            }
+        }
+
+        for tile in pending {
+            warn!("Cannot mirror tile {}: its endpoints are missing", tile.id);
+        }
+
+        window.request_quadtree_update();
+    }
+
+    // returns false when the tile has to wait for its endpoints
+    fn apply_remote_tile(&mut self, window: &GraspEditorWindow, remote: &TileJson) -> bool {
+        let mosaic = &window.document_mosaic;
+
+        let bound = self
+            .remote_to_local
+            .get(&remote.id)
+            .and_then(|local| mosaic.get(*local));
+
+        if let Some(local) = bound {
+            if self.known.get(&local.id).map(|k| &k.fields) != Some(&remote.fields) {
+                set_fields(&local, &remote.fields);
+                self.known.insert(local.id, TileJson::from(&local));
+            }
+            return true;
+        }
+
+        if !has_component_type(mosaic, &remote.component) {
+            warn!(
+                "Cannot mirror tile {}: unknown component '{}'",
+                remote.id, remote.component
+            );
+            return true;
+        }
+
+        let endpoint = |id: EntityId| {
+            self.remote_to_local
+                .get(&id)
+                .and_then(|local| mosaic.get(*local))
+        };
+
+        let component = remote.component.as_str();
+        let created = match remote.kind.as_str() {
+            "object" => mosaic.new_object(component, void()),
+            "arrow" => match (endpoint(remote.source), endpoint(remote.target)) {
+                (Some(source), Some(target)) => {
+                    mosaic.new_arrow(&source, &target, component, void())
+                }
+                _ => return false,
+            },
+            "descriptor" => match endpoint(remote.target) {
+                Some(target) => mosaic.new_descriptor(&target, component, void()),
+                None => return false,
+            },
+            "extension" => match endpoint(remote.source) {
+                Some(source) => mosaic.new_extension(&source, component, void()),
+                None => return false,
+            },
+            _ => return true,
+        };
+
+        set_fields(&created, &remote.fields);
+        self.bind(created.id, remote.id);
+        self.known.insert(created.id, TileJson::from(&created));
+        true
+    }
+
+    fn delete_local(&mut self, window: &GraspEditorWindow, remote: EntityId) {
+        if let Some(local) = self.remote_to_local.remove(&remote) {
+            self.local_to_remote.remove(&local);
+            self.known.remove(&local);
+            self.ignored.insert(local);
+
+            if let Some(tile) = window.document_mosaic.get(local) {
+                window.delete_tiles(&[tile]);
+                window.request_quadtree_update();
+            }
+        }
+    }
+
+    fn send_local_changes(&mut self, mosaic: &Arc<Mosaic>, revision: u64) {
+        if !self.synced || (self.scanned_revision == Some(revision) && !self.rescan) {
+            return;
+        }
+
+        self.scanned_revision = Some(revision);
+        self.rescan = false;
+
+        let current = tile_map(mosaic);
+        self.ignored.retain(|id| current.contains_key(id));
+
+        let removed = self
+            .local_to_remote
+            .keys()
+            .filter(|local| !current.contains_key(local))
+            .copied()
+            .collect_vec();
+
+        for local in removed {
+            let remote = self.local_to_remote.remove(&local).unwrap();
+            self.remote_to_local.remove(&remote);
+            self.known.remove(&local);
+            self.send(ClientMessage::Delete { tile: remote }, None);
+        }
+
+        let in_flight = self
+            .awaiting
+            .iter()
+            .flatten()
+            .map(|t| t.id)
+            .collect::<HashSet<_>>();
+
+        for tile in current.into_values().sorted_by_key(|t| t.id) {
+            if self.ignored.contains(&tile.id) || in_flight.contains(&tile.id) {
+                continue;
+            }
+
+            if let Some(remote) = self.local_to_remote.get(&tile.id).copied() {
+                let known = self.known.get(&tile.id).map(|k| k.fields.clone());
+                for (field, value) in &tile.fields {
+                    if known.as_ref().and_then(|k| k.get(field)) != Some(value) {
+                        self.send(
+                            ClientMessage::SetField {
+                                tile: remote,
+                                field: field.clone(),
+                                value: value.clone(),
+                            },
+                            None,
+                        );
+                    }
+                }
+
+                self.known.insert(tile.id, tile);
+                continue;
+            }
+
+            // tiles that hang off others wait until the host knows their endpoints
+            let remote_of = |id: EntityId| {
+                if tile.kind == "object" {
+                    Some(0)
+                } else {
+                    self.local_to_remote.get(&id).copied()
+                }
+            };
+
+            if let (Some(source), Some(target)) = (remote_of(tile.source), remote_of(tile.target)) {
+                let remote = TileJson {
+                    source,
+                    target,
+                    ..tile.clone()
+                };
+                self.send(ClientMessage::Create { tile: remote }, Some(tile));
+            }
+        }
+    }
+
+    fn send_presence(&mut self, window: &GraspEditorWindow) {
+        if self.client.is_none() {
+            return;
+        }
+
+        let cursor = canvas_cursor(window);
+        let selection = window
+            .editor_data
+            .selected
+            .iter()
+            .filter_map(|t| self.local_to_remote.get(&t.id).copied())
+            .collect_vec();
+
+        let presence = (cursor, selection);
+        if self.presence.as_ref() != Some(&presence) {
+            self.presence = Some(presence.clone());
+            self.send(
+                ClientMessage::Presence {
+                    name: participant_name(),
+                    cursor: presence.0,
+                    selection: presence.1,
+                },
+                None,
+            );
+        }
+    }
+}
+
+fn set_fields(tile: &Tile, fields: &BTreeMap<String, String>) {
+    for (field, value) in fields {
+        if let Err(error) = set_field_from_str(&mut tile.clone(), field, value) {
+            warn!(
+                "Cannot mirror field {} of tile {}: {}",
+                field, tile.id, error
+            );
+        }
+    }
+}
+
+impl Collaboration {
+    pub fn update_participant(
+        &mut self,
+        key: NetworkKey,
+        client: usize,
+        name: &str,
+        cursor: [f32; 2],
+        selection: &[EntityId],
+    ) -> Result<(), String> {
+        let session = self
+            .hosted
+            .get_mut(&key)
+            .ok_or(format!("{} is not hosted", key.path()))?;
+
+        let participant = Participant {
+            client,
+            name: name.to_string(),
+            color: palette_color(client),
+            cursor,
+            selection: selection.to_vec(),
+        };
+
+        if session.participants.get(&client) != Some(&participant) {
+            session.participants.insert(client, participant);
+            session.dirty = true;
+        }
+
+        Ok(())
+    }
+
+    pub fn remove_participant(&mut self, key: NetworkKey, client: usize) {
+        if let Some(session) = self.hosted.get_mut(&key) {
+            session.dirty |= session.participants.remove(&client).is_some();
+        }
+    }
+
+    pub fn draw_participants(&self, window: &GraspEditorWindow, s: &GuiState) {
+        let others = if let Some(session) = self.hosted.get(&window.document_mosaic.get_key()) {
+            session
+                .participants
+                .values()
+                .filter(|p| p.client != HOST_CLIENT)
+                .cloned()
+                .collect_vec()
+        } else if let Some(session) = self.joined.get(&window.window_tile.id) {
+            session
+                .participants
+                .iter()
+                .filter(|p| Some(p.client) != session.client)
+                .map(|p| Participant {
+                    selection: p
+                        .selection
+                        .iter()
+                        .filter_map(|id| session.remote_to_local.get(id).copied())
+                        .collect_vec(),
+                    ..p.clone()
+                })
+                .collect_vec()
+        } else {
+            return;
+        };
+
+        let painter = s.ui.get_window_draw_list();
+        for participant in others {
+            let [r, g, b, _] = participant.color;
+            let color = ImColor32::from_rgba_f32s(r, g, b, 0.9);
+
+            let cursor = window.get_position_with_offset_and_pan(Vec2::new(
+                participant.cursor[0],
+                participant.cursor[1],
+            ));
+            painter
+                .add_triangle(
+                    [cursor.x, cursor.y],
+                    [cursor.x, cursor.y + 16.0],
+                    [cursor.x + 11.0, cursor.y + 11.0],
+                    color,
+                )
+                .filled(true)
+                .build();
+            painter.add_text([cursor.x + 12.0, cursor.y + 12.0], color, &participant.name);
+
+            for id in participant.selection {
+                if let Some(tile) = window.document_mosaic.get(id).filter(is_on_canvas) {
+                    let pos = tile_screen_position(window, &tile);
+                    painter
+                        .add_circle([pos.x, pos.y], 17.0, color)
+                        .thickness(2.0)
+                        .build();
+                }
+            }
+        }
+    }
+}
+
+impl GraspEditorState {
+    pub fn host_window(&mut self, window_tile: &Tile) {
+        if let Some(window) = self
+            .window_list
+            .windows
+            .iter()
+            .find(|w| &w.window_tile == window_tile)
+        {
+            // windows only register Node once one is placed, guests may send one first
+            window.document_mosaic.new_type("Node: unit;").unwrap();
+            let key = window.document_mosaic.get_key();
+            self.collaboration.hosted.entry(key).or_default();
+        }
+    }
+
+    pub fn stop_hosting(&mut self, window_tile: &Tile) {
+        if let Some(window) = self
+            .window_list
+            .windows
+            .iter()
+            .find(|w| &w.window_tile == window_tile)
+        {
+            let key = window.document_mosaic.get_key();
+            if self.collaboration.hosted.remove(&key).is_some() {
+                publish_event(
+                    key,
+                    &ServerMessage::Presence {
+                        participants: vec![],
+                    },
+                );
+            }
+        }
+    }
+
+    pub fn join_session(&mut self, address: &str) {
+        self.new_window(None);
+        let window = self.window_list.windows.front().unwrap();
+        window.document_mosaic.new_type("Node: unit;").unwrap();
+        self.collaboration.joined.insert(
+            window.window_tile.id,
+            JoinedSession::connect(join_url(address)),
+        );
+    }
+
+    pub fn leave_session(&mut self, window_tile: &Tile) {
+        self.collaboration.joined.remove(&window_tile.id);
+    }
+
+    pub fn process_collaboration(&mut self) {
+        for window in self.window_list.windows.iter_mut() {
+            if let Some(session) = self.collaboration.joined.get_mut(&window.window_tile.id) {
+                session.receive(window);
+                session.send_local_changes(&window.document_mosaic, window.revision);
+                session.send_presence(window);
+            }
+        }
+    }
+
+    pub fn publish_presence(&mut self) {
+        for (key, session) in self.collaboration.hosted.iter_mut() {
+            if let Some(window) = self
+                .window_list
+                .windows
+                .iter()
+                .find(|w| w.document_mosaic.get_key() == *key)
+            {
+                let host = Participant {
+                    client: HOST_CLIENT,
+                    name: participant_name(),
+                    color: palette_color(HOST_CLIENT),
+                    cursor: canvas_cursor(window),
+                    selection: window
+                        .editor_data
+                        .selected
+                        .iter()
+                        .map(|t| t.id)
+                        .collect_vec(),
+                };
+
+                if session.participants.get(&HOST_CLIENT) != Some(&host) {
+                    session.participants.insert(HOST_CLIENT, host);
+                    session.dirty = true;
+                }
+            }
+
+            if session.dirty {
+                session.dirty = false;
+                publish_event(
+                    *key,
+                    &ServerMessage::Presence {
+                        participants: session.participants.values().cloned().collect_vec(),
+                    },
+                );
+            }
+        }
+    }
+
+    pub fn show_session_menu(&mut self, s: &GuiState) {
+        let windows = self
+            .window_list
+            .windows
+            .iter()
+            .filter(|w| !self.collaboration.joined.contains_key(&w.window_tile.id))
+            .sorted_by_key(|w| w.window_list_index)
+            .map(|w| {
+                let key = w.document_mosaic.get_key();
+                (
+                    w.window_tile.clone(),
+                    w.name.clone(),
+                    key,
+                    self.collaboration.hosted.contains_key(&key),
+                )
+            })
+            .collect_vec();
+
+        for (window_tile, name, key, hosting) in windows {
+            let on = if hosting { "X" } else { " " };
+            if s.menu_item(&format!("[{}] Host {}", on, name)) {
+                if hosting {
+                    self.stop_hosting(&window_tile);
+                } else {
+                    self.host_window(&window_tile);
+                }
+            }
+
+            if hosting {
+                s.ui.text_disabled(format!(
+                    "join at {}:{}/{}",
+                    self.editor_options.network_address,
+                    self.editor_options.network_port,
+                    key.path()
+                ));
+            }
+        }
+
+        s.separator();
+
+        s.ui.input_text(
+            "Address##session-join",
+            &mut self.collaboration.join_address,
+        )
+        .hint("192.168.0.10:9000/mosaic/3")
+        .build();
+
+        if s.menu_item("Join") && !self.collaboration.join_address.trim().is_empty() {
+            let address = self.collaboration.join_address.clone();
+            self.join_session(&address);
+        }
+
+        let joined = self
+            .window_list
+            .windows
+            .iter()
+            .filter_map(|w| {
+                self.collaboration
+                    .joined
+                    .get(&w.window_tile.id)
+                    .map(|session| {
+                        (
+                            w.window_tile.clone(),
+                            w.name.clone(),
+                            session.url.clone(),
+                            session.status.clone(),
+                        )
+                    })
+            })
+            .collect_vec();
+
+        if !joined.is_empty() {
+            s.separator();
+        }
+
+        for (window_tile, name, url, status) in joined {
+            s.ui.text_disabled(format!("{}: {} ({})", name, url, status));
+            if s.menu_item(&format!("Leave##session-{}", window_tile.id)) {
+                self.leave_session(&window_tile);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod collaboration_tests {
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    use mosaic::internals::{void, Mosaic, MosaicCRUD, MosaicIO};
+    use tokio::sync::mpsc;
+
+    use crate::editor_state::network::{ClientMessage, TileJson};
+
+    use super::JoinedSession;
+
+    #[test]
+    fn test_local_delete_reaches_host() {
+        let mosaic = Mosaic::new();
+        let node = mosaic.new_object("void", void());
+
+        let (outgoing, mut sent) = mpsc::unbounded_channel();
+        let incoming = Arc::new(Mutex::new(VecDeque::new()));
+        let mut session = JoinedSession::new("ws://host/ws".to_string(), outgoing, incoming);
+        session.synced = true;
+        session.bind(node.id, 40);
+        session.known.insert(node.id, TileJson::from(&node));
+
+        session.send_local_changes(&mosaic, 1);
+        assert!(sent.try_recv().is_err());
+
+        // the delete queue moves the revision on, without it the deletion is never looked for
+        mosaic.delete_tile(node.id);
+        session.send_local_changes(&mosaic, 1);
+        assert!(sent.try_recv().is_err());
+
+        session.send_local_changes(&mosaic, 2);
+        let message = serde_json::from_str::<ClientMessage>(&sent.try_recv().unwrap()).unwrap();
+        assert_eq!(ClientMessage::Delete { tile: 40 }, message);
+        assert!(sent.try_recv().is_err());
+    }
+}
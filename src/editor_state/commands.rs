@@ -0,0 +1,358 @@
+use itertools::Itertools;
+use mosaic::internals::{pars, void, ComponentValuesBuilderSetter, EntityId, MosaicIO};
+
+use crate::{
+    core::{
+        gui::windowing::gui_set_window_focus,
+        structures::{errors::ErrorCapability, grasp_queues},
+    },
+    editor_state_machine::{EditorState, EditorStateTrigger, StateMachine},
+    grasp_queues::{CloseWindowRequestQueue, WindowTransformerQueue},
+    transformers::TransformerUtilities,
+    GuiState,
+};
+
+use super::{
+    file_operations::SaveFileCapability,
+    foundation::GraspEditorState,
+    keymap::{Keymap, KEYMAP_FILE},
+    windows::GraspEditorWindow,
+};
+
+pub const TRANSFORMER_COMMAND_PREFIX: &str = "transformer:";
+
+pub struct Command {
+    pub id: String,
+    pub title: String,
+    // commands a focused text field needs for itself are not taken by the keymap while typing
+    pub while_typing: bool,
+}
+
+const BUILTIN_COMMANDS: [(&str, &str, bool); 31] = [
+    ("document.new_window", "Document: New Window", true),
+    ("document.open", "Document: Open", true),
+    ("document.import", "Document: Import", true),
+    ("document.insert", "Document: Insert from file...", true),
+    (
+        "document.insert_group",
+        "Document: Insert from file as group...",
+        true,
+    ),
+    ("document.save", "Document: Save", true),
+    ("document.save_as", "Document: Save As", true),
+    (
+        "document.save_selection",
+        "Document: Save Selection As...",
+        true,
+    ),
+    ("document.create_node", "Document: Create Node", true),
+    ("document.exit", "Document: Exit", true),
+    ("window.close", "Window: Close", true),
+    ("view.tab_view", "View: Show Tab View", true),
+    ("view.ruler", "View: Toggle Ruler", true),
+    ("view.debug_draw", "View: Toggle Debug Draw", true),
+    ("view.grid", "View: Toggle Grid", true),
+    ("view.dominators", "View: Toggle Dominator Arrows", true),
+    ("windows.hierarchy", "Windows: Hierarchy", true),
+    ("windows.properties", "Windows: Properties", true),
+    ("windows.errors", "Windows: Errors", true),
+    ("windows.metrics", "Windows: Metrics", true),
+    ("windows.query", "Windows: Query", true),
+    ("windows.log", "Windows: Log", true),
+    ("windows.find", "Windows: Find", true),
+    ("edit.find", "Edit: Find", true),
+    ("edit.undo", "Edit: Undo", false),
+    ("edit.copy", "Edit: Copy", false),
+    ("edit.cut", "Edit: Cut", false),
+    ("edit.paste", "Edit: Paste", false),
+    ("snapshot.take", "Snapshot: Take", true),
+    ("snapshot.update", "Snapshot: Update", true),
+    ("palette.open", "Command Palette", true),
+];
+
+pub fn transformer_command(name: &str) -> Command {
+    Command {
+        id: format!("{}{}", TRANSFORMER_COMMAND_PREFIX, name),
+        title: format!("Transformer: {}", name),
+        while_typing: true,
+    }
+}
+
+impl GraspEditorState {
+    pub fn commands(&self) -> Vec<Command> {
+        BUILTIN_COMMANDS
+            .iter()
+            .map(|(id, title, while_typing)| Command {
+                id: id.to_string(),
+                title: title.to_string(),
+                while_typing: *while_typing,
+            })
+            .chain(
+                self.transformer_functions
+                    .keys()
+                    .sorted()
+                    .map(|name| transformer_command(name)),
+            )
+            .collect_vec()
+    }
+
+    // the keymap is only checked once the transformers are in, they are commands as well
+    pub fn load_keymap(&mut self) {
+        let (keymap, mut errors) = Keymap::load();
+        let commands = self.commands();
+        for (_, binding) in &keymap.bindings {
+            if !commands.iter().any(|c| c.id == binding.command) {
+                errors.push(format!(
+                    "Unknown command '{}' bound to {}",
+                    binding.command, binding.chord
+                ));
+            }
+        }
+
+        for error in errors {
+            self.editor_mosaic.make_error(
+                "Keymap",
+                &format!("{}: {}", KEYMAP_FILE, error),
+                None,
+                None,
+            );
+        }
+
+        self.keymap = keymap;
+    }
+
+    pub fn process_keymap(&mut self, s: &GuiState) {
+        let io = s.ui.io();
+        let pressed = self
+            .keymap
+            .pressed(&io.keys_down, io.key_ctrl, io.key_shift, io.key_alt);
+        if pressed.is_empty() {
+            return;
+        }
+
+        let commands = self.commands();
+        for id in pressed {
+            let allowed = commands
+                .iter()
+                .find(|c| c.id == id)
+                .is_some_and(|c| c.while_typing || !io.want_text_input);
+
+            if allowed {
+                self.run_command(s, &id);
+            }
+        }
+    }
+
+    pub fn run_command(&mut self, s: &GuiState, id: &str) {
+        self.run_command_in(s, id, None);
+    }
+
+    // window commands act on `target`, or on the focused window without one
+    pub fn run_command_in(&mut self, s: &GuiState, id: &str, target: Option<EntityId>) {
+        if let Some(name) = id.strip_prefix(TRANSFORMER_COMMAND_PREFIX) {
+            self.run_transformer(name, target);
+            return;
+        }
+
+        let options = &mut self.editor_options;
+        match id {
+            "document.new_window" => self.new_window(None),
+            "document.open" => self.open_files(),
+            "document.import" => self.import_files(),
+            "document.insert" => self.insert_files(false),
+            "document.insert_group" => self.insert_files(true),
+            "document.save" => {
+                if let Some(window) = self.window_list.get_by_id_or_focused_mut(target) {
+                    window.save_file();
+                }
+            }
+            "document.save_as" => {
+                if let Some(window) = self.window_list.get_by_id_or_focused_mut(target) {
+                    window.save_file_as();
+                }
+            }
+            "document.save_selection" => {
+                if let Some(window) = self.window_list.get_by_id_or_focused_mut(target) {
+                    window.save_selection_as();
+                }
+            }
+            "document.create_node" => {
+                if let Some(window) = self.window_list.get_by_id_or_focused_mut(target) {
+                    // picked from the context menu, the node goes where the menu was opened
+                    let at = if window.state == EditorState::ContextMenu {
+                        window.editor_data.popup_cursor
+                    } else {
+                        window.editor_data.cursor
+                    };
+
+                    window.create_new_object(
+                        at - window.editor_data.window_offset - window.editor_data.pan,
+                    );
+                    window.mark_changed();
+                }
+            }
+            "document.exit" => s.exit(),
+            "window.close" => {
+                // the close request acts on the focused window
+                if let Some(id) = target {
+                    self.focus_window(id);
+                }
+
+                if !self.window_list.windows.is_empty()
+                    && grasp_queues::is_empty(CloseWindowRequestQueue, &self.editor_mosaic)
+                {
+                    let request = self.editor_mosaic.new_object("CloseWindowRequest", void());
+                    grasp_queues::enqueue(CloseWindowRequestQueue, request);
+                }
+            }
+            "view.tab_view" => {
+                options.toggle_tabview = !options.toggle_tabview;
+                options.save();
+            }
+            "view.ruler" => {
+                if let Some(window) = self.window_list.get_by_id_or_focused_mut(target) {
+                    window.ruler_visible = !window.ruler_visible;
+                }
+            }
+            "view.debug_draw" => {
+                if let Some(window) = self.window_list.get_by_id_or_focused_mut(target) {
+                    window.editor_data.debug = !window.editor_data.debug;
+                }
+            }
+            "view.grid" => {
+                if let Some(window) = self.window_list.get_by_id_or_focused_mut(target) {
+                    window.grid_visible = !window.grid_visible;
+                }
+            }
+            "view.dominators" => {
+                if let Some(window) = self.window_list.get_by_id_or_focused_mut(target) {
+                    window.dominators_visible = !window.dominators_visible;
+                }
+            }
+            "windows.hierarchy" => {
+                options.toggle_hierarchy = !options.toggle_hierarchy;
+                options.save();
+            }
+            "windows.properties" => {
+                options.toggle_properties = !options.toggle_properties;
+                options.save();
+            }
+            "windows.errors" => {
+                options.toggle_errors = !options.toggle_errors;
+                options.save();
+            }
+            "windows.metrics" => {
+                options.toggle_metrics = !options.toggle_metrics;
+                options.save();
+            }
+            "windows.query" => {
+                options.toggle_query = !options.toggle_query;
+                options.save();
+            }
+            "windows.log" => {
+                options.toggle_log = !options.toggle_log;
+                options.save();
+            }
+            "windows.find" => {
+                options.toggle_find = !options.toggle_find;
+                options.save();
+            }
+            "edit.find" => {
+                options.toggle_find = true;
+                self.find_panel.focus_query = true;
+            }
+            "edit.undo" => self.undo(),
+            "edit.copy" => {
+                if let Some(window) = self.window_list.get_by_id_or_focused_mut(target) {
+                    window.copy_selection(s);
+                }
+            }
+            "edit.cut" => {
+                if let Some(window) = self.window_list.get_by_id_or_focused_mut(target) {
+                    window.cut_selection(s);
+                }
+            }
+            "edit.paste" => {
+                if let Some(window) = self.window_list.get_by_id_or_focused_mut(target) {
+                    window.paste_clipboard(s);
+                }
+            }
+            "snapshot.take" => self.snapshot_all("SNAPSHOT"),
+            "snapshot.update" => self.update_snapshot_all("SNAPSHOT"),
+            "palette.open" => self.command_palette.open(),
+            _ => self.editor_mosaic.make_error(
+                "Commands",
+                &format!("Unknown command '{}'", id),
+                None,
+                None,
+            ),
+        }
+    }
+
+    fn focus_window(&mut self, id: EntityId) {
+        let windows = &mut self.window_list.windows;
+        if let Some(pos) = windows.iter().position(|w| w.window_tile.id == id) {
+            let window = windows.remove(pos).unwrap();
+            gui_set_window_focus(&window.name);
+            windows.push_front(window);
+        }
+    }
+
+    fn run_transformer(&mut self, name: &str, target: Option<EntityId>) {
+        if !self.transformer_functions.contains_key(name)
+            || self.editor_mosaic.is_transformer_pending()
+        {
+            return;
+        }
+
+        let Some(window) = self.window_list.get_by_id_or_focused_mut(target) else {
+            return;
+        };
+
+        let request = self.editor_mosaic.new_object(
+            "WindowTransformerRequest",
+            pars()
+                .set("transform", name)
+                .set("window_index", window.window_tile.id as u64)
+                .ok(),
+        );
+
+        window.trigger(EditorStateTrigger::TransformerSelected);
+        grasp_queues::enqueue(WindowTransformerQueue, request);
+    }
+
+    // menu entries go through the same commands, showing the chord they are bound to
+    pub fn command_menu_item(&mut self, s: &GuiState, label: &str, id: &str) {
+        if command_menu_entry(s, &self.keymap, label, id) {
+            self.run_command(s, id);
+        }
+    }
+
+    // commands picked from the menus of a window, run once every window is drawn
+    pub fn run_pending_window_commands(&mut self, s: &GuiState) {
+        let pending = self
+            .window_list
+            .windows
+            .iter_mut()
+            .filter_map(|w| Some((w.pending_command.take()?, w.window_tile.id)))
+            .collect_vec();
+
+        for (id, window) in pending {
+            self.run_command_in(s, &id, Some(window));
+        }
+    }
+}
+
+fn command_menu_entry(s: &GuiState, keymap: &Keymap, label: &str, id: &str) -> bool {
+    let shortcut = keymap.chord_for(id).unwrap_or_default();
+    s.ui.menu_item_config(label).shortcut(shortcut).build()
+}
+
+impl GraspEditorWindow {
+    // the window cannot reach the editor state while it is drawn, the command is run afterwards
+    pub fn command_menu_item(&mut self, s: &GuiState, keymap: &Keymap, label: &str, id: &str) {
+        if command_menu_entry(s, keymap, label, id) {
+            self.pending_command = Some(id.to_string());
+        }
+    }
+}
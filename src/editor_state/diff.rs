@@ -0,0 +1,194 @@
+use std::collections::{HashMap, HashSet};
+
+use imgui::ImColor32;
+use itertools::Itertools;
+use mosaic::internals::{EntityId, MosaicIO, Tile};
+
+use crate::{
+    core::{
+        math::Vec2,
+        structures::{diff::DocumentDiff, owners::TileOwner},
+    },
+    GuiState,
+};
+
+use super::{
+    network::TileJson,
+    query::{is_on_canvas, tile_screen_position},
+    windows::GraspEditorWindow,
+};
+
+const ADDED_COLOR: [f32; 4] = [0.3, 0.85, 0.4, 1.0];
+const REMOVED_COLOR: [f32; 4] = [1.0, 0.35, 0.35, 0.5];
+const CHANGED_COLOR: [f32; 4] = [1.0, 0.8, 0.3, 1.0];
+
+// the window's document is the "after" side, the compared file the "before" side
+pub struct DiffOverlay {
+    pub name: String,
+    pub diff: DocumentDiff,
+    // worked out once, the overlay is drawn every frame
+    removed: Vec<TileJson>,
+    added: HashSet<EntityId>,
+    changed_owners: Vec<EntityId>,
+    before_positions: HashMap<EntityId, Vec2>,
+}
+
+impl DiffOverlay {
+    pub fn new(name: String, diff: DocumentDiff) -> Self {
+        let removed = diff
+            .removed()
+            .into_iter()
+            .filter(|t| t.kind == "object" || t.kind == "arrow")
+            .cloned()
+            .collect_vec();
+        let added = diff.added().iter().map(|t| t.id).collect();
+        let changed_owners = diff
+            .changed
+            .iter()
+            .map(|c| diff.after[&c.after].owner_id())
+            .unique()
+            .collect_vec();
+
+        // where each tile of the compared file used to be, in canvas coordinates
+        let before_positions = diff
+            .before
+            .values()
+            .filter(|t| t.component == "Position" && t.kind != "object")
+            .filter_map(|p| {
+                let x = p.fields.get("x")?.parse().ok()?;
+                let y = p.fields.get("y")?.parse().ok()?;
+                Some((p.owner_id(), Vec2::new(x, y)))
+            })
+            .collect();
+
+        DiffOverlay {
+            name,
+            diff,
+            removed,
+            added,
+            changed_owners,
+            before_positions,
+        }
+    }
+}
+
+fn color(c: [f32; 4]) -> ImColor32 {
+    ImColor32::from_rgba_f32s(c[0], c[1], c[2], c[3])
+}
+
+fn current_tile(window: &GraspEditorWindow, id: EntityId) -> Option<Tile> {
+    window.document_mosaic.get(id).filter(is_on_canvas)
+}
+
+// a removed arrow is drawn to the current position of its endpoints when they still exist
+fn before_screen_position(
+    window: &GraspEditorWindow,
+    overlay: &DiffOverlay,
+    id: EntityId,
+) -> Option<Vec2> {
+    match overlay
+        .diff
+        .matched
+        .get(&id)
+        .and_then(|a| current_tile(window, *a))
+    {
+        Some(tile) => Some(tile_screen_position(window, &tile)),
+        None => overlay
+            .before_positions
+            .get(&id)
+            .map(|p| window.get_position_with_offset_and_pan(*p)),
+    }
+}
+
+pub fn draw_diff_overlay(window: &GraspEditorWindow, s: &GuiState) {
+    let Some(overlay) = &window.diff_overlay else {
+        return;
+    };
+
+    let painter = s.ui.get_window_draw_list();
+
+    for removed in &overlay.removed {
+        if removed.kind == "object" {
+            if let Some(pos) = overlay.before_positions.get(&removed.id) {
+                let pos = window.get_position_with_offset_and_pan(*pos);
+                painter
+                    .add_circle([pos.x, pos.y], 10.0, color(REMOVED_COLOR))
+                    .filled(true)
+                    .build();
+            }
+        } else if let (Some(p1), Some(p2)) = (
+            before_screen_position(window, overlay, removed.source),
+            before_screen_position(window, overlay, removed.target),
+        ) {
+            painter
+                .add_line([p1.x, p1.y], [p2.x, p2.y], color(REMOVED_COLOR))
+                .thickness(3.0)
+                .build();
+        }
+    }
+
+    for added in &overlay.added {
+        let Some(tile) = current_tile(window, *added) else {
+            continue;
+        };
+
+        if tile.is_arrow() {
+            let p1 = tile_screen_position(window, &tile.source());
+            let p2 = tile_screen_position(window, &tile.target());
+            painter
+                .add_line([p1.x, p1.y], [p2.x, p2.y], color(ADDED_COLOR))
+                .thickness(3.0)
+                .build();
+        } else {
+            let pos = tile_screen_position(window, &tile);
+            painter
+                .add_circle([pos.x, pos.y], 14.0, color(ADDED_COLOR))
+                .thickness(2.0)
+                .build();
+        }
+    }
+
+    for owner in &overlay.changed_owners {
+        if let Some(tile) = current_tile(window, *owner) {
+            let pos = tile_screen_position(window, &tile);
+            painter
+                .add_circle([pos.x, pos.y], 17.0, color(CHANGED_COLOR))
+                .thickness(2.0)
+                .build();
+        }
+    }
+}
+
+// lists what the compared file had differently for a tile and everything attached to it
+pub fn show_diff_changes(window: &GraspEditorWindow, s: &GuiState, tile: &Tile) {
+    let Some(overlay) = &window.diff_overlay else {
+        return;
+    };
+
+    let diff = &overlay.diff;
+    if overlay.added.contains(&tile.id) {
+        s.ui.text_colored(ADDED_COLOR, format!("Added since {}", overlay.name));
+        return;
+    }
+
+    let changes = diff
+        .changed
+        .iter()
+        .filter(|c| diff.after[&c.after].owner_id() == tile.id)
+        .collect_vec();
+
+    if changes.is_empty() {
+        return;
+    }
+
+    s.ui.text_colored(CHANGED_COLOR, format!("Changed since {}", overlay.name));
+    for change in changes {
+        let component = &diff.after[&change.after].component;
+        for field in &change.fields {
+            s.ui.bullet_text(format!(
+                "{}.{}: {} -> {}",
+                component, field.field, field.before, field.after
+            ));
+        }
+    }
+}
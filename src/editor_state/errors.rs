@@ -0,0 +1,302 @@
+use std::sync::Arc;
+
+use imgui::{Condition, ImColor32, ImString, SelectableFlags};
+use itertools::Itertools;
+use mosaic::{
+    internals::{Mosaic, MosaicCRUD, MosaicIO, Tile},
+    iterators::{component_selectors::ComponentSelectors, tile_deletion::TileDeletion},
+};
+
+use crate::{
+    core::structures::errors::{ErrorCapability, Severity},
+    GuiState,
+};
+
+use super::{
+    foundation::GraspEditorState,
+    helpers::RequireWindowFocus,
+    query::{is_on_canvas, tile_screen_position},
+    windows::GraspEditorWindow,
+};
+
+pub struct ErrorsPanel {
+    pub info: bool,
+    pub warning: bool,
+    pub error: bool,
+    pub filter: String,
+    pub current_window_only: bool,
+}
+
+impl Default for ErrorsPanel {
+    fn default() -> Self {
+        Self {
+            info: true,
+            warning: true,
+            error: true,
+            filter: String::new(),
+            current_window_only: false,
+        }
+    }
+}
+
+impl ErrorsPanel {
+    fn shows(&self, error: &Tile, focused_window: Option<usize>) -> bool {
+        let severity_on = match Severity::of(error) {
+            Severity::Info => self.info,
+            Severity::Warning => self.warning,
+            Severity::Error => self.error,
+        };
+
+        let filter = self.filter.to_lowercase();
+        let text_on = filter.is_empty()
+            || error
+                .get("message")
+                .as_str()
+                .to_lowercase()
+                .contains(&filter)
+            || error
+                .get("source")
+                .as_str()
+                .to_lowercase()
+                .contains(&filter);
+
+        let window_on = !self.current_window_only
+            || focused_window == Some(error.get("window").as_u64() as usize);
+
+        severity_on && text_on && window_on
+    }
+}
+
+pub fn severity_color(severity: Severity) -> [f32; 4] {
+    match severity {
+        Severity::Info => [0.5, 0.75, 1.0, 1.0],
+        Severity::Warning => [1.0, 0.8, 0.3, 1.0],
+        Severity::Error => [1.0, 0.4, 0.4, 1.0],
+    }
+}
+
+fn error_target(window: &GraspEditorWindow, error: &Tile) -> Option<Tile> {
+    match error.get("target").as_u64() as usize {
+        0 => None,
+        id => window.document_mosaic.get(id).filter(is_on_canvas),
+    }
+}
+
+// one badge per tile, colored by the worst of its outstanding errors
+pub fn draw_error_badges(editor_mosaic: &Arc<Mosaic>, window: &GraspEditorWindow, s: &GuiState) {
+    let errors = editor_mosaic
+        .get_all()
+        .include_component("Error")
+        .filter(|e| e.get("window").as_u64() as usize == window.window_tile.id)
+        .filter_map(|e| error_target(window, &e).map(|target| (target, e)))
+        .into_group_map_by(|(target, _)| target.id);
+
+    let painter = s.ui.get_window_draw_list();
+    let mouse: [f32; 2] = s.ui.io().mouse_pos;
+
+    for (_, group) in errors.into_iter().sorted_by_key(|(id, _)| *id) {
+        let target = &group[0].0;
+        let severity = group.iter().map(|(_, e)| Severity::of(e)).max().unwrap();
+        let [r, g, b, _] = severity_color(severity);
+
+        let pos = tile_screen_position(window, target);
+        let center = [pos.x + 12.0, pos.y - 12.0];
+        painter
+            .add_circle(center, 7.0, ImColor32::from_rgba_f32s(r, g, b, 1.0))
+            .filled(true)
+            .build();
+        painter.add_text([center[0] - 2.0, center[1] - 7.0], ImColor32::BLACK, "!");
+
+        let hovered = (mouse[0] - center[0]).powi(2) + (mouse[1] - center[1]).powi(2) < 49.0;
+        if hovered && s.ui.is_window_hovered() {
+            s.ui.tooltip_text(
+                group
+                    .iter()
+                    .map(|(_, e)| {
+                        format!(
+                            "[{}] {}",
+                            e.get("source").as_str(),
+                            e.get("message").as_str()
+                        )
+                    })
+                    .join("\n"),
+            );
+        }
+    }
+}
+
+// conflicts that a merge recorded in a document show up in the Errors panel once it is opened
+pub fn adopt_document_errors(editor_mosaic: &Arc<Mosaic>, window: &GraspEditorWindow) {
+    let errors = window
+        .document_mosaic
+        .get_all()
+        .include_component("Error")
+        .collect_vec();
+
+    for error in errors {
+        let target = match error.get("target").as_u64() as usize {
+            0 => None,
+            id => window.document_mosaic.get(id),
+        };
+
+        editor_mosaic.make_diagnostic(
+            Severity::of(&error),
+            &error.get("source").as_str(),
+            &error.get("message").as_str(),
+            Some(window.window_tile.clone()),
+            target,
+        );
+        window.document_mosaic.delete_tile(error.id);
+    }
+}
+
+impl GraspEditorState {
+    // errors outlive neither their window nor the tile they point at
+    pub fn prune_errors(&self) {
+        self.editor_mosaic
+            .get_all()
+            .include_component("Error")
+            .filter(|e| {
+                let window = e.get("window").as_u64() as usize;
+                let target = e.get("target").as_u64() as usize;
+                if window == 0 {
+                    return false;
+                }
+
+                match self
+                    .window_list
+                    .windows
+                    .iter()
+                    .find(|w| w.window_tile.id == window)
+                {
+                    Some(w) => target != 0 && !w.document_mosaic.is_tile_valid(&target),
+                    None => true,
+                }
+            })
+            .delete();
+    }
+
+    fn focus_error(&mut self, error: &Tile) {
+        let window_id = error.get("window").as_u64() as usize;
+        let Some(window) = self.window_list.get_by_id_mut(window_id) else {
+            return;
+        };
+
+        if let Some(target) = error_target(window, error) {
+            window.focus_tile(&target);
+        }
+
+        let name = window.name.clone();
+        self.require_named_window_focus(&name);
+    }
+
+    pub fn show_errors(&mut self, s: &GuiState) {
+        let mut focused: Option<Tile> = None;
+        let mut dismissed: Vec<Tile> = vec![];
+
+        if let Some(_w) =
+            s.ui.window(ImString::new("Errors"))
+                .position([100.0, 100.0], Condition::FirstUseEver)
+                .size([700.0, 200.0], Condition::FirstUseEver)
+                .begin()
+        {
+            let focused_window = self.window_list.get_focused().map(|w| w.window_tile.id);
+            let panel = &mut self.errors_panel;
+
+            for (severity, on) in Severity::ALL.into_iter().zip([
+                &mut panel.info,
+                &mut panel.warning,
+                &mut panel.error,
+            ]) {
+                s.ui.checkbox(severity.as_str(), on);
+                s.ui.same_line();
+            }
+
+            s.ui.checkbox("Current window", &mut panel.current_window_only);
+            s.ui.same_line();
+            s.ui.set_next_item_width(150.0);
+            s.ui.input_text("##errors-filter", &mut panel.filter)
+                .hint("Filter")
+                .build();
+
+            let errors = self
+                .editor_mosaic
+                .get_all()
+                .include_component("Error")
+                .filter(|e| panel.shows(e, focused_window))
+                .sorted_by(|a, b| {
+                    b.get("timestamp")
+                        .as_str()
+                        .cmp(&a.get("timestamp").as_str())
+                })
+                .collect_vec();
+
+            s.ui.same_line();
+            if s.ui.button("Dismiss All") {
+                dismissed.extend(errors.iter().cloned());
+            }
+
+            s.ui.separator();
+
+            s.ui.columns(6, "errors_columns", true);
+            for (column, width) in [70.0, 170.0, 150.0, 60.0, 70.0].into_iter().enumerate() {
+                s.ui.set_column_width(column as i32, width);
+            }
+
+            for header in ["Severity", "Source", "Window", "Time", "", "Message"] {
+                s.ui.text(header);
+                s.ui.next_column();
+            }
+
+            s.ui.separator();
+
+            for error in &errors {
+                let severity = Severity::of(error);
+                if s.ui
+                    .selectable_config(format!("##error-{}", error.id))
+                    .flags(SelectableFlags::SPAN_ALL_COLUMNS | SelectableFlags::ALLOW_ITEM_OVERLAP)
+                    .build()
+                {
+                    focused = Some(error.clone());
+                }
+                s.ui.same_line();
+                s.ui.text_colored(severity_color(severity), severity.as_str());
+                s.ui.next_column();
+
+                s.ui.text(error.get("source").as_str());
+                s.ui.next_column();
+
+                let window_id = error.get("window").as_u64() as usize;
+                let name = self
+                    .window_list
+                    .windows
+                    .iter()
+                    .find(|w| w.window_tile.id == window_id)
+                    .map(|w| w.name.clone())
+                    .unwrap_or_default();
+                s.ui.text(&name);
+                s.ui.next_column();
+
+                let timestamp = error.get("timestamp").as_str();
+                s.ui.text(timestamp.get(11..19).unwrap_or_default());
+                s.ui.next_column();
+
+                if s.ui.small_button(format!("Dismiss##error-{}", error.id)) {
+                    dismissed.push(error.clone());
+                }
+                s.ui.next_column();
+
+                s.ui.text(error.get("message").as_str());
+                s.ui.next_column();
+            }
+
+            s.ui.columns(1, "errors_columns_end", false);
+        }
+
+        if let Some(error) = focused {
+            self.focus_error(&error);
+        }
+
+        dismissed.into_iter().delete();
+    }
+}
@@ -1,11 +1,30 @@
-use std::{env, fs};
+use std::{env, fs, path::Path, sync::Arc};
 
-use mosaic::internals::{pars, ComponentValuesBuilderSetter, MosaicIO};
+use itertools::Itertools;
+use mosaic::{
+    capabilities::ArchetypeSubject,
+    internals::{par, pars, ComponentValuesBuilderSetter, Mosaic, MosaicIO, Tile},
+    iterators::component_selectors::ComponentSelectors,
+};
 
-use crate::{core::structures::grasp_queues, grasp_queues::WindowRenameRequestQueue};
+use crate::{
+    core::{
+        math::Vec2,
+        structures::{
+            errors::ErrorCapability,
+            grasp_queues,
+            subgraph::{bounds, capture_subgraph, load_subgraph, place_subgraph, Subgraph},
+        },
+    },
+    grasp_queues::WindowRenameRequestQueue,
+    transformers::make_colored_selection,
+};
 
 use super::{foundation::GraspEditorState, windows::GraspEditorWindow};
 
+const INSERT_MARGIN: f32 = 100.0;
+const INSERT_COLOR: [f32; 4] = [0.6, 0.6, 0.6, 0.3];
+
 pub trait SaveFileCapability {
     fn save_file(&mut self);
     fn save_file_as(&mut self);
@@ -41,6 +60,7 @@ impl SaveFileCapability for GraspEditorWindow {
             fs::write(self.path.clone().unwrap(), document).unwrap();
 
             self.changed = false;
+            self.discard_recovery();
         }
     }
 
@@ -65,6 +85,132 @@ impl SaveFileCapability for GraspEditorWindow {
             );
             self.path = Some(file.clone());
             self.changed = false;
+            self.discard_recovery();
+        }
+    }
+}
+
+impl GraspEditorWindow {
+    // the document's top left corner is put at `at`, or right of everything already on the canvas
+    pub fn insert_file(
+        &mut self,
+        file: &Path,
+        source: &str,
+        at: Option<Vec2>,
+        group: Option<&str>,
+    ) -> Vec<Tile> {
+        let mut subgraph = match load_subgraph(file) {
+            Ok(subgraph) => subgraph,
+            Err(error) => {
+                self.editor_mosaic
+                    .make_error(source, &error, Some(self.window_tile.clone()), None);
+                return vec![];
+            }
+        };
+
+        self.drop_conflicting_types(&mut subgraph, source);
+
+        let existing = self
+            .document_mosaic
+            .get_all()
+            .include_component("Position")
+            .map(|p| Vec2::new(p.get("x").as_f32(), p.get("y").as_f32()))
+            .collect_vec();
+
+        let corner = at.unwrap_or_else(|| {
+            bounds(&existing)
+                .map(|(min, max)| Vec2::new(max.x + INSERT_MARGIN, min.y))
+                .unwrap_or(Vec2::ZERO)
+        });
+
+        let offset = subgraph
+            .bounds()
+            .map(|(min, _)| corner - min)
+            .unwrap_or(Vec2::ZERO);
+
+        let inserted = place_subgraph(&self.document_mosaic, &subgraph, offset);
+
+        if let Some(label) = group {
+            if let Some(selection) = make_colored_selection(&inserted, INSERT_COLOR) {
+                selection.add_component("Label", par(label.chars().take(32).collect::<String>()));
+            }
+        }
+
+        self.editor_data.selected = inserted.clone();
+        self.mark_changed();
+        self.request_quadtree_update();
+        inserted
+    }
+}
+
+impl GraspEditorWindow {
+    // tiles left out for pointing outside of the selection are reported as a warning
+    pub fn extract_selection(&self, selected: &[Tile], source: &str) -> Option<Subgraph> {
+        let subgraph = capture_subgraph(selected);
+        if subgraph.tiles.is_empty() {
+            self.editor_mosaic.make_warning(
+                source,
+                "Nothing to extract: no objects are selected",
+                Some(self.window_tile.clone()),
+                None,
+            );
+            return None;
+        }
+
+        if !subgraph.stripped.is_empty() {
+            let counts = subgraph
+                .stripped
+                .iter()
+                .counts_by(|t| t.component.clone())
+                .into_iter()
+                .sorted()
+                .map(|(component, count)| format!("{} ({})", component, count))
+                .join(", ");
+
+            self.editor_mosaic.make_warning(
+                source,
+                &format!(
+                    "Stripped {} tile(s) referring to tiles outside of the selection: {}",
+                    subgraph.stripped.len(),
+                    counts
+                ),
+                Some(self.window_tile.clone()),
+                None,
+            );
+        }
+
+        Some(subgraph)
+    }
+
+    pub fn save_selection_as(&self) {
+        let Some(subgraph) = self.extract_selection(&self.editor_data.selected, "Save Selection")
+        else {
+            return;
+        };
+
+        if let Some(file) = rfd::FileDialog::new()
+            .add_filter("Mosaic", &["mos"])
+            .set_directory(env::current_dir().unwrap())
+            .save_file()
+        {
+            let mosaic = Mosaic::new();
+            GraspEditorState::prepare_mosaic(
+                &self.component_mosaic,
+                &self.editor_mosaic,
+                Arc::clone(&mosaic),
+            );
+
+            place_subgraph(&mosaic, &subgraph, Vec2::ZERO);
+            if let Err(error) = fs::write(file.clone(), mosaic.save()) {
+                self.editor_mosaic.make_error(
+                    "Save Selection",
+                    &format!("Cannot write {}: {}", file.display(), error),
+                    Some(self.window_tile.clone()),
+                    None,
+                );
+            } else {
+                GraspEditorState::prepend_recent(file);
+            }
         }
     }
 }
@@ -0,0 +1,305 @@
+use imgui::{Condition, ImString};
+use itertools::Itertools;
+use mosaic::internals::{MosaicIO, Tile, Value};
+use regex::{NoExpand, Regex, RegexBuilder};
+
+use crate::{
+    core::{gui::docking::GuiViewport, structures::owners::TileOwner},
+    querying::query_tiles::is_query_tile,
+    utilities::{field_names, set_field_from_str},
+    GuiState,
+};
+
+use super::{
+    foundation::GraspEditorState, helpers::RequireWindowFocus, query::is_on_canvas,
+    undo::FieldChange, windows::GraspEditorWindow,
+};
+
+#[derive(Default)]
+pub struct FindPanel {
+    pub query: String,
+    pub replacement: String,
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub all_windows: bool,
+    pub replace_mode: bool,
+    pub focus_query: bool,
+    results: Vec<FindResult>,
+    status: String,
+}
+
+struct FindResult {
+    window: usize,
+    window_name: String,
+    owner: usize,
+    tile: usize,
+    component: String,
+    field: String,
+    text: String,
+}
+
+impl FindPanel {
+    fn matcher(&self) -> Result<Regex, regex::Error> {
+        let pattern = if self.regex {
+            self.query.clone()
+        } else {
+            regex::escape(&self.query)
+        };
+
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+    }
+
+    fn replace(&self, matcher: &Regex, text: &str) -> String {
+        if self.regex {
+            matcher
+                .replace_all(text, self.replacement.as_str())
+                .to_string()
+        } else {
+            matcher
+                .replace_all(text, NoExpand(&self.replacement))
+                .to_string()
+        }
+    }
+}
+
+// s32 and str fields are searchable, a descriptor or extension is found through its owner
+fn text_fields(tile: &Tile) -> Vec<(String, String)> {
+    field_names(tile)
+        .into_iter()
+        .filter_map(|field| match tile.get(&field) {
+            Value::S32(v) => Some((field, v.to_string())),
+            Value::STR(v) => Some((field, v)),
+            _ => None,
+        })
+        .collect_vec()
+}
+
+fn find_in_window(window: &GraspEditorWindow, matcher: &Regex) -> Vec<FindResult> {
+    window
+        .document_mosaic
+        .get_all()
+        .filter(|tile| !is_query_tile(tile))
+        .flat_map(|tile| {
+            text_fields(&tile)
+                .into_iter()
+                .filter(|(_, text)| matcher.is_match(text))
+                .map(|(field, text)| FindResult {
+                    window: window.window_tile.id,
+                    window_name: window.name.clone(),
+                    owner: tile.owner_id(),
+                    tile: tile.id,
+                    component: tile.component.to_string(),
+                    field,
+                    text,
+                })
+                .collect_vec()
+        })
+        .sorted_by_key(|r| (r.owner, r.tile))
+        .collect_vec()
+}
+
+impl GraspEditorState {
+    fn find_scope(&self) -> Vec<&GraspEditorWindow> {
+        if self.find_panel.all_windows {
+            self.window_list
+                .windows
+                .iter()
+                .sorted_by_key(|w| w.window_list_index)
+                .collect_vec()
+        } else {
+            self.window_list.get_focused().into_iter().collect_vec()
+        }
+    }
+
+    fn run_find(&mut self) {
+        self.find_panel.results.clear();
+        if self.find_panel.query.is_empty() {
+            self.find_panel.status.clear();
+            return;
+        }
+
+        let results = match self.find_panel.matcher() {
+            Ok(matcher) => self
+                .find_scope()
+                .into_iter()
+                .flat_map(|w| find_in_window(w, &matcher))
+                .collect_vec(),
+            Err(error) => {
+                self.find_panel.status = error.to_string();
+                return;
+            }
+        };
+
+        self.find_panel.status = format!("{} match(es)", results.len());
+        self.find_panel.results = results;
+    }
+
+    // a single undo entry covers every field rewritten in every window
+    fn run_replace(&mut self) {
+        let Ok(matcher) = self.find_panel.matcher() else {
+            return;
+        };
+
+        let description = format!(
+            "replace '{}' with '{}'",
+            self.find_panel.query, self.find_panel.replacement
+        );
+        let scope = self
+            .find_scope()
+            .iter()
+            .map(|w| w.window_tile.id)
+            .collect_vec();
+
+        let (mut replaced, mut skipped) = (0, 0);
+        let mut changes = vec![];
+        for id in scope {
+            let Some(window) = self.window_list.get_by_id_mut(id) else {
+                continue;
+            };
+
+            let mut window_changed = false;
+            for found in find_in_window(window, &matcher) {
+                let Some(mut tile) = window.document_mosaic.get(found.tile) else {
+                    continue;
+                };
+
+                let text = self.find_panel.replace(&matcher, &found.text);
+                let is_s32 = matches!(tile.get(&found.field), Value::S32(_));
+                if text == found.text {
+                    continue;
+                } else if is_s32 && text.len() > 32 {
+                    skipped += 1;
+                    continue;
+                }
+
+                if set_field_from_str(&mut tile, &found.field, &text).is_ok() {
+                    replaced += 1;
+                    window_changed = true;
+                    changes.push(FieldChange {
+                        window: id,
+                        tile: found.tile,
+                        field: found.field,
+                        before: found.text,
+                        after: text,
+                    });
+                }
+            }
+
+            if window_changed {
+                window.mark_changed();
+                window.request_quadtree_update();
+            }
+        }
+
+        self.push_undo(description, changes);
+
+        self.run_find();
+        self.find_panel.status = if skipped > 0 {
+            format!(
+                "{} value(s) replaced, {} skipped for being longer than 32 characters",
+                replaced, skipped
+            )
+        } else {
+            format!("{} value(s) replaced", replaced)
+        };
+    }
+
+    fn focus_find_result(&mut self, window_id: usize, owner: usize) {
+        let Some(window) = self.window_list.get_by_id_mut(window_id) else {
+            return;
+        };
+
+        if let Some(tile) = window.document_mosaic.get(owner) {
+            if is_on_canvas(&tile) {
+                window.focus_tile(&tile);
+            } else {
+                window.editor_data.selected = vec![tile];
+            }
+        }
+
+        let name = window.name.clone();
+        self.require_named_window_focus(&name);
+    }
+
+    pub fn show_find(&mut self, s: &GuiState) {
+        let viewport = GuiViewport::get_main_viewport();
+        let mut search = false;
+        let mut replace = false;
+        let mut clicked = None;
+
+        if let Some(_w) =
+            s.ui.window(ImString::new("Find"))
+                .position(
+                    [viewport.size().x / 2.0 - 250.0, 60.0],
+                    Condition::FirstUseEver,
+                )
+                .size([500.0, 300.0], Condition::FirstUseEver)
+                .begin()
+        {
+            let panel = &mut self.find_panel;
+
+            if panel.focus_query {
+                s.ui.set_keyboard_focus_here();
+                panel.focus_query = false;
+            }
+
+            s.ui.set_next_item_width(-80.0);
+            search |=
+                s.ui.input_text("##find-query", &mut panel.query)
+                    .hint("Find")
+                    .build();
+            s.ui.same_line();
+            search |= s.ui.button("Find");
+
+            if panel.replace_mode {
+                s.ui.set_next_item_width(-80.0);
+                s.ui.input_text("##find-replacement", &mut panel.replacement)
+                    .hint("Replace with")
+                    .build();
+                s.ui.same_line();
+                replace = s.ui.button("Replace");
+            }
+
+            search |= s.ui.checkbox("Regex", &mut panel.regex);
+            s.ui.same_line();
+            search |= s.ui.checkbox("Match case", &mut panel.case_sensitive);
+            s.ui.same_line();
+            search |= s.ui.checkbox("All windows", &mut panel.all_windows);
+            s.ui.same_line();
+            s.ui.checkbox("Replace mode", &mut panel.replace_mode);
+
+            s.ui.text_disabled(&panel.status);
+            s.ui.separator();
+
+            if let Some(_c) = s.ui.child_window("##find-results").begin() {
+                for (index, result) in panel.results.iter().enumerate() {
+                    let label = format!(
+                        "{} #{} [{}.{}] {}##find-{}",
+                        result.window_name,
+                        result.owner,
+                        result.component,
+                        result.field,
+                        result.text.lines().next().unwrap_or_default(),
+                        index
+                    );
+
+                    if s.ui.selectable(label) {
+                        clicked = Some((result.window, result.owner));
+                    }
+                }
+            }
+        }
+
+        if replace {
+            self.run_replace();
+        } else if search {
+            self.run_find();
+        }
+
+        if let Some((window, owner)) = clicked {
+            self.focus_find_result(window, owner);
+        }
+    }
+}
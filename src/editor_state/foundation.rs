@@ -22,18 +22,39 @@ use crate::{
     core::{
         gui::components::{setup_component_renderers, setup_file_importers},
         math::Rect2,
+        structures::errors::{ErrorCapability, ERROR_TYPE},
     },
     editor_state::{helpers::RequireWindowFocus, windows::GraspEditorWindow},
     editor_state_machine::EditorState,
     grasp_editor_window_list::GraspEditorWindowList,
     grasp_render,
-    transformers::{finite_state_transformer, pattern_match_tool, select},
+    transformers::{
+        bipartition_graph, color_graph, compare_by_label_transformer, compare_transformer,
+        dominators_transformer, extract_selection_transformer, finite_state_transformer,
+        load_transformer, max_flow_transformer, minimum_spanning_tree_transformer,
+        natural_loops_transformer, pattern_match_tool, post_dominators_transformer,
+        remove_implied_arrows, select, transitive_closure, transitive_reduction,
+    },
     GuiState,
 };
 
 use super::{
     categories::ComponentCategory,
-    network::Networked,
+    collaboration::Collaboration,
+    errors::ErrorsPanel,
+    find::FindPanel,
+    keymap::Keymap,
+    logs::LogPanel,
+    metrics::MetricsPanel,
+    network::{
+        register_networked, run_server, unregister_networked, NetworkKey, NetworkSnapshot,
+        Networked, DEFAULT_PORT,
+    },
+    outline::OutlinePanel,
+    palette::CommandPalette,
+    query::QueryConsole,
+    recovery::RecoveryState,
+    undo::UndoEntry,
     view::{ComponentPropertyRenderer, ComponentRenderer, DeleteReaction, FileImporter},
 };
 
@@ -52,12 +73,40 @@ pub struct Transformer {
     pub transform_function: TransformerFn,
 }
 
-#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Deserialize, Serialize, Clone, PartialEq, PartialOrd)]
 pub struct EditorOptions {
     pub toggle_tabview: bool,
     pub toggle_hierarchy: bool,
     pub toggle_properties: bool,
     pub toggle_errors: bool,
+    #[serde(default)]
+    pub toggle_metrics: bool,
+    #[serde(default)]
+    pub toggle_query: bool,
+    #[serde(default)]
+    pub toggle_log: bool,
+    #[serde(default)]
+    pub toggle_find: bool,
+    #[serde(default = "default_network_port")]
+    pub network_port: u16,
+    #[serde(default)]
+    pub api_token: String,
+    #[serde(default = "default_autosave_seconds")]
+    pub autosave_seconds: u64,
+    #[serde(default = "default_network_address")]
+    pub network_address: String,
+}
+
+fn default_autosave_seconds() -> u64 {
+    30
+}
+
+fn default_network_address() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_network_port() -> u16 {
+    DEFAULT_PORT
 }
 
 impl Default for EditorOptions {
@@ -67,6 +116,14 @@ impl Default for EditorOptions {
             toggle_hierarchy: false,
             toggle_properties: false,
             toggle_errors: false,
+            toggle_metrics: false,
+            toggle_query: false,
+            toggle_log: false,
+            toggle_find: false,
+            network_port: DEFAULT_PORT,
+            api_token: String::new(),
+            autosave_seconds: default_autosave_seconds(),
+            network_address: default_network_address(),
         };
 
         if let Ok(config_content) = fs::read_to_string("env\\config.ron") {
@@ -101,8 +158,20 @@ pub struct GraspEditorState {
     pub refresh_quadtree_queue: QueueTile,
     pub locked_components: Vec<S32>,
     pub editor_options: EditorOptions,
+    pub metrics_panel: MetricsPanel,
+    pub query_console: QueryConsole,
+    pub log_panel: LogPanel,
+    pub errors_panel: ErrorsPanel,
+    pub outline_panel: OutlinePanel,
+    pub find_panel: FindPanel,
+    pub keymap: Keymap,
+    pub command_palette: CommandPalette,
+    pub undo_stack: Vec<UndoEntry>,
+    pub recovery: RecoveryState,
+    pub network_snapshots: HashMap<NetworkKey, NetworkSnapshot>,
+    pub collaboration: Collaboration,
     pub properties_hovered: bool,
-    pub queued_component_delete: Option<usize>,
+    pub queued_component_delete: Option<(String, Vec<usize>)>,
     pub transformer_functions: HashMap<String, Transformer>,
     pub pending_close_window_request: Option<Tile>,
     pub pending_transform_window_request: Option<Tile>,
@@ -125,6 +194,38 @@ impl GraspEditorState {
         self.add_transformer("[Selection] Delete", Box::new(deselect));
         self.add_transformer("[Pattern] Match", Box::new(pattern_match_tool));
         self.add_transformer("[FSM] Compile", Box::new(finite_state_transformer));
+        self.add_transformer(
+            "[Graph] Transitive Reduction",
+            Box::new(transitive_reduction),
+        );
+        self.add_transformer("[Graph] Transitive Closure", Box::new(transitive_closure));
+        self.add_transformer(
+            "[Graph] Remove Implied Arrows",
+            Box::new(remove_implied_arrows),
+        );
+        self.add_transformer("[Graph] Color (greedy/DSatur)", Box::new(color_graph));
+        self.add_transformer("[Graph] Bipartition", Box::new(bipartition_graph));
+        self.add_transformer(
+            "[Graph] Minimum Spanning Tree",
+            Box::new(minimum_spanning_tree_transformer),
+        );
+        self.add_transformer("[Graph] Max Flow / Min Cut", Box::new(max_flow_transformer));
+        self.add_transformer("[CFG] Dominators", Box::new(dominators_transformer));
+        self.add_transformer(
+            "[CFG] Post-dominators",
+            Box::new(post_dominators_transformer),
+        );
+        self.add_transformer("[CFG] Natural Loops", Box::new(natural_loops_transformer));
+        self.add_transformer("[Document] Load", Box::new(load_transformer));
+        self.add_transformer(
+            "[Document] Extract Selection",
+            Box::new(extract_selection_transformer),
+        );
+        self.add_transformer("[Document] Compare With...", Box::new(compare_transformer));
+        self.add_transformer(
+            "[Document] Compare By Label...",
+            Box::new(compare_by_label_transformer),
+        );
     }
 
     pub fn new() -> Self {
@@ -184,6 +285,18 @@ impl GraspEditorState {
             component_mosaic,
             transformer_mosaic,
             editor_options: EditorOptions::default(),
+            metrics_panel: MetricsPanel::default(),
+            query_console: QueryConsole::default(),
+            log_panel: LogPanel::default(),
+            errors_panel: ErrorsPanel::default(),
+            outline_panel: OutlinePanel::default(),
+            find_panel: FindPanel::default(),
+            keymap: Keymap::default(),
+            command_palette: CommandPalette::default(),
+            undo_stack: vec![],
+            recovery: RecoveryState::default(),
+            network_snapshots: HashMap::new(),
+            collaboration: Collaboration::default(),
             properties_hovered: false,
             queued_component_delete: None,
             locked_components: vec![
@@ -202,7 +315,12 @@ impl GraspEditorState {
         setup_file_importers(&mut instance);
 
         instance.initialize_networked();
+        register_networked(instance.component_mosaic.get_key(), "Components");
+        register_networked(instance.transformer_mosaic.get_key(), "Transformers");
+        tokio::spawn(run_server(instance.editor_options.clone()));
+
         instance.load_transformers();
+        instance.load_keymap();
         instance
     }
 
@@ -243,6 +361,18 @@ impl GraspEditorState {
                         .ok(),
                 );
             }
+
+            for (field, (min, max)) in &category.bounds {
+                component_mosaic.new_extension(
+                    &cat_tile,
+                    "ComponentBounds",
+                    pars()
+                        .set("name", field.clone())
+                        .set("min", *min)
+                        .set("max", *max)
+                        .ok(),
+                );
+            }
         }
     }
 
@@ -253,9 +383,7 @@ impl GraspEditorState {
     ) -> (Arc<Mosaic>, Vec<ComponentCategory>) {
         println!("Loading mosaic");
         assert_ne!(component_mosaic.id, editor_mosaic.id);
-        component_mosaic
-            .new_type("Error: { message: str, target: u64, window: u64 };")
-            .unwrap();
+        component_mosaic.new_type(ERROR_TYPE).unwrap();
 
         component_mosaic
             .new_type("ComponentCategory: { name: s32, hidden: bool };")
@@ -265,6 +393,10 @@ impl GraspEditorState {
             .new_type("ComponentEntry: { name: s32, definition: str };")
             .unwrap();
 
+        component_mosaic
+            .new_type("ComponentBounds: { name: str, min: f32, max: f32 };")
+            .unwrap();
+
         let components: Vec<ComponentCategory> = fs::read_dir("env\\components")
             .unwrap()
             .flat_map(|file_entry| {
@@ -281,31 +413,24 @@ impl GraspEditorState {
                             vec![]
                         } else {
                             println!("{:?}", parsing.clone().unwrap_err().to_string());
-                            editor_mosaic.new_object(
-                                "Error",
-                                pars()
-                                    .set("message", parsing.unwrap_err().to_string())
-                                    .set("target", 0)
-                                    .set("window", 0)
-                                    .ok(),
+                            editor_mosaic.make_error(
+                                "Components",
+                                &parsing.unwrap_err().to_string(),
+                                None,
+                                None,
                             );
 
                             vec![]
                         }
                     } else {
-                        editor_mosaic.new_object(
-                            "Error",
-                            pars()
-                                .set(
-                                    "message",
-                                    format!(
-                                        "Couldn't open {} components configuration file.",
-                                        file.file_name().to_str().unwrap()
-                                    ),
-                                )
-                                .set("target", 0)
-                                .set("window", 0)
-                                .ok(),
+                        editor_mosaic.make_error(
+                            "Components",
+                            &format!(
+                                "Couldn't open {} components configuration file.",
+                                file.file_name().to_str().unwrap()
+                            ),
+                            None,
+                            None,
                         );
                         vec![]
                     }
@@ -356,6 +481,7 @@ impl GraspEditorState {
             path: path.cloned(),
             window_tile,
             changed: false,
+            revision: 0,
             quadtree: Mutex::new(Quadtree::new_with_anchor((-1000, -1000).into(), 16)),
             document_mosaic,
             component_mosaic: Arc::clone(&self.component_mosaic),
@@ -366,6 +492,10 @@ impl GraspEditorState {
             state: EditorState::Idle,
             grid_visible: false,
             ruler_visible: false,
+            dominators_visible: true,
+            query_name: String::new(),
+            query_text: String::new(),
+            query_highlights: Default::default(),
             renderer: grasp_render::default_renderer_draw,
             left_drag_last_frame: false,
             middle_drag_last_frame: false,
@@ -377,9 +507,12 @@ impl GraspEditorState {
                 height: 0.0,
             },
             window_list_index: id,
+            diff_overlay: None,
+            pending_command: None,
         };
 
         window.document_mosaic.initialize_networked();
+        register_networked(window.document_mosaic.get_key(), &name);
 
         self.window_list.named_windows.push(name);
         self.window_list.windows.push_front(window);
@@ -395,6 +528,12 @@ impl GraspEditorState {
         {
             let window = self.window_list.windows.get(pos).unwrap();
             println!("Deleting {:?}", window.name);
+            unregister_networked(window.document_mosaic.get_key());
+            window.discard_recovery();
+            self.collaboration
+                .hosted
+                .remove(&window.document_mosaic.get_key());
+            self.collaboration.joined.remove(&window_tile.id);
 
             let p = self
                 .window_list
@@ -0,0 +1,229 @@
+use std::{collections::HashSet, fs};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+pub const KEYMAP_FILE: &str = "env\\keymap.ron";
+
+const DEFAULT_BINDINGS: [(&str, &str); 9] = [
+    ("Ctrl+S", "document.save"),
+    ("Ctrl+F", "edit.find"),
+    ("Ctrl+Z", "edit.undo"),
+    ("Ctrl+C", "edit.copy"),
+    ("Ctrl+X", "edit.cut"),
+    ("Ctrl+V", "edit.paste"),
+    ("F11", "snapshot.take"),
+    ("F12", "snapshot.update"),
+    ("Ctrl+Shift+P", "palette.open"),
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KeyBinding {
+    pub chord: String,
+    pub command: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeyChord {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    // index into io.keys_down
+    pub key: usize,
+}
+
+// SDL scancodes, the function keys are moved past the imgui keys by the window loop
+fn key_index(name: &str) -> Option<usize> {
+    let upper = name.to_uppercase();
+    let mut chars = upper.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c @ 'A'..='Z'), None) => return Some(4 + (c as usize - 'A' as usize)),
+        (Some('0'), None) => return Some(39),
+        (Some(c @ '1'..='9'), None) => return Some(30 + (c as usize - '1' as usize)),
+        _ => {}
+    }
+
+    if let Some(n) = upper
+        .strip_prefix('F')
+        .and_then(|n| n.parse::<usize>().ok())
+    {
+        return (1..=12).contains(&n).then(|| 57 + n + 514);
+    }
+
+    match upper.as_str() {
+        "ENTER" => Some(40),
+        "ESCAPE" | "ESC" => Some(41),
+        "BACKSPACE" => Some(42),
+        "TAB" => Some(43),
+        "SPACE" => Some(44),
+        "INSERT" => Some(73),
+        "HOME" => Some(74),
+        "PAGEUP" => Some(75),
+        "DELETE" => Some(76),
+        "END" => Some(77),
+        "PAGEDOWN" => Some(78),
+        _ => None,
+    }
+}
+
+impl KeyChord {
+    pub fn parse(text: &str) -> Result<KeyChord, String> {
+        let mut chord = KeyChord {
+            ctrl: false,
+            shift: false,
+            alt: false,
+            key: 0,
+        };
+
+        let parts = text.split('+').map(|p| p.trim()).collect_vec();
+        let Some((key, modifiers)) = parts.split_last() else {
+            return Err(format!("Empty key chord '{}'", text));
+        };
+
+        for modifier in modifiers {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" => chord.ctrl = true,
+                "shift" => chord.shift = true,
+                "alt" => chord.alt = true,
+                _ => return Err(format!("Unknown modifier '{}' in '{}'", modifier, text)),
+            }
+        }
+
+        chord.key = key_index(key).ok_or(format!("Unknown key '{}' in '{}'", key, text))?;
+        Ok(chord)
+    }
+}
+
+#[derive(Default)]
+pub struct Keymap {
+    pub bindings: Vec<(KeyChord, KeyBinding)>,
+    // keys that were down last frame, so a held chord only fires once
+    held: HashSet<usize>,
+}
+
+impl Keymap {
+    pub fn from_bindings(bindings: Vec<KeyBinding>) -> (Keymap, Vec<String>) {
+        let mut errors = vec![];
+        let bindings = bindings
+            .into_iter()
+            .filter_map(|binding| match KeyChord::parse(&binding.chord) {
+                Ok(chord) => Some((chord, binding)),
+                Err(error) => {
+                    errors.push(error);
+                    None
+                }
+            })
+            .collect_vec();
+
+        (
+            Keymap {
+                bindings,
+                held: HashSet::new(),
+            },
+            errors,
+        )
+    }
+
+    pub fn default_bindings() -> Vec<KeyBinding> {
+        DEFAULT_BINDINGS
+            .iter()
+            .map(|(chord, command)| KeyBinding {
+                chord: chord.to_string(),
+                command: command.to_string(),
+            })
+            .collect_vec()
+    }
+
+    pub fn load() -> (Keymap, Vec<String>) {
+        match fs::read_to_string(KEYMAP_FILE) {
+            Ok(content) => match ron::from_str::<Vec<KeyBinding>>(&content) {
+                Ok(bindings) => Self::from_bindings(bindings),
+                Err(error) => {
+                    let (keymap, mut errors) = Self::from_bindings(Self::default_bindings());
+                    errors.insert(0, format!("Cannot read {}: {}", KEYMAP_FILE, error));
+                    (keymap, errors)
+                }
+            },
+            Err(_) => Self::from_bindings(Self::default_bindings()),
+        }
+    }
+
+    pub fn chord_for(&self, command: &str) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|(_, b)| b.command == command)
+            .map(|(_, b)| b.chord.as_str())
+    }
+
+    // modifiers have to match exactly, so Ctrl+Shift+S does not also save
+    pub fn pressed(
+        &mut self,
+        keys_down: &[bool],
+        ctrl: bool,
+        shift: bool,
+        alt: bool,
+    ) -> Vec<String> {
+        let is_down = |key: usize| keys_down.get(key).copied().unwrap_or(false);
+
+        let commands = self
+            .bindings
+            .iter()
+            .filter(|(chord, _)| {
+                is_down(chord.key)
+                    && !self.held.contains(&chord.key)
+                    && (chord.ctrl, chord.shift, chord.alt) == (ctrl, shift, alt)
+            })
+            .map(|(_, binding)| binding.command.clone())
+            .collect_vec();
+
+        self.held = self
+            .bindings
+            .iter()
+            .map(|(chord, _)| chord.key)
+            .filter(|key| is_down(*key))
+            .collect();
+
+        commands
+    }
+}
+
+#[cfg(test)]
+mod keymap_tests {
+    use super::{KeyBinding, KeyChord, Keymap};
+
+    #[test]
+    fn test_parse_chords() {
+        let chord = KeyChord::parse("Ctrl+Shift+P").unwrap();
+        assert!(chord.ctrl && chord.shift && !chord.alt);
+        assert_eq!(19, chord.key);
+
+        assert_eq!(69 + 514, KeyChord::parse("F12").unwrap().key);
+        assert_eq!(39, KeyChord::parse("alt+0").unwrap().key);
+        assert!(KeyChord::parse("Hyper+A").is_err());
+        assert!(KeyChord::parse("Ctrl+F13").is_err());
+    }
+
+    #[test]
+    fn test_chord_fires_once_per_press() {
+        let (mut keymap, errors) = Keymap::from_bindings(vec![KeyBinding {
+            chord: "Ctrl+S".to_string(),
+            command: "document.save".to_string(),
+        }]);
+        assert!(errors.is_empty());
+
+        let mut keys = vec![false; 600];
+        keys[22] = true;
+
+        assert!(keymap.pressed(&keys, false, false, false).is_empty());
+        keys[22] = false;
+        keymap.pressed(&keys, true, false, false);
+
+        keys[22] = true;
+        assert_eq!(
+            vec!["document.save"],
+            keymap.pressed(&keys, true, false, false)
+        );
+        assert!(keymap.pressed(&keys, true, false, false).is_empty());
+        assert!(keymap.pressed(&keys, true, true, false).is_empty());
+    }
+}
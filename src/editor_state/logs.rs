@@ -0,0 +1,111 @@
+use imgui::{Condition, ImString};
+use log::Level;
+
+use crate::{
+    core::gui::docking::GuiViewport,
+    logging::{clear_log_entries, log_entries, LogSource},
+    GuiState,
+};
+
+use super::foundation::GraspEditorState;
+
+const LEVELS: [Level; 5] = [
+    Level::Error,
+    Level::Warn,
+    Level::Info,
+    Level::Debug,
+    Level::Trace,
+];
+
+pub struct LogPanel {
+    pub level: usize,
+    pub grasp: bool,
+    pub mosaic: bool,
+    pub external: bool,
+    pub follow: bool,
+}
+
+impl Default for LogPanel {
+    fn default() -> Self {
+        Self {
+            level: 4,
+            grasp: true,
+            mosaic: true,
+            external: true,
+            follow: true,
+        }
+    }
+}
+
+impl LogPanel {
+    fn shows(&self, level: Level, source: LogSource) -> bool {
+        let source_on = match source {
+            LogSource::Grasp => self.grasp,
+            LogSource::Mosaic => self.mosaic,
+            LogSource::Extern => self.external,
+        };
+
+        source_on && level <= LEVELS[self.level]
+    }
+}
+
+fn level_color(level: Level) -> [f32; 4] {
+    match level {
+        Level::Error => [1.0, 0.4, 0.4, 1.0],
+        Level::Warn => [1.0, 0.8, 0.3, 1.0],
+        Level::Info => [0.9, 0.9, 0.9, 1.0],
+        Level::Debug | Level::Trace => [0.6, 0.6, 0.6, 1.0],
+    }
+}
+
+impl GraspEditorState {
+    pub fn show_log(&mut self, s: &GuiState) {
+        let viewport = GuiViewport::get_main_viewport();
+        if let Some(_w) =
+            s.ui.window(ImString::new("Log"))
+                .position([300.0, viewport.size().y - 250.0], Condition::FirstUseEver)
+                .size([700.0, 230.0], Condition::FirstUseEver)
+                .begin()
+        {
+            let panel = &mut self.log_panel;
+
+            let names = LEVELS.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+            s.ui.set_next_item_width(100.0);
+            s.ui.combo_simple_string("Level", &mut panel.level, &names);
+            s.ui.same_line();
+            s.ui.checkbox("[GRASP]", &mut panel.grasp);
+            s.ui.same_line();
+            s.ui.checkbox("[MOSAIC]", &mut panel.mosaic);
+            s.ui.same_line();
+            s.ui.checkbox("[EXTERN]", &mut panel.external);
+            s.ui.same_line();
+            s.ui.checkbox("Follow", &mut panel.follow);
+            s.ui.same_line();
+            if s.ui.button("Clear") {
+                clear_log_entries();
+            }
+
+            s.ui.separator();
+
+            if let Some(_c) = s.ui.child_window("##log-entries").begin() {
+                for entry in log_entries(|e| panel.shows(e.level, e.source)) {
+                    s.ui.text_colored(
+                        level_color(entry.level),
+                        format!(
+                            "{} {:<5} [{}] {}",
+                            entry.timestamp, entry.level, entry.source, entry.message
+                        ),
+                    );
+
+                    if s.ui.is_item_hovered() && !entry.file.is_empty() {
+                        s.ui.tooltip_text(format!("{}:{}", entry.file, entry.line));
+                    }
+                }
+
+                if panel.follow && s.ui.scroll_y() >= s.ui.scroll_max_y() {
+                    s.ui.set_scroll_here_y_with_ratio(1.0);
+                }
+            }
+        }
+    }
+}
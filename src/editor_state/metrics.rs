@@ -0,0 +1,214 @@
+use imgui::{Condition, ImString, TreeNodeFlags};
+use itertools::Itertools;
+use mosaic::internals::{EntityId, MosaicIO, Tile, TileFieldEmptyQuery};
+
+use crate::{
+    core::gui::docking::GuiViewport,
+    querying::{
+        metrics::GraphMetrics,
+        query_tiles::QUERY_COMPONENTS,
+        traversal::{Traversal, Traverse},
+    },
+    transformers::transformer_scope,
+    utilities::SelfText,
+    GuiState,
+};
+
+use super::{foundation::GraspEditorState, windows::GraspEditorWindow};
+
+const FILTER_MODES: [&str; 3] = ["Scope", "Include", "Exclude"];
+
+type MetricsKey = (usize, Vec<EntityId>, usize, usize, String);
+
+pub struct MetricsPanel {
+    pub top_n: i32,
+    pub filter_mode: usize,
+    pub filter_text: String,
+    key: Option<MetricsKey>,
+    metrics: GraphMetrics,
+}
+
+impl Default for MetricsPanel {
+    fn default() -> Self {
+        Self {
+            top_n: 10,
+            filter_mode: 0,
+            filter_text: String::new(),
+            key: None,
+            metrics: GraphMetrics::default(),
+        }
+    }
+}
+
+impl MetricsPanel {
+    fn key_for(&self, window: &GraspEditorWindow) -> MetricsKey {
+        (
+            window.window_tile.id,
+            window
+                .editor_data
+                .selected
+                .iter()
+                .map(|t| t.id)
+                .sorted()
+                .collect_vec(),
+            window.document_mosaic.get_all().len(),
+            self.filter_mode,
+            self.filter_text.clone(),
+        )
+    }
+
+    fn compute(&mut self, window: &GraspEditorWindow) {
+        let components = self
+            .filter_text
+            .split(',')
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .collect_vec();
+
+        let excluded = components
+            .iter()
+            .cloned()
+            .chain(QUERY_COMPONENTS.iter().map(|c| c.to_string()))
+            .collect_vec();
+
+        let traversal = match self.filter_mode {
+            1 => Traversal::Include {
+                components: &components,
+            },
+            2 => Traversal::Exclude {
+                components: &excluded,
+            },
+            _ => transformer_scope(window, &window.editor_data.selected).into(),
+        };
+
+        self.metrics = window.document_mosaic.traverse(traversal).metrics();
+    }
+}
+
+fn node_name(tile: &Tile) -> String {
+    let label = SelfText(tile, "Label".to_string()).query();
+    if label.is_empty() {
+        format!("#{}", tile.id)
+    } else {
+        format!("{} (#{})", label, tile.id)
+    }
+}
+
+fn show_ranking(
+    s: &GuiState,
+    title: &str,
+    ranking: &[(Tile, f32)],
+    top_n: usize,
+    clicked: &mut Option<Vec<Tile>>,
+) {
+    if s.ui.collapsing_header(title, TreeNodeFlags::DEFAULT_OPEN) {
+        for (tile, score) in ranking.iter().take(top_n) {
+            let label = format!(
+                "{:>10.4}  {}##{}-{}",
+                score,
+                node_name(tile),
+                title,
+                tile.id
+            );
+            if s.ui.selectable(label) {
+                *clicked = Some(vec![tile.clone()]);
+            }
+        }
+    }
+}
+
+impl GraspEditorState {
+    pub fn show_metrics(&mut self, s: &GuiState) {
+        let viewport = GuiViewport::get_main_viewport();
+        if let Some(_w) =
+            s.ui.window(ImString::new("Metrics"))
+                .position([viewport.size().x - 600.0, 18.0], Condition::FirstUseEver)
+                .size([300.0, viewport.size().y - 18.0], Condition::FirstUseEver)
+                .begin()
+        {
+            let panel = &mut self.metrics_panel;
+            let window = match self.window_list.get_focused() {
+                Some(window) => window,
+                None => {
+                    s.ui.text("No document in focus.");
+                    return;
+                }
+            };
+
+            s.ui.set_next_item_width(100.0);
+            s.ui.combo_simple_string("##metrics-mode", &mut panel.filter_mode, &FILTER_MODES);
+            if panel.filter_mode != 0 {
+                s.ui.same_line();
+                s.ui.set_next_item_width(-1.0);
+                s.ui.input_text("##metrics-filter", &mut panel.filter_text)
+                    .hint("Components, comma separated")
+                    .build();
+            }
+
+            s.ui.set_next_item_width(100.0);
+            s.ui.input_int("Top N", &mut panel.top_n).build();
+            panel.top_n = panel.top_n.max(1);
+
+            s.ui.same_line();
+            let refresh = s.ui.button("Refresh");
+
+            let key = panel.key_for(window);
+            if refresh || panel.key.as_ref() != Some(&key) {
+                panel.compute(window);
+                panel.key = Some(key);
+            }
+
+            let metrics = &panel.metrics;
+            let top_n = panel.top_n as usize;
+            let mut clicked = None;
+
+            s.ui.separator();
+            s.ui.text(format!("Nodes: {}", metrics.node_count));
+            s.ui.text(format!("Arrows: {}", metrics.arrow_count));
+            s.ui.text(format!("Density: {:.4}", metrics.density));
+            s.ui.text(format!("Components: {}", metrics.components.len()));
+
+            if s.ui
+                .collapsing_header("Degree distribution", TreeNodeFlags::empty())
+            {
+                for (degree, count) in &metrics.degree_distribution {
+                    s.ui.text(format!(
+                        "{:>4}: {:>4} {}",
+                        degree,
+                        count,
+                        "|".repeat(*count)
+                    ));
+                }
+            }
+
+            if s.ui
+                .collapsing_header("Connected components", TreeNodeFlags::empty())
+            {
+                for (i, component) in metrics.components.iter().enumerate() {
+                    let label = format!("#{}: {} nodes##component-{}", i, component.len(), i);
+                    if s.ui.selectable(label) {
+                        clicked = Some(component.clone());
+                    }
+                }
+            }
+
+            show_ranking(s, "Degree", &metrics.degree, top_n, &mut clicked);
+            show_ranking(s, "Betweenness", &metrics.betweenness, top_n, &mut clicked);
+            show_ranking(s, "PageRank", &metrics.pagerank, top_n, &mut clicked);
+
+            if let Some(tiles) = clicked {
+                if let Some(window) = self.window_list.get_focused_mut() {
+                    let tiles = tiles
+                        .into_iter()
+                        .filter(|t| window.document_mosaic.is_tile_valid(&t.id))
+                        .collect_vec();
+
+                    if let Some(first) = tiles.first() {
+                        window.focus_tile(first);
+                        window.editor_data.selected = tiles;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,75 +1,456 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
 
-use super::foundation::GraspEditorState;
+use super::api::{api_routes, authorized, recover_api, same_origin};
+use super::collaboration::Participant;
+use super::foundation::{EditorOptions, GraspEditorState};
+use super::windows::GraspEditorWindow;
+use crate::core::math::Vec2;
+use crate::core::structures::grasp_queues;
+use crate::grasp_queues::WindowTransformerQueue;
+use crate::utilities::{field_names, set_field_from_str, value_to_string};
 use futures::{SinkExt, StreamExt};
-use mosaic::internals::{par, Mosaic, MosaicIO, Tile};
-use tokio::time::sleep;
+use itertools::Itertools;
+use mosaic::internals::{
+    par, pars, void, ComponentValuesBuilderSetter, EntityId, Mosaic, MosaicCRUD, MosaicIO, Tile,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc};
 use warp::filters::ws::Message;
 use warp::{filters::ws::WebSocket, Filter};
 
 use lazy_static::lazy_static;
 
 lazy_static! {
-    pub static ref DOTS: Arc<Mutex<HashMap<usize, String>>> = Arc::new(Mutex::new(HashMap::new()));
+    pub static ref DOTS: Arc<Mutex<HashMap<NetworkKey, String>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref CHANNELS: Mutex<HashMap<NetworkKey, NetworkChannels>> = Mutex::new(HashMap::new());
+    static ref NETWORK_REQUESTS: Mutex<VecDeque<NetworkRequest>> = Mutex::new(VecDeque::new());
+    static ref NETWORKED: Mutex<BTreeMap<NetworkKey, String>> = Mutex::new(BTreeMap::new());
 }
 
-pub async fn client_connection(ws: WebSocket, id: u16) {
+pub const DEFAULT_PORT: u16 = 9000;
+
+static NEXT_CLIENT: AtomicUsize = AtomicUsize::new(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum NetworkKey {
+    Mosaic(usize),
+    Dot(usize, usize),
+}
+
+impl NetworkKey {
+    pub fn path(&self) -> String {
+        match self {
+            NetworkKey::Mosaic(id) => format!("mosaic/{}", id),
+            NetworkKey::Dot(mosaic, tile) => format!("dot/{}/{}", mosaic, tile),
+        }
+    }
+
+    fn mosaic(&self) -> usize {
+        match self {
+            NetworkKey::Mosaic(id) | NetworkKey::Dot(id, _) => *id,
+        }
+    }
+}
+
+pub fn register_networked(key: NetworkKey, name: &str) {
+    NETWORKED.lock().unwrap().insert(key, name.to_string());
+}
+
+// dropping the channels closes every connection to the entity
+pub fn unregister_networked(key: NetworkKey) {
+    let removed = |k: &NetworkKey| match key {
+        NetworkKey::Mosaic(id) => k.mosaic() == id,
+        _ => *k == key,
+    };
+
+    NETWORKED.lock().unwrap().retain(|k, _| !removed(k));
+    DOTS.lock().unwrap().retain(|k, _| !removed(k));
+    CHANNELS.lock().unwrap().retain(|k, _| !removed(k));
+}
+
+pub fn is_networked(key: &NetworkKey) -> bool {
+    NETWORKED.lock().unwrap().contains_key(key)
+}
+
+#[derive(Clone)]
+struct NetworkChannels {
+    dots: broadcast::Sender<String>,
+    tiles: broadcast::Sender<String>,
+}
+
+fn channels(key: NetworkKey) -> NetworkChannels {
+    CHANNELS
+        .lock()
+        .unwrap()
+        .entry(key)
+        .or_insert_with(|| NetworkChannels {
+            dots: broadcast::channel(16).0,
+            tiles: broadcast::channel(1024).0,
+        })
+        .clone()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TileJson {
+    pub id: EntityId,
+    pub kind: String,
+    pub component: String,
+    pub source: EntityId,
+    pub target: EntityId,
+    pub fields: BTreeMap<String, String>,
+}
+
+impl From<&Tile> for TileJson {
+    fn from(tile: &Tile) -> Self {
+        let kind = if tile.is_object() {
+            "object"
+        } else if tile.is_arrow() {
+            "arrow"
+        } else if tile.is_descriptor() {
+            "descriptor"
+        } else {
+            "extension"
+        };
+
+        TileJson {
+            id: tile.id,
+            kind: kind.to_string(),
+            component: tile.component.to_string(),
+            source: tile.source_id(),
+            target: tile.target_id(),
+            fields: field_names(tile)
+                .into_iter()
+                .map(|f| {
+                    let value = value_to_string(&tile.get(&f));
+                    (f, value)
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Subscribe,
+    CreateObject {
+        component: String,
+        #[serde(default)]
+        fields: BTreeMap<String, String>,
+    },
+    Link {
+        source: EntityId,
+        target: EntityId,
+        component: String,
+        #[serde(default)]
+        fields: BTreeMap<String, String>,
+    },
+    SetField {
+        tile: EntityId,
+        field: String,
+        value: String,
+    },
+    Delete {
+        tile: EntityId,
+    },
+    RunTransformer {
+        name: String,
+        #[serde(default)]
+        tiles: Vec<EntityId>,
+    },
+    Create {
+        tile: TileJson,
+    },
+    Presence {
+        name: String,
+        cursor: [f32; 2],
+        #[serde(default)]
+        selection: Vec<EntityId>,
+    },
+    Leave,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Snapshot { tiles: Vec<TileJson> },
+    Created { tile: TileJson },
+    Updated { tile: TileJson },
+    Deleted { id: EntityId },
+    Dot { content: String },
+    Done { tile: Option<EntityId> },
+    Error { message: String },
+    Welcome { client: usize },
+    Presence { participants: Vec<Participant> },
+}
+
+impl ServerMessage {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+pub struct NetworkRequest {
+    pub key: NetworkKey,
+    pub client: usize,
+    pub message: ClientMessage,
+    pub reply: mpsc::UnboundedSender<String>,
+}
+
+fn enqueue_network_request(request: NetworkRequest) {
+    NETWORK_REQUESTS.lock().unwrap().push_back(request);
+}
+
+fn dequeue_network_request() -> Option<NetworkRequest> {
+    NETWORK_REQUESTS.lock().unwrap().pop_front()
+}
+
+// what subscribers of a mosaic last saw
+pub struct NetworkSnapshot {
+    revision: Option<u64>,
+    tiles: HashMap<EntityId, TileJson>,
+}
+
+pub fn publish_event(key: NetworkKey, message: &ServerMessage) {
+    let _ = channels(key).tiles.send(message.to_json());
+}
+
+pub fn has_component_type(mosaic: &Arc<Mosaic>, component: &str) -> bool {
+    mosaic
+        .component_registry
+        .get_component_type(component.into())
+        .into_iter()
+        .next()
+        .is_some()
+}
+
+pub fn publish_dot(key: NetworkKey, content: String) {
+    DOTS.lock().unwrap().insert(key, content.clone());
+    let _ = channels(key).dots.send(content);
+}
+
+pub fn tile_map(mosaic: &Arc<Mosaic>) -> HashMap<EntityId, TileJson> {
+    mosaic
+        .get_all()
+        .map(|t| (t.id, TileJson::from(&t)))
+        .collect()
+}
+
+pub fn tile_events(
+    previous: &HashMap<EntityId, TileJson>,
+    current: &HashMap<EntityId, TileJson>,
+) -> Vec<ServerMessage> {
+    let mut events = vec![];
+
+    for (id, tile) in current.iter().sorted_by_key(|(id, _)| **id) {
+        match previous.get(id) {
+            None => events.push(ServerMessage::Created { tile: tile.clone() }),
+            Some(old) if old != tile => events.push(ServerMessage::Updated { tile: tile.clone() }),
+            _ => {}
+        }
+    }
+
+    for id in previous.keys().sorted() {
+        if !current.contains_key(id) {
+            events.push(ServerMessage::Deleted { id: *id });
+        }
+    }
+
+    events
+}
+
+pub async fn client_connection(ws: WebSocket, key: NetworkKey) {
     println!("establishing client connection... {:?}", ws);
 
-    let (mut sender, mut _receiver) = ws.split();
+    let (mut sender, mut receiver) = ws.split();
+    let client = NEXT_CLIENT.fetch_add(1, Ordering::Relaxed);
+    let (reply, mut replies) = mpsc::unbounded_channel::<String>();
+    let mut dots = channels(key).dots.subscribe();
+    let mut tiles: Option<broadcast::Receiver<String>> = None;
 
-    let mut last_message = String::new();
+    // clients that never subscribe keep receiving plain DOT text
+    let last_dot = DOTS.lock().unwrap().get(&key).cloned();
+    if let Some(dot) = last_dot {
+        if sender.send(Message::text(dot)).await.is_err() {
+            return;
+        }
+    }
 
     loop {
-        let maybe_message = {
-            let lock = DOTS.lock().unwrap();
-            lock.get(&(id as usize)).cloned()
+        let outgoing = tokio::select! {
+            biased;
+
+            incoming = receiver.next() => match incoming {
+                Some(Ok(message)) if message.is_text() => {
+                    match serde_json::from_str::<ClientMessage>(message.to_str().unwrap_or_default()) {
+                        Ok(message) => {
+                            if message == ClientMessage::Subscribe && tiles.is_none() {
+                                tiles = Some(channels(key).tiles.subscribe());
+                                let _ = reply.send(ServerMessage::Welcome { client }.to_json());
+                            }
+
+                            enqueue_network_request(NetworkRequest {
+                                key,
+                                client,
+                                message,
+                                reply: reply.clone(),
+                            });
+                            None
+                        }
+                        Err(error) => Some(
+                            ServerMessage::Error {
+                                message: format!("invalid message: {}", error),
+                            }
+                            .to_json(),
+                        ),
+                    }
+                }
+                Some(Ok(message)) if message.is_close() => break,
+                Some(Ok(_)) => None,
+                _ => break,
+            },
+
+            Some(message) = replies.recv() => Some(message),
+
+            event = async {
+                match tiles.as_mut() {
+                    Some(tiles) => tiles.recv().await,
+                    None => futures::future::pending().await,
+                }
+            } => match event {
+                Ok(event) => Some(event),
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    enqueue_network_request(NetworkRequest {
+                        key,
+                        client,
+                        message: ClientMessage::Subscribe,
+                        reply: reply.clone(),
+                    });
+                    None
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+
+            dot = dots.recv() => match dot {
+                Ok(content) if tiles.is_some() => Some(ServerMessage::Dot { content }.to_json()),
+                Ok(content) => Some(content),
+                Err(broadcast::error::RecvError::Lagged(_)) => None,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
         };
 
-        if let Some(message) = maybe_message {
-            if last_message != message {
-                last_message = message.clone();
-                sender.send(Message::text(message)).await.unwrap();
+        if let Some(outgoing) = outgoing {
+            if sender.send(Message::text(outgoing)).await.is_err() {
+                break;
             }
         }
-
-        sleep(Duration::from_millis(1000)).await;
     }
+
+    enqueue_network_request(NetworkRequest {
+        key,
+        client,
+        message: ClientMessage::Leave,
+        reply,
+    });
 }
 
-pub async fn run_server(id: u16) {
-    println!("Running server for id {:?}", id);
-    let websocket = warp::path("graph")
+#[derive(Serialize)]
+struct EntityEntry {
+    path: String,
+    name: String,
+}
+
+pub async fn run_server(options: EditorOptions) {
+    let port = options.network_port;
+    let address = options
+        .network_address
+        .parse::<IpAddr>()
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+    println!("Running server on {}:{}", address, port);
+
+    let page =
+        warp::path::end().map(|| warp::reply::html(include_str!("../../assets/viewer/index.html")));
+
+    let entities = warp::path!("entities")
+        .and(same_origin())
+        .and(authorized(options.api_token.clone()))
+        .map(|| {
+            let entries = NETWORKED
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(key, name)| EntityEntry {
+                    path: key.path(),
+                    name: name.clone(),
+                })
+                .collect_vec();
+            warp::reply::json(&entries)
+        });
+
+    let websocket = warp::path!("mosaic" / usize / "ws")
+        .and(same_origin())
+        .and(authorized(options.api_token.clone()))
         .and(warp::ws())
-        .map(move |ws: warp::ws::Ws| {
-            println!("Upgrading connection to websocket");
-            ws.on_upgrade(move |ws| client_connection(ws, id))
+        .and_then(|id: usize, ws: warp::ws::Ws| async move {
+            let key = NetworkKey::Mosaic(id);
+            if is_networked(&key) {
+                println!("Upgrading connection to websocket");
+                Ok(ws.on_upgrade(move |ws| client_connection(ws, key)))
+            } else {
+                Err(warp::reject::not_found())
+            }
         });
 
-    warp::serve(websocket.with(warp::cors().allow_any_origin()))
-        .run(([127, 0, 0, 1], id))
-        .await;
-}
+    let dot = |key: NetworkKey| {
+        let content = DOTS.lock().unwrap().get(&key).cloned();
+        content.ok_or_else(warp::reject::not_found)
+    };
+
+    let mosaic_dot = warp::path!("mosaic" / usize / "dot")
+        .and(same_origin())
+        .and(authorized(options.api_token.clone()))
+        .and_then(move |id: usize| async move { dot(NetworkKey::Mosaic(id)) });
+
+    let tile_dot = warp::path!("dot" / usize / usize)
+        .and(same_origin())
+        .and(authorized(options.api_token.clone()))
+        .and_then(
+            move |mosaic: usize, tile: usize| async move { dot(NetworkKey::Dot(mosaic, tile)) },
+        );
 
-pub const MIN_PORT: usize = 9001;
+    let routes = api_routes(options.api_token)
+        .or(page)
+        .or(entities)
+        .or(websocket)
+        .or(mosaic_dot)
+        .or(tile_dot)
+        .recover(recover_api);
+
+    warp::serve(routes).run((address, port)).await;
+}
 
 pub trait Networked {
-    fn get_id(&self) -> usize;
+    fn get_key(&self) -> NetworkKey;
+    fn get_name(&self) -> String;
     fn prepare_content(&self) -> String;
 
     fn initialize_networked(&mut self) {
-        println!("Registering new id: {}", self.get_id());
-
-        let id = self.get_id() as u16;
-        tokio::spawn(run_server(id));
+        println!("Registering {}", self.get_key().path());
+        register_networked(self.get_key(), &self.get_name());
     }
 }
 
 impl Networked for GraspEditorState {
-    fn get_id(&self) -> usize {
-        MIN_PORT + self.editor_state_tile.id
+    fn get_key(&self) -> NetworkKey {
+        NetworkKey::Mosaic(self.editor_mosaic.id)
+    }
+
+    fn get_name(&self) -> String {
+        "Editor".to_string()
     }
 
     fn prepare_content(&self) -> String {
@@ -78,8 +459,12 @@ impl Networked for GraspEditorState {
 }
 
 impl Networked for Arc<Mosaic> {
-    fn get_id(&self) -> usize {
-        MIN_PORT + self.id
+    fn get_key(&self) -> NetworkKey {
+        NetworkKey::Mosaic(self.id)
+    }
+
+    fn get_name(&self) -> String {
+        format!("Mosaic {}", self.id)
     }
 
     fn prepare_content(&self) -> String {
@@ -88,8 +473,12 @@ impl Networked for Arc<Mosaic> {
 }
 
 impl Networked for Tile {
-    fn get_id(&self) -> usize {
-        MIN_PORT + self.id
+    fn get_key(&self) -> NetworkKey {
+        NetworkKey::Dot(self.mosaic.id, self.id)
+    }
+
+    fn get_name(&self) -> String {
+        format!("Dot {}", self.id)
     }
 
     fn prepare_content(&self) -> String {
@@ -108,7 +497,281 @@ pub trait NetworkCapability {
 impl NetworkCapability for Arc<Mosaic> {
     fn make_snapshot_step(&self, name: &str) {
         let content = self.dot(name);
-        let mut dot = self.new_object("Dot", par(content));
+        let mut dot = self.new_object("Dot", par(content.clone()));
         dot.initialize_networked();
+        publish_dot(dot.get_key(), content);
+    }
+}
+
+pub fn on_dot_deleted(_window: &mut GraspEditorWindow, comp: String, dot: &Tile) {
+    assert_eq!(&comp, "Dot");
+    unregister_networked(dot.get_key());
+}
+
+// everything but hosted windows can only be watched
+fn read_only(key: NetworkKey) -> String {
+    format!("{} is not a hosted window and cannot be edited", key.path())
+}
+
+fn set_fields(tile: &Tile, fields: &BTreeMap<String, String>) -> Result<(), String> {
+    for (field, value) in fields {
+        set_field_from_str(&mut tile.clone(), field, value)?;
+    }
+
+    Ok(())
+}
+
+impl GraspEditorState {
+    pub fn networked_mosaics(&self) -> Vec<(NetworkKey, Arc<Mosaic>)> {
+        let mut mosaics = vec![
+            (self.get_key(), Arc::clone(&self.editor_mosaic)),
+            (
+                self.component_mosaic.get_key(),
+                Arc::clone(&self.component_mosaic),
+            ),
+            (
+                self.transformer_mosaic.get_key(),
+                Arc::clone(&self.transformer_mosaic),
+            ),
+        ];
+
+        for window in &self.window_list.windows {
+            mosaics.push((
+                window.document_mosaic.get_key(),
+                Arc::clone(&window.document_mosaic),
+            ));
+        }
+
+        mosaics
+    }
+
+    pub fn process_network_request_queue(&mut self) {
+        while let Some(request) = dequeue_network_request() {
+            let reply = match self.apply_network_request(&request) {
+                Ok(message) => message,
+                Err(message) => ServerMessage::Error { message },
+            };
+
+            let _ = request.reply.send(reply.to_json());
+        }
+
+        self.publish_tile_events();
+        self.publish_presence();
+    }
+
+    // document windows are only diffed again once their revision moved on
+    fn publish_tile_events(&mut self) {
+        let revisions = self
+            .window_list
+            .windows
+            .iter()
+            .map(|w| (w.document_mosaic.get_key(), w.revision))
+            .collect::<HashMap<_, _>>();
+
+        for (key, mosaic) in self.networked_mosaics() {
+            let sender = channels(key).tiles;
+            if sender.receiver_count() == 0 {
+                self.network_snapshots.remove(&key);
+                continue;
+            }
+
+            let revision = revisions.get(&key).copied();
+            let previous = self.network_snapshots.get(&key);
+            if revision.is_some() && previous.is_some_and(|p| p.revision == revision) {
+                continue;
+            }
+
+            let current = tile_map(&mosaic);
+            if let Some(previous) = previous {
+                for event in tile_events(&previous.tiles, &current) {
+                    let _ = sender.send(event.to_json());
+                }
+            }
+
+            self.network_snapshots.insert(
+                key,
+                NetworkSnapshot {
+                    revision,
+                    tiles: current,
+                },
+            );
+        }
+    }
+
+    fn apply_network_request(&mut self, request: &NetworkRequest) -> Result<ServerMessage, String> {
+        let mosaic = self
+            .networked_mosaics()
+            .into_iter()
+            .find(|(key, _)| *key == request.key)
+            .map(|(_, mosaic)| mosaic)
+            .ok_or(format!("nothing is served under {}", request.key.path()))?;
+
+        let get_tile = |id: EntityId| mosaic.get(id).ok_or(format!("tile {} does not exist", id));
+
+        let has_type = |component: &str| has_component_type(&mosaic, component);
+
+        // only windows hosted from the Session menu take edits
+        let hosted = self.collaboration.hosted.contains_key(&request.key);
+        let window = self
+            .window_list
+            .windows
+            .iter_mut()
+            .find(|w| w.document_mosaic.get_key() == request.key)
+            .filter(|_| hosted);
+
+        match &request.message {
+            ClientMessage::Subscribe => {
+                self.publish_tile_events();
+                let tiles = tile_map(&mosaic)
+                    .into_values()
+                    .sorted_by_key(|t| t.id)
+                    .collect_vec();
+                Ok(ServerMessage::Snapshot { tiles })
+            }
+            ClientMessage::CreateObject { component, fields } => {
+                let window = window.ok_or(read_only(request.key))?;
+
+                // placed like a double click, which also registers the Node type
+                let tile = if component == "Node" {
+                    let coordinate = |name: &str| {
+                        fields
+                            .get(name)
+                            .and_then(|v| v.parse::<f32>().ok())
+                            .unwrap_or_default()
+                    };
+
+                    window.create_new_object(Vec2::new(coordinate("x"), coordinate("y")))
+                } else if has_type(component) {
+                    let tile = mosaic.new_object(component, void());
+                    set_fields(&tile, fields)?;
+                    tile
+                } else {
+                    return Err(format!("unknown component '{}'", component));
+                };
+
+                window.mark_changed();
+                window.request_quadtree_update();
+
+                Ok(ServerMessage::Done {
+                    tile: Some(tile.id),
+                })
+            }
+            ClientMessage::Link {
+                source,
+                target,
+                component,
+                fields,
+            } => {
+                let window = window.ok_or(read_only(request.key))?;
+                if !has_type(component) {
+                    return Err(format!("unknown component '{}'", component));
+                }
+
+                let arrow =
+                    mosaic.new_arrow(&get_tile(*source)?, &get_tile(*target)?, component, void());
+                set_fields(&arrow, fields)?;
+
+                window.mark_changed();
+                window.request_quadtree_update();
+
+                Ok(ServerMessage::Done {
+                    tile: Some(arrow.id),
+                })
+            }
+            ClientMessage::SetField { tile, field, value } => {
+                let window = window.ok_or(read_only(request.key))?;
+                let mut tile = get_tile(*tile)?;
+                set_field_from_str(&mut tile, field, value)?;
+
+                window.mark_changed();
+                window.request_quadtree_update();
+
+                Ok(ServerMessage::Done {
+                    tile: Some(tile.id),
+                })
+            }
+            ClientMessage::Delete { tile } => {
+                let window = window.ok_or(read_only(request.key))?;
+                let tile = get_tile(*tile)?;
+                window.delete_tiles(&[tile.clone()]);
+                window.mark_changed();
+
+                Ok(ServerMessage::Done {
+                    tile: Some(tile.id),
+                })
+            }
+            ClientMessage::RunTransformer { name, tiles } => {
+                if !self.transformer_functions.contains_key(name) {
+                    return Err(format!("unknown transformer '{}'", name));
+                }
+
+                let window = window.ok_or(read_only(request.key))?;
+
+                window.editor_data.selected = tiles
+                    .iter()
+                    .map(|id| get_tile(*id))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let request = self.editor_mosaic.new_object(
+                    "WindowTransformerRequest",
+                    pars()
+                        .set("transform", name.as_str())
+                        .set("window_index", window.window_tile.id as u64)
+                        .ok(),
+                );
+                grasp_queues::enqueue(WindowTransformerQueue, request);
+
+                Ok(ServerMessage::Done { tile: None })
+            }
+            ClientMessage::Create { tile } => {
+                let window = window.ok_or(read_only(request.key))?;
+                if !has_type(&tile.component) {
+                    return Err(format!("unknown component '{}'", tile.component));
+                }
+
+                let component = tile.component.as_str();
+                let created = match tile.kind.as_str() {
+                    "object" => mosaic.new_object(component, void()),
+                    "arrow" => mosaic.new_arrow(
+                        &get_tile(tile.source)?,
+                        &get_tile(tile.target)?,
+                        component,
+                        void(),
+                    ),
+                    "descriptor" => {
+                        mosaic.new_descriptor(&get_tile(tile.target)?, component, void())
+                    }
+                    "extension" => mosaic.new_extension(&get_tile(tile.source)?, component, void()),
+                    other => return Err(format!("unknown tile kind '{}'", other)),
+                };
+                set_fields(&created, &tile.fields)?;
+
+                window.mark_changed();
+                window.request_quadtree_update();
+
+                Ok(ServerMessage::Done {
+                    tile: Some(created.id),
+                })
+            }
+            ClientMessage::Presence {
+                name,
+                cursor,
+                selection,
+            } => {
+                self.collaboration.update_participant(
+                    request.key,
+                    request.client,
+                    name,
+                    *cursor,
+                    selection,
+                )?;
+                Ok(ServerMessage::Done { tile: None })
+            }
+            ClientMessage::Leave => {
+                self.collaboration
+                    .remove_participant(request.key, request.client);
+                Ok(ServerMessage::Done { tile: None })
+            }
+        }
     }
 }
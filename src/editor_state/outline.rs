@@ -0,0 +1,256 @@
+use imgui::{MouseButton, TreeNodeFlags};
+use itertools::Itertools;
+use mosaic::{
+    capabilities::ArchetypeSubject,
+    internals::{MosaicIO, Tile},
+    iterators::tile_getters::TileGetters,
+};
+
+use crate::{
+    core::structures::{ListTile, PairTile},
+    querying::query_tiles::is_query_tile,
+    utilities::SelfText,
+    GuiState,
+};
+
+use super::{foundation::GraspEditorState, selection::SelectionTile};
+
+const COLLECTIONS: [(&str, &str); 3] = [
+    ("List", "Lists"),
+    ("Pair", "Pairs"),
+    ("SelectionOwner", "Selections"),
+];
+
+#[derive(Default)]
+pub struct OutlinePanel {
+    pub filter: String,
+    synced_selection: Vec<usize>,
+}
+
+struct OutlineClick {
+    tile: Tile,
+    toggle: bool,
+    focus: bool,
+}
+
+fn outline_name(tile: &Tile) -> String {
+    let label = SelfText(tile, "Label".to_string()).query();
+    let name = if label.is_empty() {
+        format!("[{}] #{}", tile.component, tile.id)
+    } else {
+        format!("{} [{}] #{}", label, tile.component, tile.id)
+    };
+
+    if tile.is_arrow() {
+        format!("{} -> #{}", name, tile.target_id())
+    } else {
+        name
+    }
+}
+
+fn collection_members(component: &str, owner: &Tile) -> Vec<Tile> {
+    match component {
+        "List" => ListTile::from_tile(owner.clone())
+            .map(|l| l.iter().collect_vec())
+            .unwrap_or_default(),
+        "Pair" => PairTile::from_tile(owner.clone())
+            .map(|p| {
+                [p.get_first(), p.get_second()]
+                    .into_iter()
+                    .flatten()
+                    .collect_vec()
+            })
+            .unwrap_or_default(),
+        _ => SelectionTile::from_tile(owner.clone()).iter().collect_vec(),
+    }
+}
+
+fn is_collection(tile: &Tile) -> bool {
+    COLLECTIONS
+        .iter()
+        .any(|(component, _)| tile.get_component(component).is_some())
+}
+
+fn outline_node(
+    s: &GuiState,
+    tile: &Tile,
+    selected: &[usize],
+    reveal: bool,
+    clicked: &mut Option<OutlineClick>,
+) {
+    let arrows = tile.iter().get_arrows_from().collect_vec();
+    let details = tile
+        .iter()
+        .get_descriptors()
+        .chain(tile.iter().get_extensions())
+        .collect_vec();
+
+    let mut flags = TreeNodeFlags::OPEN_ON_ARROW | TreeNodeFlags::SPAN_AVAIL_WIDTH;
+    if selected.contains(&tile.id) {
+        flags |= TreeNodeFlags::SELECTED;
+    }
+
+    if arrows.is_empty() && details.is_empty() {
+        flags |= TreeNodeFlags::LEAF;
+    }
+
+    let node =
+        s.ui.tree_node_config(format!("{}##outline-{}", outline_name(tile), tile.id))
+            .flags(flags)
+            .push();
+
+    if reveal && selected.first() == Some(&tile.id) {
+        s.ui.set_scroll_here_y_with_ratio(0.5);
+    }
+
+    if s.ui.is_item_hovered() && s.ui.is_mouse_double_clicked(MouseButton::Left) {
+        *clicked = Some(OutlineClick {
+            tile: tile.clone(),
+            toggle: false,
+            focus: true,
+        });
+    } else if s.ui.is_item_clicked() && !s.ui.is_item_toggled_open() {
+        *clicked = Some(OutlineClick {
+            tile: tile.clone(),
+            toggle: s.ui.io().key_ctrl,
+            focus: false,
+        });
+    }
+
+    if let Some(_node) = node {
+        for arrow in &arrows {
+            outline_node(s, arrow, selected, reveal, clicked);
+        }
+
+        for detail in &details {
+            s.ui.bullet_text(format!("[{}] #{}", detail.component, detail.id));
+        }
+    }
+}
+
+impl GraspEditorState {
+    pub fn show_outline(&mut self, s: &GuiState) {
+        let panel = &mut self.outline_panel;
+        let Some(window) = self.window_list.get_focused_mut() else {
+            s.ui.text_disabled("No document is focused");
+            return;
+        };
+
+        s.ui.set_next_item_width(-1.0);
+        s.ui.input_text("##outline-filter", &mut panel.filter)
+            .hint("Filter")
+            .build();
+
+        // the tree scrolls to a selection that was made on the canvas
+        let selected = window
+            .editor_data
+            .selected
+            .iter()
+            .map(|t| t.id)
+            .collect_vec();
+        let reveal = selected != panel.synced_selection;
+        panel.synced_selection = selected.clone();
+
+        let filter = panel.filter.to_lowercase();
+        let matches =
+            |t: &Tile| filter.is_empty() || outline_name(t).to_lowercase().contains(&filter);
+
+        let groups = window
+            .document_mosaic
+            .get_all()
+            .filter(|t| t.is_object() && !is_collection(t) && !is_query_tile(t))
+            .filter(matches)
+            .into_group_map_by(|t| SelfText(t, "Label".to_string()).query());
+
+        let mut clicked = None;
+
+        if let Some(_c) = s.ui.child_window("##outline-tree").begin() {
+            if let Some(_objects) =
+                s.ui.tree_node_config("Objects")
+                    .flags(TreeNodeFlags::DEFAULT_OPEN)
+                    .push()
+            {
+                for (label, tiles) in groups.iter().sorted_by_key(|(label, _)| *label) {
+                    if reveal && tiles.iter().any(|t| selected.contains(&t.id)) {
+                        s.ui.set_next_item_open(true);
+                    }
+
+                    let title = if label.is_empty() {
+                        "(unlabeled)"
+                    } else {
+                        label.as_str()
+                    };
+                    if let Some(_group) =
+                        s.ui.tree_node_config(format!(
+                            "{} ({})##outline-group-{}",
+                            title,
+                            tiles.len(),
+                            label
+                        ))
+                        .push()
+                    {
+                        for tile in tiles.iter().sorted_by_key(|t| t.id) {
+                            outline_node(s, tile, &selected, reveal, &mut clicked);
+                        }
+                    }
+                }
+            }
+
+            if let Some(_collections) = s.ui.tree_node("Collections") {
+                for (component, title) in COLLECTIONS {
+                    let owners = window
+                        .document_mosaic
+                        .get_all()
+                        .filter(|t| t.is_object() && t.get_component(component).is_some())
+                        .map(|t| (collection_members(component, &t), t))
+                        .filter(|(members, owner)| matches(owner) || members.iter().any(matches))
+                        .sorted_by_key(|(_, owner)| owner.id)
+                        .collect_vec();
+
+                    if let Some(_kind) = s.ui.tree_node(format!("{} ({})", title, owners.len())) {
+                        for (members, owner) in owners {
+                            if reveal && members.iter().any(|t| selected.contains(&t.id)) {
+                                s.ui.set_next_item_open(true);
+                            }
+
+                            if let Some(_owner) =
+                                s.ui.tree_node_config(format!(
+                                    "{}##outline-{}",
+                                    outline_name(&owner),
+                                    owner.id
+                                ))
+                                .push()
+                            {
+                                for member in &members {
+                                    outline_node(s, member, &selected, reveal, &mut clicked);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(click) = clicked {
+            if click.focus {
+                window.focus_tile(&click.tile);
+            } else {
+                let selection = &mut window.editor_data.selected;
+                if !click.toggle {
+                    *selection = vec![click.tile];
+                } else if let Some(index) = selection.iter().position(|t| t.id == click.tile.id) {
+                    selection.remove(index);
+                } else {
+                    selection.push(click.tile);
+                }
+            }
+
+            panel.synced_selection = window
+                .editor_data
+                .selected
+                .iter()
+                .map(|t| t.id)
+                .collect_vec();
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use imgui::{Condition, ImString, Key, WindowFlags};
+use itertools::Itertools;
+
+use crate::{core::gui::docking::GuiViewport, GuiState};
+
+use super::foundation::GraspEditorState;
+
+#[derive(Default)]
+pub struct CommandPalette {
+    pub visible: bool,
+    query: String,
+    selected: usize,
+    focus_query: bool,
+}
+
+impl CommandPalette {
+    pub fn open(&mut self) {
+        self.visible = true;
+        self.query.clear();
+        self.selected = 0;
+        self.focus_query = true;
+    }
+}
+
+fn score_from(query: &[char], text: &[char], start: usize) -> Option<i32> {
+    let mut score = 0;
+    let mut position = start;
+    let mut previous: Option<usize> = None;
+
+    for c in query {
+        let found = position + text[position..].iter().position(|t| t == c)?;
+
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        } else if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        } else {
+            score -= (found - position).min(3) as i32;
+        }
+
+        score += 1;
+        previous = Some(found);
+        position = found + 1;
+    }
+
+    Some(score)
+}
+
+// every query character has to appear in order, runs and word starts score higher
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let query = query
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect_vec();
+    let text = text.to_lowercase().chars().collect_vec();
+
+    let Some(first) = query.first() else {
+        return Some(0);
+    };
+
+    // the greedy match is tried from every place the first character appears
+    (0..text.len())
+        .filter(|i| text[*i] == *first)
+        .filter_map(|i| score_from(&query, &text, i))
+        .max()
+}
+
+impl GraspEditorState {
+    pub fn show_command_palette(&mut self, s: &GuiState) {
+        if !self.command_palette.visible {
+            return;
+        }
+
+        let matches = self
+            .commands()
+            .into_iter()
+            .filter_map(|c| Some((fuzzy_score(&self.command_palette.query, &c.title)?, c)))
+            .sorted_by_key(|(score, c)| (-score, c.title.clone()))
+            .map(|(_, c)| c)
+            .collect_vec();
+
+        let viewport = GuiViewport::get_main_viewport();
+        let mut chosen = None;
+        let mut visible = true;
+
+        if let Some(_w) =
+            s.ui.window(ImString::new("Command Palette"))
+                .position(
+                    [viewport.size().x / 2.0 - 250.0, 40.0],
+                    Condition::Appearing,
+                )
+                .size([500.0, 320.0], Condition::Appearing)
+                .flags(WindowFlags::NO_COLLAPSE)
+                .opened(&mut visible)
+                .begin()
+        {
+            let palette = &mut self.command_palette;
+            if palette.focus_query {
+                s.ui.set_keyboard_focus_here();
+                palette.focus_query = false;
+            }
+
+            s.ui.set_next_item_width(-1.0);
+            if s.ui
+                .input_text("##palette-query", &mut palette.query)
+                .hint("Type a command")
+                .build()
+            {
+                palette.selected = 0;
+            }
+
+            if s.ui.is_key_pressed(Key::DownArrow) {
+                palette.selected = (palette.selected + 1).min(matches.len().saturating_sub(1));
+            } else if s.ui.is_key_pressed(Key::UpArrow) {
+                palette.selected = palette.selected.saturating_sub(1);
+            } else if s.ui.is_key_pressed(Key::Enter) {
+                chosen = matches.get(palette.selected).map(|c| c.id.clone());
+            } else if s.ui.is_key_pressed(Key::Escape) {
+                visible = false;
+            }
+
+            if let Some(_c) = s.ui.child_window("##palette-results").begin() {
+                for (index, command) in matches.iter().enumerate() {
+                    let shortcut = self.keymap.chord_for(&command.id).unwrap_or_default();
+                    let label = format!("{}##palette-{}", command.title, index);
+
+                    if s.ui
+                        .selectable_config(label)
+                        .selected(index == self.command_palette.selected)
+                        .build()
+                    {
+                        chosen = Some(command.id.clone());
+                    }
+
+                    if !shortcut.is_empty() {
+                        s.ui.same_line();
+                        s.ui.text_disabled(shortcut);
+                    }
+                }
+            }
+        }
+
+        self.command_palette.visible = visible && chosen.is_none();
+        if let Some(id) = chosen {
+            self.run_command(s, &id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod palette_tests {
+    use super::fuzzy_score;
+
+    #[test]
+    fn test_fuzzy_subsequence() {
+        assert!(fuzzy_score("svsel", "Document: Save Selection As...").is_some());
+        assert!(fuzzy_score("", "Edit: Undo").is_some());
+        assert!(fuzzy_score("undo", "Edit: Paste").is_none());
+        assert!(fuzzy_score("odnu", "Edit: Undo").is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_prefers_runs_and_word_starts() {
+        let run = fuzzy_score("grid", "Toggle Grid").unwrap();
+        let scattered = fuzzy_score("grid", "Go right inside").unwrap();
+        assert!(run > scattered);
+
+        let word_start = fuzzy_score("tg", "Toggle Grid").unwrap();
+        let inside = fuzzy_score("tg", "Stage").unwrap();
+        assert!(word_start > inside);
+    }
+}
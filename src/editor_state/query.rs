@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use imgui::{Condition, DrawListMut, ImColor32, ImString};
+use itertools::Itertools;
+use mosaic::{
+    capabilities::ArchetypeSubject,
+    internals::{EntityId, MosaicIO, Tile, TileFieldEmptyQuery},
+};
+
+use crate::{
+    core::{gui::docking::GuiViewport, math::Vec2},
+    grasp_transitions::query_position_recursive,
+    querying::{
+        collage::MosaicCollage,
+        collage_parser::{parse_collage, ParseError},
+        query_tiles::{QueryStorage, QueryTile},
+    },
+    transformers::{make_colored_selection, palette_color, select, set_color},
+    utilities::{ColorQuery, OffsetQuery, SelfText},
+    GuiState,
+};
+
+use super::{foundation::GraspEditorState, windows::GraspEditorWindow};
+
+#[derive(Default)]
+pub struct QueryConsole {
+    pub text: String,
+    pub name: String,
+    pub limit_to_selection: bool,
+    pub result: Option<Result<Vec<Tile>, ParseError>>,
+}
+
+// highlighted query results, thrown away whenever the window revision moves on
+#[derive(Default)]
+pub struct QueryHighlightCache {
+    revision: Option<u64>,
+    tiles: HashMap<EntityId, Vec<Tile>>,
+}
+
+fn tile_name(tile: &Tile) -> String {
+    let label = SelfText(tile, "Label".to_string()).query();
+    let kind = if tile.is_object() {
+        "object"
+    } else if tile.is_arrow() {
+        "arrow"
+    } else {
+        "tile"
+    };
+
+    format!("#{} {} [{}] {}", tile.id, kind, tile.component, label)
+}
+
+pub fn is_on_canvas(tile: &Tile) -> bool {
+    if tile.is_arrow() {
+        is_on_canvas(&tile.source()) && is_on_canvas(&tile.target())
+    } else {
+        tile.is_object() && tile.get_component("Position").is_some()
+    }
+}
+
+pub fn tile_screen_position(window: &GraspEditorWindow, tile: &Tile) -> Vec2 {
+    if tile.is_arrow() {
+        let p1 = window.get_position_with_offset_and_pan(query_position_recursive(&tile.source()));
+        let p2 = window.get_position_with_offset_and_pan(query_position_recursive(&tile.target()));
+        p1.lerp(p2, 0.5) + OffsetQuery(tile).query()
+    } else {
+        window.get_position_with_offset_and_pan(query_position_recursive(tile))
+    }
+}
+
+impl GraspEditorWindow {
+    pub fn save_query(&mut self, name: &str, text: &str) -> Result<QueryTile, ParseError> {
+        let collage = parse_collage(text)?;
+        let count = self.document_mosaic.get_queries().len();
+        let name = if name.trim().is_empty() {
+            format!("Query {}", count + 1)
+        } else {
+            name.trim().to_string()
+        };
+
+        let query = self.document_mosaic.make_query(&name, &collage);
+        set_color(&query.0, palette_color(count));
+        self.mark_changed();
+        Ok(query)
+    }
+
+    pub fn run_query(&mut self, query: &QueryTile) -> Vec<Tile> {
+        let tiles = match query.get_collage() {
+            Some(collage) => self
+                .document_mosaic
+                .apply_collage(&collage, None)
+                .filter(is_on_canvas)
+                .collect_vec(),
+            None => vec![],
+        };
+
+        let color = ColorQuery(&query.0).query();
+        make_colored_selection(&tiles, [color.x, color.y, color.z, 0.5]);
+        self.mark_changed();
+        tiles
+    }
+}
+
+pub fn query_highlight_renderer(
+    _s: &GuiState,
+    window: &mut GraspEditorWindow,
+    input: Tile,
+    painter: &mut DrawListMut<'_>,
+) {
+    let query = QueryTile(input);
+    let collage = match query.get_collage() {
+        Some(collage) => collage,
+        None => return,
+    };
+
+    let color = ColorQuery(&query.0).query();
+    let color = ImColor32::from_rgba_f32s(color.x, color.y, color.z, 0.9);
+
+    let cache = &mut window.query_highlights;
+    if cache.revision != Some(window.revision) {
+        cache.tiles.clear();
+        cache.revision = Some(window.revision);
+    }
+
+    let tiles = cache
+        .tiles
+        .entry(query.0.id)
+        .or_insert_with(|| {
+            window
+                .document_mosaic
+                .apply_collage(&collage, None)
+                .filter(is_on_canvas)
+                .collect_vec()
+        })
+        .clone();
+
+    for tile in tiles {
+        let pos = tile_screen_position(window, &tile);
+
+        painter
+            .add_circle([pos.x, pos.y], 14.0, color)
+            .thickness(3.0)
+            .build();
+    }
+}
+
+pub fn on_query_deleted(window: &mut GraspEditorWindow, comp: String, query: &Tile) {
+    assert_eq!(&comp, "Query");
+
+    let nodes = QueryTile(query.clone()).get_nodes();
+    window.delete_tiles(&nodes);
+}
+
+impl GraspEditorState {
+    pub fn show_query(&mut self, s: &GuiState) {
+        let viewport = GuiViewport::get_main_viewport();
+        if let Some(_w) =
+            s.ui.window(ImString::new("Query"))
+                .position([300.0, viewport.size().y - 300.0], Condition::FirstUseEver)
+                .size([500.0, 280.0], Condition::FirstUseEver)
+                .begin()
+        {
+            let console = &mut self.query_console;
+
+            s.ui.set_next_item_width(-60.0);
+            let entered =
+                s.ui.input_text("##query-text", &mut console.text)
+                    .hint("targets(arrows(include[Label](all)))")
+                    .enter_returns_true(true)
+                    .build();
+            s.ui.same_line();
+            let run = s.ui.button("Run") || entered;
+
+            s.ui.checkbox("Limit to selection", &mut console.limit_to_selection);
+            s.ui.same_line();
+            s.ui.set_next_item_width(150.0);
+            s.ui.input_text("##query-name", &mut console.name)
+                .hint("name")
+                .build();
+            s.ui.same_line();
+            let save = s.ui.button("Save");
+
+            let window = match self.window_list.get_focused_mut() {
+                Some(window) => window,
+                None => {
+                    s.ui.text("No document in focus.");
+                    return;
+                }
+            };
+
+            if save {
+                if let Err(error) = window.save_query(&console.name, &console.text) {
+                    console.result = Some(Err(error));
+                } else {
+                    console.name.clear();
+                }
+            }
+
+            if run {
+                console.result = Some(parse_collage(&console.text).map(|query| {
+                    let scope = if console.limit_to_selection {
+                        Some(window.editor_data.selected.clone())
+                    } else {
+                        None
+                    };
+
+                    window
+                        .document_mosaic
+                        .apply_collage(&query, scope)
+                        .sorted()
+                        .collect_vec()
+                }));
+            }
+
+            match &console.result {
+                Some(Err(error)) => {
+                    s.ui.text(&console.text);
+                    s.ui.text_colored(
+                        [1.0, 0.4, 0.4, 1.0],
+                        format!("{}^", " ".repeat(error.position)),
+                    );
+                    s.ui.text_colored([1.0, 0.4, 0.4, 1.0], format!("{}", error));
+                }
+                Some(Ok(tiles)) => {
+                    let tiles = tiles
+                        .iter()
+                        .filter(|t| window.document_mosaic.is_tile_valid(&t.id))
+                        .cloned()
+                        .collect_vec();
+
+                    s.ui.text(format!("{} tiles", tiles.len()));
+                    s.ui.same_line();
+                    if s.ui.button("Select") {
+                        window.editor_data.selected = tiles.clone();
+                    }
+
+                    s.ui.same_line();
+                    if s.ui.button("Make Selection") && !tiles.is_empty() {
+                        let any = tiles.first().cloned().unwrap();
+                        select(window, s, &tiles, &any);
+                        window.editor_data.selected = tiles.clone();
+                        window.mark_changed();
+                    }
+
+                    s.ui.separator();
+                    for tile in &tiles {
+                        let label = format!("{}##query-{}", tile_name(tile), tile.id);
+                        if s.ui.selectable(label) {
+                            window.focus_tile(tile);
+                        }
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+}
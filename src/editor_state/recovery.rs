@@ -0,0 +1,279 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use imgui::{Condition, ImString};
+use iso8601_timestamp::Timestamp;
+use itertools::Itertools;
+use log::{error, warn};
+use mosaic::internals::{pars, ComponentValuesBuilderSetter, MosaicCRUD, MosaicIO, Tile};
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, System};
+
+use crate::{
+    core::{gui::docking::GuiViewport, math::Vec2, structures::grasp_queues},
+    grasp_queues::WindowRenameRequestQueue,
+    GuiState,
+};
+
+use super::{foundation::GraspEditorState, windows::GraspEditorWindow};
+
+const RECOVERY_DIR: &str = "env\\recovery";
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct RecoveryEntry {
+    pub title: String,
+    pub path: Option<PathBuf>,
+    pub pan: (f32, f32),
+    pub saved_at: String,
+    #[serde(skip)]
+    key: String,
+}
+
+pub struct RecoveryState {
+    pub pending: Vec<RecoveryEntry>,
+    last_autosave: Instant,
+}
+
+impl Default for RecoveryState {
+    fn default() -> Self {
+        Self {
+            pending: find_recovery_entries(),
+            last_autosave: Instant::now(),
+        }
+    }
+}
+
+fn recovery_key(window: &GraspEditorWindow) -> String {
+    format!("{}-{}", std::process::id(), window.window_tile.id)
+}
+
+fn recovery_file(key: &str, extension: &str) -> PathBuf {
+    Path::new(RECOVERY_DIR).join(format!("{}.{}", key, extension))
+}
+
+fn discard_recovery(key: &str) {
+    let _ = fs::remove_file(recovery_file(key, "mos"));
+    let _ = fs::remove_file(recovery_file(key, "ron"));
+}
+
+// another Grasp that is still open keeps autosaving its own entries
+fn is_owner_running(key: &str) -> bool {
+    key.split_once('-')
+        .and_then(|(pid, _)| pid.parse::<u32>().ok())
+        .is_some_and(|pid| System::new().refresh_process(Pid::from_u32(pid)))
+}
+
+fn find_recovery_entries() -> Vec<RecoveryEntry> {
+    let Ok(files) = fs::read_dir(RECOVERY_DIR) else {
+        return vec![];
+    };
+
+    files
+        .flatten()
+        .map(|f| f.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "ron"))
+        .filter_map(|p| {
+            let key = p.file_stem()?.to_str()?.to_string();
+            if is_owner_running(&key) {
+                return None;
+            }
+
+            let content = fs::read_to_string(&p).ok()?;
+            match ron::from_str::<RecoveryEntry>(&content) {
+                Ok(entry) if recovery_file(&key, "mos").exists() => {
+                    Some(RecoveryEntry { key, ..entry })
+                }
+                _ => {
+                    warn!("Discarding broken recovery file {:?}", p);
+                    discard_recovery(&key);
+                    None
+                }
+            }
+        })
+        .sorted_by(|a, b| a.saved_at.cmp(&b.saved_at))
+        .collect_vec()
+}
+
+fn window_title(window: &GraspEditorWindow) -> String {
+    match window.name.split_once("] ") {
+        Some((_, title)) => title.to_string(),
+        None => window.name.clone(),
+    }
+}
+
+impl GraspEditorWindow {
+    pub fn discard_recovery(&self) {
+        discard_recovery(&recovery_key(self));
+    }
+}
+
+impl GraspEditorState {
+    // written right away, so a save or close that discards the files afterwards cannot be undone
+    pub fn autosave(&mut self) {
+        let interval = self.editor_options.autosave_seconds;
+        if interval == 0 || self.recovery.last_autosave.elapsed() < Duration::from_secs(interval) {
+            return;
+        }
+
+        self.recovery.last_autosave = Instant::now();
+
+        let mut snapshots = vec![];
+        for window in &self.window_list.windows {
+            let key = recovery_key(window);
+            if !window.changed {
+                if recovery_file(&key, "ron").exists() {
+                    discard_recovery(&key);
+                }
+                continue;
+            }
+
+            let entry = RecoveryEntry {
+                title: window_title(window),
+                path: window.path.clone(),
+                pan: (window.editor_data.pan.x, window.editor_data.pan.y),
+                saved_at: Timestamp::now_utc().to_string(),
+                key: key.clone(),
+            };
+
+            snapshots.push((key, entry, window.document_mosaic.save()));
+        }
+
+        if snapshots.is_empty() {
+            return;
+        }
+
+        if let Err(err) = fs::create_dir_all(RECOVERY_DIR) {
+            error!("Cannot create {}: {}", RECOVERY_DIR, err);
+            return;
+        }
+
+        for (key, entry, document) in snapshots {
+            // the document goes first so that a listed entry always has one
+            let written = fs::write(recovery_file(&key, "mos"), document).and_then(|_| {
+                fs::write(
+                    recovery_file(&key, "ron"),
+                    ron::to_string(&entry).unwrap_or_default(),
+                )
+            });
+
+            if let Err(err) = written {
+                error!("Cannot autosave {}: {}", entry.title, err);
+            }
+        }
+    }
+
+    pub fn restore_recovery(&mut self, entry: &RecoveryEntry) {
+        let document = match fs::read(recovery_file(&entry.key, "mos")) {
+            Ok(document) => document,
+            Err(err) => {
+                error!("Cannot read recovery file for {}: {}", entry.title, err);
+                return;
+            }
+        };
+
+        self.new_window(entry.path.as_ref());
+        let window = self.window_list.windows.front_mut().unwrap();
+        Self::prepare_mosaic(
+            &window.component_mosaic,
+            &self.editor_mosaic,
+            Arc::clone(&window.document_mosaic),
+        );
+
+        if window.document_mosaic.load(&document).is_err() {
+            let window_tile: Tile = window.window_tile.clone();
+            error!("Recovery file for {} is not a valid document", entry.title);
+            self.close_window(window_tile);
+            return;
+        }
+
+        window.editor_data.pan = Vec2::new(entry.pan.0, entry.pan.1);
+        window.mark_changed();
+
+        grasp_queues::enqueue(
+            WindowRenameRequestQueue,
+            self.editor_mosaic.new_object(
+                "WindowRenameRequest",
+                pars()
+                    .set("id", window.window_tile.id as u64)
+                    .set("index", window.window_list_index as u64)
+                    .set("name", entry.title.as_str())
+                    .ok(),
+            ),
+        );
+
+        self.editor_mosaic.request_quadtree_update();
+        discard_recovery(&entry.key);
+    }
+
+    pub fn show_recovery(&mut self, s: &GuiState) {
+        if self.recovery.pending.is_empty() {
+            return;
+        }
+
+        let viewport = GuiViewport::get_main_viewport();
+        let mut restored: Vec<String> = vec![];
+        let mut discarded: Vec<String> = vec![];
+
+        if let Some(_w) =
+            s.ui.window(ImString::new("Recover Documents"))
+                .position(
+                    [
+                        viewport.size().x / 2.0 - 200.0,
+                        viewport.size().y / 2.0 - 100.0,
+                    ],
+                    Condition::FirstUseEver,
+                )
+                .size([400.0, 200.0], Condition::FirstUseEver)
+                .begin()
+        {
+            s.ui.text_wrapped("These documents had unsaved changes when Grasp last closed:");
+            s.ui.separator();
+
+            for entry in &self.recovery.pending {
+                s.ui.text(format!("{} ({})", entry.title, entry.saved_at));
+                s.ui.same_line();
+                if s.ui
+                    .small_button(format!("Restore##recovery-{}", entry.key))
+                {
+                    restored.push(entry.key.clone());
+                }
+                s.ui.same_line();
+                if s.ui
+                    .small_button(format!("Discard##recovery-{}", entry.key))
+                {
+                    discarded.push(entry.key.clone());
+                }
+            }
+
+            s.ui.separator();
+            let keys = self.recovery.pending.iter().map(|e| e.key.clone());
+            if s.ui.button("Restore All") {
+                restored = keys.collect_vec();
+            } else {
+                s.ui.same_line();
+                if s.ui.button("Discard All") {
+                    discarded = keys.collect_vec();
+                }
+            }
+        }
+
+        let (chosen, rest): (Vec<_>, Vec<_>) = self
+            .recovery
+            .pending
+            .drain(..)
+            .partition(|e| restored.contains(&e.key) || discarded.contains(&e.key));
+        self.recovery.pending = rest;
+
+        for entry in chosen {
+            if restored.contains(&entry.key) {
+                self.restore_recovery(&entry);
+            } else {
+                discard_recovery(&entry.key);
+            }
+        }
+    }
+}
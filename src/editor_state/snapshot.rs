@@ -8,7 +8,7 @@ use mosaic::{
 
 use super::{
     foundation::GraspEditorState,
-    network::{Networked, DOTS},
+    network::{publish_dot, Networked},
 };
 
 impl GraspEditorState {
@@ -85,13 +85,23 @@ impl GraspEditorState {
     }
 
     pub fn open_snapshot(&self, name: &str, networked: &dyn Networked, mosaic: &Arc<Mosaic>) {
-        let _ = open::that(format!("http://localhost:9000/#{}", networked.get_id()));
+        let token = &self.editor_options.api_token;
+        let query = if token.is_empty() {
+            String::new()
+        } else {
+            format!("?token={}", token)
+        };
+
+        let _ = open::that(format!(
+            "http://localhost:{}/{}#{}",
+            self.editor_options.network_port,
+            query,
+            networked.get_key().path()
+        ));
         self.snapshot(name, networked, mosaic);
     }
 
     pub fn snapshot(&self, _name: &str, networked: &dyn Networked, _mosaic: &Arc<Mosaic>) {
-        let content = networked.prepare_content();
-        let mut lock = DOTS.lock().unwrap();
-        lock.insert(networked.get_id(), content);
+        publish_dot(networked.get_key(), networked.prepare_content());
     }
 }
@@ -0,0 +1,91 @@
+use log::warn;
+use mosaic::internals::{EntityId, MosaicIO};
+
+use crate::utilities::{set_field_from_str, value_to_string};
+
+use super::foundation::GraspEditorState;
+
+const UNDO_LIMIT: usize = 100;
+
+pub struct FieldChange {
+    // the window tile of the document the field belongs to
+    pub window: EntityId,
+    pub tile: EntityId,
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+// a replace across several windows is a single entry, undone in all of them at once
+pub struct UndoEntry {
+    pub description: String,
+    pub changes: Vec<FieldChange>,
+}
+
+impl GraspEditorState {
+    pub fn push_undo(&mut self, description: String, changes: Vec<FieldChange>) {
+        if changes.is_empty() {
+            return;
+        }
+
+        if self.undo_stack.len() >= UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+
+        self.undo_stack.push(UndoEntry {
+            description,
+            changes,
+        });
+    }
+
+    // undoes the latest entry that touched the focused window
+    pub fn undo(&mut self) {
+        let Some(focused) = self.window_list.get_focused().map(|w| w.window_tile.id) else {
+            return;
+        };
+
+        let Some(index) = self
+            .undo_stack
+            .iter()
+            .rposition(|e| e.changes.iter().any(|c| c.window == focused))
+        else {
+            return;
+        };
+
+        let entry = self.undo_stack.remove(index);
+        for change in entry.changes.iter().rev() {
+            let Some(window) = self.window_list.get_by_id_mut(change.window) else {
+                warn!(
+                    "Cannot undo {}: the window of tile {} was closed",
+                    entry.description, change.tile
+                );
+                continue;
+            };
+
+            let Some(mut tile) = window.document_mosaic.get(change.tile) else {
+                warn!(
+                    "Cannot undo {}: tile {} no longer exists",
+                    entry.description, change.tile
+                );
+                continue;
+            };
+
+            // a field edited after the replace keeps the newer value
+            if value_to_string(&tile.get(&change.field)) != change.after {
+                warn!(
+                    "Not undoing {} on tile {}: {} was changed since",
+                    entry.description, change.tile, change.field
+                );
+                continue;
+            }
+
+            if let Err(error) = set_field_from_str(&mut tile, &change.field, &change.before) {
+                warn!("Cannot undo {}: {}", entry.description, error);
+                continue;
+            }
+
+            window.mark_changed();
+            window.request_quadtree_update();
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::{
+    collections::BTreeMap,
     env,
-    fmt::Display,
     fs::{self, File},
     io::Write,
     path::PathBuf,
@@ -9,14 +9,16 @@ use std::{
 };
 
 use imgui::{
-    CollapsingHeader, Condition, DrawListMut, ImString, MouseButton, StyleColor, TreeNodeFlags,
-    WindowFlags,
+    internal::DataTypeKind, CollapsingHeader, Condition, DrawListMut, ImString, MouseButton,
+    StyleColor, TreeNodeFlags, WindowFlags,
 };
 use itertools::Itertools;
 use log::error;
 use mosaic::{
     capabilities::{ArchetypeSubject, QueueCapability},
-    internals::{void, Datatype, MosaicCRUD, MosaicIO, Tile, TileFieldSetter, ToByteArray, Value},
+    internals::{
+        void, Datatype, MosaicCRUD, MosaicIO, Tile, TileFieldSetter, ToByteArray, Value, S32,
+    },
     iterators::{
         component_selectors::ComponentSelectors, tile_deletion::TileDeletion,
         tile_filters::TileFilters,
@@ -31,11 +33,13 @@ use crate::{
     },
     editor_state_machine::EditorState,
     grasp_queues::CloseWindowRequestQueue,
+    utilities::value_as_f64,
     GuiState,
 };
 
 use super::{
-    file_operations::SaveFileCapability,
+    diff::{draw_diff_overlay, show_diff_changes},
+    errors::{adopt_document_errors, draw_error_badges},
     foundation::GraspEditorState,
     helpers::{QuadtreeUpdateCapability, RequireWindowFocus},
     sense::hash_input,
@@ -53,15 +57,7 @@ pub type ComponentPropertyRenderer =
 
 impl GraspEditorState {
     pub fn show(&mut self, s: &GuiState) {
-        {
-            let io = s.ui.io();
-            if io.key_ctrl && io.keys_down[22] {
-                // CTRL+S
-                if let Some(w) = self.window_list.get_focused_mut() {
-                    w.save_file()
-                }
-            }
-        }
+        self.process_keymap(s);
 
         if self.editor_options.toggle_hierarchy {
             self.show_hierarchy(s);
@@ -71,6 +67,10 @@ impl GraspEditorState {
             self.show_properties(s);
         }
 
+        if self.editor_options.toggle_metrics {
+            self.show_metrics(s);
+        }
+
         self.show_menu_bar(s);
 
         let mut caught_events = vec![];
@@ -81,6 +81,21 @@ impl GraspEditorState {
             self.show_errors(s);
         }
 
+        if self.editor_options.toggle_query {
+            self.show_query(s);
+        }
+
+        if self.editor_options.toggle_log {
+            self.show_log(s);
+        }
+
+        if self.editor_options.toggle_find {
+            self.show_find(s);
+        }
+
+        self.show_command_palette(s);
+        self.show_recovery(s);
+
         caught_events.clear();
     }
 
@@ -183,12 +198,15 @@ impl GraspEditorState {
                     }
 
                     (window.renderer)(window, s, &self.component_entity_renderers);
+                    draw_error_badges(&self.editor_mosaic, window, s);
+                    draw_diff_overlay(window, s);
+                    self.collaboration.draw_participants(window, s);
 
                     window.draw_debug(s);
 
                     if self.pending_close_window_request.is_none() {
                         window.update_context_menu(front_window_id, s);
-                        window.context_popup(s);
+                        window.context_popup(s, &self.keymap);
                     }
 
                     if self.pending_close_window_request.is_none()
@@ -218,48 +236,8 @@ impl GraspEditorState {
                 .unwrap()
                 .update(s);
         }
-    }
-
-    fn show_errors(&mut self, s: &GuiState) {
-        if let Some(_w) =
-            s.ui.window(ImString::new("Errors"))
-                .position([100.0, 100.0], Condition::FirstUseEver)
-                .size([300.0, 100.0], Condition::FirstUseEver)
-                .begin()
-        {
-            s.ui.columns(3, "errors_columns", true);
-            s.ui.set_column_width(0, 150.0);
-            s.ui.set_column_width(1, 150.0);
-
-            s.ui.text("Window ID");
-            s.ui.next_column();
 
-            s.ui.text("Entity ID");
-            s.ui.next_column();
-
-            s.ui.text("Message");
-            s.ui.next_column();
-
-            for error in self.editor_mosaic.get_all().include_component("Error") {
-                let id = error.get("window").as_u64() as usize;
-                let name = self
-                    .window_list
-                    .windows
-                    .iter()
-                    .find(|w| w.window_tile.id == id)
-                    .map(|w| w.name.clone())
-                    .unwrap_or_default()
-                    .clone();
-                s.ui.text(&name);
-                s.ui.next_column();
-
-                s.ui.text(format!("{}", error.get("target").as_u64()));
-                s.ui.next_column();
-
-                s.ui.text(&error.get("message").as_str());
-                s.ui.next_column();
-            }
-        }
+        self.run_pending_window_commands(s);
     }
 
     fn show_hierarchy(&mut self, s: &GuiState) {
@@ -297,13 +275,16 @@ impl GraspEditorState {
             let color =
                 s.ui.push_style_color(StyleColor::FrameBg, [0.1, 0.1, 0.15, 1.0]);
 
-            if s.ui.list_box("##window-list", &mut i, items.as_slice(), 20) {
+            if s.ui.list_box("##window-list", &mut i, items.as_slice(), 6) {
                 let item: &str = items.get(i as usize).unwrap();
                 self.require_named_window_focus(item);
                 println!("Focus on {}", item);
             }
 
             color.end();
+
+            s.ui.separator();
+            self.show_outline(s);
         }
     }
 
@@ -356,13 +337,55 @@ impl GraspEditorState {
                 let mut selected = focused_window.editor_data.selected.clone();
                 selected = selected.into_iter().unique().collect_vec();
 
+                if selected.len() > 1
+                    && s.ui.collapsing_header(
+                        format!("Selection ({} tiles)##selection-header", selected.len()),
+                        TreeNodeFlags::DEFAULT_OPEN,
+                    )
+                {
+                    s.ui.separator();
+                    for (component, (owners, parts)) in shared_components(&selected) {
+                        let is_bullet = is_unit_component(focused_window, &parts[0]);
+                        let title = format!(
+                            "{} ({}/{})##shared-{}",
+                            component,
+                            owners.len(),
+                            selected.len(),
+                            component
+                        );
+
+                        let open = tree(
+                            s,
+                            &title,
+                            is_bullet,
+                            !self.hidden_property_renderers.contains(&component),
+                        );
+
+                        let is_locked = self.locked_components.contains(&parts[0].component)
+                            || parts.iter().any(|p| owners.contains(&p.id));
+                        if component_menu_requested(s, is_locked) {
+                            self.queued_component_delete = Some((component, owners));
+                            s.ui.open_popup("component-menu");
+                        }
+
+                        if open.is_some() {
+                            draw_shared_property_renderer(s, focused_window, &parts);
+                        }
+                    }
+
+                    s.ui.spacing();
+                    s.ui.separator();
+                }
+
                 if !selected.is_empty() {
+                    let expanded = selected.len() == 1;
                     for selected_tile in selected {
                         let title =
                             &format!("Entity {}##{}-header", selected_tile.id, selected_tile.id);
 
-                        if let Some(_) = tree(s, title, false, true) {
+                        if let Some(_) = tree(s, title, false, expanded) {
                             s.ui.separator();
+                            show_diff_changes(focused_window, s, &selected_tile);
                             for (part, tiles) in &selected_tile
                                 .get_full_archetype()
                                 .into_iter()
@@ -370,34 +393,40 @@ impl GraspEditorState {
                                 .collect_vec()
                             {
                                 for part_tile in tiles.iter().sorted_by(|a, b| a.id.cmp(&b.id)) {
+                                    let is_locked =
+                                        self.locked_components.contains(&part_tile.component)
+                                            || part_tile.id == selected_tile.id;
+
                                     if let Some(renderer) =
                                         self.component_property_renderers.get(part)
                                     {
-                                        if let Some(_) = tree(s, &part.to_string(), false, true) {
+                                        let open = tree(s, &part.to_string(), false, true);
+                                        if component_menu_requested(s, is_locked) {
+                                            self.queued_component_delete =
+                                                Some((part.clone(), vec![selected_tile.id]));
+                                            s.ui.open_popup("component-menu");
+                                        }
+
+                                        if open.is_some() {
                                             renderer(s, focused_window, part_tile.clone());
-                                        } else {
-                                            // delete component
                                         }
                                     } else {
-                                        let is_bullet = {
-                                            let comp = focused_window
-                                                .document_mosaic
-                                                .component_registry
-                                                .get_component_type(part_tile.component)
-                                                .unwrap();
-
-                                            let fields = comp.get_fields();
-                                            fields.len() == 1
-                                                && fields.first().unwrap().datatype
-                                                    == Datatype::UNIT
-                                        };
-
-                                        if let Some(_) = tree(
+                                        let is_bullet =
+                                            is_unit_component(focused_window, part_tile);
+
+                                        let open = tree(
                                             s,
                                             &part.to_string(),
                                             is_bullet,
                                             !self.hidden_property_renderers.contains(part),
-                                        ) {
+                                        );
+                                        if component_menu_requested(s, is_locked) {
+                                            self.queued_component_delete =
+                                                Some((part.clone(), vec![selected_tile.id]));
+                                            s.ui.open_popup("component-menu");
+                                        }
+
+                                        if open.is_some() {
                                             draw_default_property_renderer(
                                                 s,
                                                 focused_window,
@@ -467,14 +496,10 @@ impl GraspEditorState {
                                         ) {
                                             let is_locked =
                                                 self.locked_components.contains(&tile.component);
-                                            let is_header_covered = s.ui.is_item_hovered();
-                                            let is_header_clicked = s
-                                                .ui
-                                                .is_item_clicked_with_button(MouseButton::Right);
-
-                                            if !is_locked && is_header_covered && is_header_clicked
-                                            {
-                                                self.queued_component_delete = Some(tile.id);
+
+                                            if component_menu_requested(s, is_locked) {
+                                                self.queued_component_delete =
+                                                    Some((component.clone(), vec![o.id]));
                                                 s.ui.open_popup("component-menu");
                                             }
 
@@ -498,6 +523,27 @@ impl GraspEditorState {
                         }
                     }
                 }
+
+                if let Some(_p) = s.ui.begin_popup("component-menu") {
+                    if let Some((component, owners)) = &self.queued_component_delete {
+                        if s.ui.menu_item(format!(
+                            "Remove {} from {} tile(s)",
+                            component,
+                            owners.len()
+                        )) {
+                            for owner in owners
+                                .iter()
+                                .filter_map(|id| focused_window.document_mosaic.get(*id))
+                            {
+                                owner.remove_components(component);
+                            }
+
+                            focused_window.mark_changed();
+                            focused_window.request_quadtree_update();
+                            self.queued_component_delete = None;
+                        }
+                    }
+                }
             }
 
             w.end();
@@ -516,6 +562,10 @@ impl GraspEditorState {
                 self.show_windows_menu(s);
             }
 
+            if let Some(_f) = s.begin_menu("Session") {
+                self.show_session_menu(s);
+            }
+
             m.end();
         }
     }
@@ -550,10 +600,12 @@ impl GraspEditorState {
             );
 
             if window_mosaic.load(&fs::read(file.clone()).unwrap()).is_ok() {
+                adopt_document_errors(&self.editor_mosaic, window);
                 Self::prepend_recent(file);
                 self.editor_mosaic.request_quadtree_update();
             } else {
                 self.editor_mosaic.make_error(
+                    "Open",
                     &format!(
                         "Cannot open file path {}: invalid format",
                         file.as_path().to_str().unwrap_or_default()
@@ -596,7 +648,7 @@ impl GraspEditorState {
         }
     }
 
-    fn open_files(&mut self) {
+    pub fn open_files(&mut self) {
         if let Some(files) = rfd::FileDialog::new()
             .add_filter("Mosaic", &["mos"])
             .set_directory(env::current_dir().unwrap())
@@ -608,7 +660,29 @@ impl GraspEditorState {
         }
     }
 
-    fn import_files(&mut self) {
+    pub fn insert_files(&mut self, grouped: bool) {
+        let Some(window) = self.window_list.get_focused_mut() else {
+            return;
+        };
+
+        if let Some(files) = rfd::FileDialog::new()
+            .add_filter("Mosaic", &["mos"])
+            .set_directory(env::current_dir().unwrap())
+            .pick_files()
+        {
+            for file in files {
+                let group = file
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .filter(|_| grouped)
+                    .map(|s| s.to_string());
+
+                window.insert_file(&file, "Insert", None, group.as_deref());
+            }
+        }
+    }
+
+    pub fn import_files(&mut self) {
         if let Some(files) = rfd::FileDialog::new()
             .set_directory(env::current_dir().unwrap())
             .pick_files()
@@ -621,17 +695,11 @@ impl GraspEditorState {
 
     fn show_document_menu(&mut self, s: &GuiState) {
         if let Some(f) = s.begin_menu("Document") {
-            if s.menu_item("New Window") {
-                self.new_window(None);
-            }
-
-            if s.menu_item("Open") {
-                self.open_files();
-            }
-
-            if s.menu_item("Import") {
-                self.import_files();
-            }
+            self.command_menu_item(s, "New Window", "document.new_window");
+            self.command_menu_item(s, "Open", "document.open");
+            self.command_menu_item(s, "Import", "document.import");
+            self.command_menu_item(s, "Insert from file...", "document.insert");
+            self.command_menu_item(s, "Insert from file as group...", "document.insert_group");
 
             if let Some(_t) = s.begin_menu("Recent") {
                 if let Ok(recent_list) = fs::read_to_string("env\\recent.txt") {
@@ -649,19 +717,13 @@ impl GraspEditorState {
 
             s.separator();
 
-            if s.menu_item("Save") {
-                self.save_file();
-            }
-
-            if s.menu_item("Save As") {
-                self.save_file_as();
-            }
+            self.command_menu_item(s, "Save", "document.save");
+            self.command_menu_item(s, "Save As", "document.save_as");
+            self.command_menu_item(s, "Save Selection As...", "document.save_selection");
 
             s.separator();
 
-            if s.menu_item("Exit") {
-                s.exit();
-            }
+            self.command_menu_item(s, "Exit", "document.exit");
 
             f.end();
         }
@@ -681,70 +743,58 @@ impl GraspEditorState {
         } else {
             " "
         };
-        let (grid_on, debug_on, _ruler_on) = {
+        let (grid_on, debug_on, _ruler_on, dominators_on) = {
             let front = self.window_list.windows.front();
             let grid_on = Self::xo(front.map(|m| m.grid_visible));
             let debug_on = Self::xo(front.map(|m| m.editor_data.debug));
             let ruler_on = Self::xo(front.map(|m| m.ruler_visible));
-            (grid_on, debug_on, ruler_on)
+            let dominators_on = Self::xo(front.map(|m| m.dominators_visible));
+            (grid_on, debug_on, ruler_on, dominators_on)
         };
 
-        if s.menu_item(format!("[{}] Show Tab View", tabview_on)) {
-            self.editor_options.toggle_tabview = !self.editor_options.toggle_tabview;
-            self.editor_options.save();
-        }
-
-        if s.menu_item(format!("[{}] Toggle Debug Draw", debug_on)) {
-            if let Some(window) = self.window_list.windows.front_mut() {
-                window.editor_data.debug = !window.editor_data.debug;
-            }
-        }
-
-        if s.menu_item(format!("[{}] Toggle Grid", grid_on)) {
-            if let Some(window) = self.window_list.windows.front_mut() {
-                window.grid_visible = !window.grid_visible;
-                self.editor_options.save();
-            }
-        }
+        self.command_menu_item(
+            s,
+            &format!("[{}] Show Tab View", tabview_on),
+            "view.tab_view",
+        );
+        self.command_menu_item(
+            s,
+            &format!("[{}] Toggle Debug Draw", debug_on),
+            "view.debug_draw",
+        );
+        self.command_menu_item(s, &format!("[{}] Toggle Grid", grid_on), "view.grid");
+        self.command_menu_item(
+            s,
+            &format!("[{}] Toggle Dominator Arrows", dominators_on),
+            "view.dominators",
+        );
     }
 
     fn show_windows_menu(&mut self, s: &GuiState) {
-        let hierarchy_on = if self.editor_options.toggle_hierarchy {
-            "X"
-        } else {
-            " "
-        };
-        if s.ui.menu_item(format!("[{}] Hierarchy", hierarchy_on)) {
-            self.editor_options.toggle_hierarchy = !self.editor_options.toggle_hierarchy;
-            self.editor_options.save();
-        }
-
-        let property_on = if self.editor_options.toggle_properties {
-            "X"
-        } else {
-            " "
-        };
-        if s.ui.menu_item(format!("[{}] Properties", property_on)) {
-            self.editor_options.toggle_properties = !self.editor_options.toggle_properties;
-            self.editor_options.save();
-        }
-
-        let errors_on = if self.editor_options.toggle_errors {
-            "X"
-        } else {
-            " "
-        };
-        if s.ui.menu_item(format!("[{}] Errors", errors_on)) {
-            self.editor_options.toggle_errors = !self.editor_options.toggle_errors;
-            self.editor_options.save();
+        let options = &self.editor_options;
+        let toggles = [
+            ("Hierarchy", options.toggle_hierarchy, "windows.hierarchy"),
+            (
+                "Properties",
+                options.toggle_properties,
+                "windows.properties",
+            ),
+            ("Errors", options.toggle_errors, "windows.errors"),
+            ("Metrics", options.toggle_metrics, "windows.metrics"),
+            ("Query", options.toggle_query, "windows.query"),
+            ("Log", options.toggle_log, "windows.log"),
+            ("Find", options.toggle_find, "windows.find"),
+        ];
+
+        for (name, on, command) in toggles {
+            let label = format!("[{}] {}", Self::xo(Some(on)), name);
+            self.command_menu_item(s, &label, command);
         }
 
         s.ui.separator();
         s.ui.separator();
 
-        if s.ui.menu_item("[+] New Window") {
-            self.new_window(None);
-        }
+        self.command_menu_item(s, "[+] New Window", "document.new_window");
 
         let items = self
             .window_list
@@ -792,7 +842,7 @@ pub fn two_float_property_xy_renderer(ui: &GuiState, window: &mut GraspEditorWin
     {
         tile.clone().set("x", x);
         tile.clone().set("y", y);
-        window.changed = true;
+        window.mark_changed();
         window.request_quadtree_update();
     }
 }
@@ -834,123 +884,322 @@ pub fn selected_property_renderer(
     selection_property_renderer(ui, window, selection_owner);
 }
 
-fn draw_default_property_renderer(ui: &GuiState, window: &mut GraspEditorWindow, d: Tile) {
-    let mosaic = &window.document_mosaic;
-    let comp = mosaic
+fn component_menu_requested(s: &GuiState, is_locked: bool) -> bool {
+    !is_locked && s.ui.is_item_hovered() && s.ui.is_item_clicked_with_button(MouseButton::Right)
+}
+
+fn is_unit_component(window: &GraspEditorWindow, part: &Tile) -> bool {
+    let comp = window
+        .document_mosaic
         .component_registry
-        .get_component_type(d.component)
+        .get_component_type(part.component)
         .unwrap();
+
     let fields = comp.get_fields();
+    fields.len() == 1 && fields.first().unwrap().datatype == Datatype::UNIT
+}
+
+// component name -> (ids of the selected tiles that have it, the parts carrying its fields)
+fn shared_components(selected: &[Tile]) -> BTreeMap<String, (Vec<usize>, Vec<Tile>)> {
+    let mut shared: BTreeMap<String, (Vec<usize>, Vec<Tile>)> = BTreeMap::new();
+    for tile in selected {
+        for (component, parts) in tile.get_full_archetype() {
+            let entry = shared.entry(component).or_default();
+            entry.0.push(tile.id);
+            entry.1.extend(parts);
+        }
+    }
 
-    for field in &fields {
+    shared
+}
+
+fn draw_default_property_renderer(ui: &GuiState, window: &mut GraspEditorWindow, d: Tile) {
+    draw_shared_property_renderer(ui, window, &[d]);
+}
+
+// parts all belong to the same component, every edit is written to each of them
+fn draw_shared_property_renderer(ui: &GuiState, window: &mut GraspEditorWindow, parts: &[Tile]) {
+    let Some(first) = parts.first() else {
+        return;
+    };
+
+    let comp = window
+        .document_mosaic
+        .component_registry
+        .get_component_type(first.component)
+        .unwrap();
+
+    for field in &comp.get_fields() {
         let name = if comp.is_alias() {
             "self".to_string()
         } else {
-            let name = field.name;
-            name.to_string()
+            field.name.to_string()
         };
 
-        let datatype = field.datatype.clone();
-
-        if datatype == Datatype::UNIT {
+        if field.datatype == Datatype::UNIT {
             continue;
         }
 
-        let value = d.get(name.as_str());
-
-        match value {
+        let label = format!("{}##{}.{}-{}", name, first.id, name, parts.len());
+        match first.get(name.as_str()) {
             Value::UNIT => {}
-            Value::I8(v) => draw_property_value(ui, window, &d, name.as_str(), v),
-            Value::I16(v) => draw_property_value(ui, window, &d, name.as_str(), v),
-            Value::I32(v) => draw_property_value(ui, window, &d, name.as_str(), v),
-            Value::I64(v) => draw_property_value(ui, window, &d, name.as_str(), v),
-            Value::U8(v) => draw_property_value(ui, window, &d, name.as_str(), v),
-            Value::U16(v) => draw_property_value(ui, window, &d, name.as_str(), v),
-            Value::U32(v) => draw_property_value(ui, window, &d, name.as_str(), v),
-            Value::U64(v) => draw_property_value(ui, window, &d, name.as_str(), v),
-            Value::F32(v) => draw_property_value(ui, window, &d, name.as_str(), v),
-            Value::F64(v) => draw_property_value(ui, window, &d, name.as_str(), v),
-            Value::S32(v) => draw_property_value(ui, window, &d, name.as_str(), v),
-            Value::STR(v) => draw_property_value(ui, window, &d, name.as_str(), v),
-
-            Value::BOOL(v) => {
-                let mut b = v;
-                ui.checkbox("", &mut b);
+            Value::BOOL(_) => draw_shared_bool(ui, window, parts, &name, &label),
+            Value::S32(_) => {
+                let values = parts
+                    .iter()
+                    .map(|p| p.get(name.as_str()).as_s32().to_string())
+                    .collect_vec();
+                draw_shared_text::<S32>(ui, window, parts, &name, &label, &values, false);
+            }
+            Value::STR(_) => {
+                let values = parts
+                    .iter()
+                    .map(|p| p.get(name.as_str()).as_str())
+                    .collect_vec();
+                draw_shared_text::<String>(ui, window, parts, &name, &label, &values, true);
+            }
+            Value::I64(_) => {
+                let values = parts
+                    .iter()
+                    .filter_map(|p| match p.get(name.as_str()) {
+                        Value::I64(v) => Some(v),
+                        _ => None,
+                    })
+                    .collect_vec();
+                let bounds = field_bounds(window, &first.component.to_string(), &name)
+                    .map(|(min, max)| (min as i64, max as i64));
+                draw_shared_scalar(ui, window, parts, &name, &label, &values, bounds);
+            }
+            Value::U64(_) => {
+                let values = parts
+                    .iter()
+                    .filter_map(|p| match p.get(name.as_str()) {
+                        Value::U64(v) => Some(v),
+                        _ => None,
+                    })
+                    .collect_vec();
+                let bounds = field_bounds(window, &first.component.to_string(), &name)
+                    .map(|(min, max)| (min as u64, max as u64));
+                draw_shared_scalar(ui, window, parts, &name, &label, &values, bounds);
+            }
+            value => {
+                let bounds = field_bounds(window, &first.component.to_string(), &name);
+                let integer = !matches!(value, Value::F32(_) | Value::F64(_));
+                draw_shared_number(ui, window, parts, &name, &label, integer, bounds);
             }
         }
     }
+}
 
-    // }
+fn field_bounds(window: &GraspEditorWindow, component: &str, field: &str) -> Option<(f32, f32)> {
+    let key = if field == "self" {
+        component.to_string()
+    } else {
+        format!("{}.{}", component, field)
+    };
+
+    window
+        .component_mosaic
+        .get_all()
+        .include_component("ComponentBounds")
+        .find(|b| b.get("name").as_str() == key)
+        .map(|b| (b.get("min").as_f32(), b.get("max").as_f32()))
 }
-/* */
-fn draw_property_value<T: Display + FromStr + ToByteArray>(
-    state: &GuiState,
+
+// the value is cast back to the declared type of the field, integers saturate at its limits
+fn set_field_number(tile: &Tile, name: &str, value: f64) {
+    let mut tile = tile.clone();
+    match tile.get(name) {
+        Value::I8(_) => tile.set(name, value as i8),
+        Value::I16(_) => tile.set(name, value as i16),
+        Value::I32(_) => tile.set(name, value as i32),
+        Value::I64(_) => tile.set(name, value as i64),
+        Value::U8(_) => tile.set(name, value as u8),
+        Value::U16(_) => tile.set(name, value as u16),
+        Value::U32(_) => tile.set(name, value as u32),
+        Value::U64(_) => tile.set(name, value as u64),
+        Value::F32(_) => tile.set(name, value as f32),
+        Value::F64(_) => tile.set(name, value),
+        _ => {}
+    }
+}
+
+fn mark_property_changed(window: &mut GraspEditorWindow) {
+    window.mark_changed();
+    window.request_quadtree_update();
+}
+
+fn draw_mixed_marker(ui: &GuiState, mixed: bool) {
+    if mixed {
+        ui.ui.same_line();
+        ui.ui.text_disabled("(mixed)");
+    }
+}
+
+fn draw_shared_bool(
+    ui: &GuiState,
+    window: &mut GraspEditorWindow,
+    parts: &[Tile],
+    name: &str,
+    label: &str,
+) {
+    let values = parts.iter().map(|p| p.get(name).as_bool()).collect_vec();
+    let mut value = values[0];
+
+    if ui.ui.checkbox(label, &mut value) {
+        for part in parts {
+            part.clone().set(name, value);
+        }
+
+        mark_property_changed(window);
+    }
+
+    draw_mixed_marker(ui, values.iter().any(|v| *v != values[0]));
+}
+
+fn draw_shared_number(
+    ui: &GuiState,
     window: &mut GraspEditorWindow,
-    tile: &Tile,
+    parts: &[Tile],
     name: &str,
-    t: T,
+    label: &str,
+    integer: bool,
+    bounds: Option<(f32, f32)>,
+) {
+    let values = parts
+        .iter()
+        .filter_map(|p| value_as_f64(&p.get(name)))
+        .collect_vec();
+    let Some(first) = values.first().copied() else {
+        return;
+    };
+
+    let edited = if integer {
+        let mut value = first as i64;
+        let changed = match bounds {
+            Some((min, max)) => ui.ui.slider(label, min as i64, max as i64, &mut value),
+            None => ui.ui.input_scalar(label, &mut value).build(),
+        };
+        changed.then_some(value as f64)
+    } else {
+        let mut value = first;
+        let changed = match bounds {
+            Some((min, max)) => ui.ui.slider(label, min as f64, max as f64, &mut value),
+            None => ui.ui.input_scalar(label, &mut value).build(),
+        };
+        changed.then_some(value)
+    };
+
+    if let Some(value) = edited {
+        for part in parts {
+            set_field_number(part, name, value);
+        }
+
+        mark_property_changed(window);
+    }
+
+    draw_mixed_marker(ui, values.iter().any(|v| *v != first));
+}
+
+// 64 bit integers do not survive a round trip through f64, they are edited as they are
+fn draw_shared_scalar<T>(
+    ui: &GuiState,
+    window: &mut GraspEditorWindow,
+    parts: &[Tile],
+    name: &str,
+    label: &str,
+    values: &[T],
+    bounds: Option<(T, T)>,
 ) where
+    T: DataTypeKind + PartialEq,
     Tile: TileFieldSetter<T>,
 {
-    let datatype = tile.get(name).get_datatype();
-    let id = format!("##{}.{}", tile.id, name);
-    let mut text = format!("{}", t);
-    let previous_text = format!("{}", t);
-
-    let committed = match datatype {
-        Datatype::S32 => {
-            let committed = state
-                .ui
-                .input_text(format!("{}##{}", name, id), &mut text)
-                .auto_select_all(true)
-                .enter_returns_true(true)
-                .build();
-
-            if text.len() >= 32 {
-                text = text[0..32].to_string();
-            }
+    let Some(first) = values.first().copied() else {
+        return;
+    };
+
+    let mut value = first;
+    let changed = match bounds {
+        Some((min, max)) => ui.ui.slider(label, min, max, &mut value),
+        None => ui.ui.input_scalar(label, &mut value).build(),
+    };
 
-            committed
+    if changed {
+        for part in parts {
+            part.clone().set(name, value);
         }
 
-        Datatype::STR => {
-            let rect = state.ui.content_region_avail();
-            let committed = state
-                .ui
-                .input_text_multiline(
-                    format!("{}##{}", name, id),
-                    &mut text,
-                    [rect[0], rect[1].min(150.0)],
-                )
-                .auto_select_all(true)
-                .enter_returns_true(true)
-                .build();
+        mark_property_changed(window);
+    }
 
-            committed
-        }
+    draw_mixed_marker(ui, values.iter().any(|v| *v != first));
+}
+
+fn draw_shared_text<T: FromStr + ToByteArray>(
+    state: &GuiState,
+    window: &mut GraspEditorWindow,
+    parts: &[Tile],
+    name: &str,
+    label: &str,
+    values: &[String],
+    multiline: bool,
+) where
+    Tile: TileFieldSetter<T>,
+{
+    let mixed = values.iter().any(|v| *v != values[0]);
+    let previous_text = if mixed {
+        String::new()
+    } else {
+        values[0].clone()
+    };
+    let mut text = previous_text.clone();
 
-        _ => state
+    let committed = if multiline {
+        let rect = state.ui.content_region_avail();
+        state
             .ui
-            .input_text(format!("{}##{}", name, id), &mut text)
+            .input_text_multiline(label, &mut text, [rect[0], rect[1].min(150.0)])
             .auto_select_all(true)
             .enter_returns_true(true)
-            .build(),
+            .build()
+    } else {
+        state
+            .ui
+            .input_text(label, &mut text)
+            .hint(if mixed { "(mixed)" } else { "" })
+            .auto_select_all(true)
+            .enter_returns_true(true)
+            .build()
+    };
+
+    // s32 fields hold 32 bytes, cut before the character that would not fit
+    if !multiline && text.len() > 32 {
+        let end = (0..=32)
+            .rev()
+            .find(|i| text.is_char_boundary(*i))
+            .unwrap_or(0);
+        text.truncate(end);
+    }
+
+    draw_mixed_marker(state, mixed && multiline);
+
+    let write = if previous_text != text {
+        window.state = EditorState::PropertyChanging;
+        true
+    } else if window.state == EditorState::PropertyChanging && committed {
+        window.state = EditorState::Idle;
+        !mixed
+    } else {
+        false
     };
 
-    state
-        .ui
-        .columns(1, format!("##{}.{}-c1", tile.id, name), false);
-    if let Ok(t) = text.parse::<T>() {
-        if previous_text != text {
-            window.state = EditorState::PropertyChanging;
-            tile.clone().set(name, t);
-            window.changed = true;
-            window.request_quadtree_update();
-        } else if window.state == EditorState::PropertyChanging && committed {
-            window.state = EditorState::Idle;
-            tile.clone().set(name, t);
-            window.changed = true;
-            window.request_quadtree_update();
+    if write {
+        for part in parts {
+            if let Ok(t) = text.parse::<T>() {
+                part.clone().set(name, t);
+            }
         }
+
+        mark_property_changed(window);
     }
 }
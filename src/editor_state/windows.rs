@@ -5,6 +5,7 @@ use crate::core::math::vec2::Vec2;
 use crate::editor_state_machine::EditorState;
 use crate::grasp_common::GraspEditorData;
 use crate::grasp_render::GraspRenderer;
+use crate::grasp_transitions::query_position_recursive;
 use crate::GuiState;
 use ::mosaic::internals::{EntityId, Mosaic, MosaicCRUD, MosaicIO, Tile, Value};
 use imgui::ImColor32;
@@ -19,12 +20,16 @@ use std::ops::Add;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
+use super::diff::DiffOverlay;
 use super::helpers::{QuadtreeUpdateCapability, RequireWindowFocus};
+use super::query::QueryHighlightCache;
 
 pub struct GraspEditorWindow {
     pub name: String,
     pub path: Option<PathBuf>,
     pub changed: bool,
+    // bumped on every change, so derived data can tell when to recompute
+    pub revision: u64,
     pub window_tile: Tile,
     pub state: EditorState,
     pub quadtree: Mutex<Quadtree<i32, EntityId>>,
@@ -35,6 +40,10 @@ pub struct GraspEditorWindow {
     pub object_to_area: Mutex<HashMap<EntityId, u64>>,
     pub ruler_visible: bool,
     pub grid_visible: bool,
+    pub dominators_visible: bool,
+    pub query_name: String,
+    pub query_text: String,
+    pub query_highlights: QueryHighlightCache,
     pub editor_data: GraspEditorData,
     pub renderer: GraspRenderer,
     pub left_drag_last_frame: bool,
@@ -42,6 +51,8 @@ pub struct GraspEditorWindow {
     pub title_bar_drag: bool,
     pub rect: Rect2,
     pub window_list_index: usize,
+    pub diff_overlay: Option<DiffOverlay>,
+    pub pending_command: Option<String>,
 }
 
 impl HasMosaic for GraspEditorWindow {
@@ -59,6 +70,11 @@ impl PartialEq for GraspEditorWindow {
 }
 
 impl GraspEditorWindow {
+    pub fn mark_changed(&mut self) {
+        self.changed = true;
+        self.revision += 1;
+    }
+
     pub fn request_quadtree_update(&self) {
         self.editor_mosaic.request_quadtree_update();
     }
@@ -103,6 +119,12 @@ impl GraspEditorWindow {
         v + self.editor_data.window_offset
     }
 
+    pub fn focus_tile(&mut self, tile: &Tile) {
+        let center = Vec2::new(self.rect.width / 2.0, self.rect.height / 2.0);
+        self.editor_data.pan = center - query_position_recursive(tile);
+        self.editor_data.selected = vec![tile.clone()];
+    }
+
     pub fn build_circle_area(&self, pos: Vec2, size: i32) -> Area<i32> {
         let pos = self.pos_add_editor_pan(pos);
         AreaBuilder::default()
@@ -1,27 +1,25 @@
-use std::sync::Arc;
-
 use itertools::Itertools;
 use mosaic::{
     capabilities::ArchetypeSubject,
-    internals::{pars, void, ComponentValuesBuilderSetter, MosaicIO},
+    internals::{void, MosaicIO},
     iterators::{component_selectors::ComponentSelectors, tile_getters::TileGetters},
 };
 
 use crate::{
-    core::{math::Vec2, structures::grasp_queues},
-    editor_state::windows::GraspEditorWindow,
+    core::structures::errors::ErrorCapability,
+    editor_state::{
+        commands::TRANSFORMER_COMMAND_PREFIX, keymap::Keymap, windows::GraspEditorWindow,
+    },
     editor_state_machine::{EditorState, EditorStateTrigger, StateMachine},
-    grasp_queues::{CloseWindowRequestQueue, WindowTransformerQueue},
+    querying::query_tiles::QueryStorage,
     transformers::TransformerUtilities,
     GuiState,
 };
 
-use crate::editor_state::file_operations::SaveFileCapability;
-
 impl GraspEditorWindow {
-    pub fn context_popup(&mut self, s: &GuiState) {
+    pub fn context_popup(&mut self, s: &GuiState, keymap: &Keymap) {
         if let Some(_token) = s.ui.begin_popup("context-menu") {
-            if self.show_default_menu(s) {
+            if self.show_default_menu(s, keymap) {
                 self.trigger(EditorStateTrigger::ExitContextMenu);
             }
         } else if self.state == EditorState::ContextMenu {
@@ -43,7 +41,7 @@ impl GraspEditorWindow {
         }
     }
 
-    fn show_selection_menu(&mut self, s: &GuiState) -> bool {
+    fn show_selection_menu(&mut self, s: &GuiState, keymap: &Keymap) -> bool {
         if let Some(_token) = s.ui.begin_menu("Add Component") {
             let mut categories = self
                 .component_mosaic
@@ -69,7 +67,7 @@ impl GraspEditorWindow {
                             if s.ui.menu_item(name.clone()) {
                                 for s in &self.editor_data.selected {
                                     s.add_component(&name, void());
-                                    self.changed = true;
+                                    self.mark_changed();
                                 }
 
                                 return true;
@@ -96,55 +94,90 @@ impl GraspEditorWindow {
                 })
                 .get_targets();
 
+            let _disabled =
+                s.ui.begin_disabled(self.editor_mosaic.is_transformer_pending());
             for transformer in transformers {
                 let name = transformer.get("self").as_s32().to_string();
-
-                if s.ui
-                    .menu_item_config(name.clone())
-                    .enabled(!self.editor_mosaic.is_transformer_pending())
-                    .build()
-                {
-                    let request = self.editor_mosaic.new_object(
-                        "WindowTransformerRequest",
-                        pars()
-                            .set("transform", name.as_str())
-                            .set("window_index", self.window_tile.id as u64)
-                            .ok(),
-                    );
-
-                    self.trigger(EditorStateTrigger::TransformerSelected);
-                    grasp_queues::enqueue(WindowTransformerQueue, request);
-
-                    return true;
-                }
+                let id = format!("{}{}", TRANSFORMER_COMMAND_PREFIX, name);
+                self.command_menu_item(s, keymap, &name, &id);
             }
         }
 
         false
     }
 
-    fn show_default_menu(&mut self, s: &GuiState) -> bool {
-        let editor_mosaic = Arc::clone(&self.editor_mosaic);
-
-        if let Some(menu_token) = s.ui.begin_menu("Window") {
-            if s.ui.menu_item("Save") {
-                self.save_file();
+    fn show_queries_menu(&mut self, s: &GuiState) -> bool {
+        s.ui.input_text("Name##query-menu-name", &mut self.query_name)
+            .hint("Query name")
+            .build();
+        s.ui.input_text("Query##query-menu-text", &mut self.query_text)
+            .hint("objects(all)")
+            .build();
+
+        if s.ui.menu_item("Save Query") {
+            let (name, text) = (self.query_name.clone(), self.query_text.clone());
+            match self.save_query(&name, &text) {
+                Ok(_) => {
+                    self.query_name.clear();
+                    self.query_text.clear();
+                }
+                Err(error) => {
+                    self.editor_mosaic.make_error(
+                        "Queries",
+                        &format!("Cannot save query: {}", error),
+                        Some(self.window_tile.clone()),
+                        None,
+                    );
+                }
             }
 
-            if s.ui.menu_item("Save As") {
-                self.save_file_as();
-            }
+            return true;
+        }
 
+        let queries = self.document_mosaic.get_queries();
+        if !queries.is_empty() {
             s.ui.separator();
+        }
 
-            if s.ui.menu_item("Close") {
-                let request = editor_mosaic.new_object("CloseWindowRequest", void());
-                grasp_queues::enqueue(CloseWindowRequestQueue, request);
-                menu_token.end();
-                return true;
+        for query in queries {
+            if let Some(_token) =
+                s.ui.begin_menu(format!("{}##query-{}", query.get_name(), query.0.id))
+            {
+                if let Some(collage) = query.get_collage() {
+                    s.ui.text_disabled(collage.to_string());
+                }
+
+                if s.ui.menu_item("Run") {
+                    self.run_query(&query);
+                    return true;
+                }
+
+                let highlight_on = if query.is_highlighted() { "X" } else { " " };
+                if s.ui.menu_item(format!("[{}] Highlight", highlight_on)) {
+                    query.set_highlighted(!query.is_highlighted());
+                    self.mark_changed();
+                    return true;
+                }
+
+                s.ui.separator();
+
+                if s.ui.menu_item("Delete") {
+                    self.delete_tiles(&[query.0.clone()]);
+                    self.mark_changed();
+                    return true;
+                }
             }
+        }
+
+        false
+    }
 
-            menu_token.end();
+    fn show_default_menu(&mut self, s: &GuiState, keymap: &Keymap) -> bool {
+        if let Some(_menu_token) = s.ui.begin_menu("Window") {
+            self.command_menu_item(s, keymap, "Save", "document.save");
+            self.command_menu_item(s, keymap, "Save As", "document.save_as");
+            s.ui.separator();
+            self.command_menu_item(s, keymap, "Close", "window.close");
         }
 
         s.ui.separator();
@@ -154,31 +187,36 @@ impl GraspEditorWindow {
             let debug_on = if self.editor_data.debug { "X" } else { " " };
             let ruler_on = if self.ruler_visible { "X" } else { " " };
 
-            if s.menu_item(format!("[{}] Toggle Ruler", ruler_on)) {
-                self.ruler_visible = !self.ruler_visible;
-            }
+            let dominators_on = if self.dominators_visible { "X" } else { " " };
 
-            if s.menu_item(format!("[{}] Toggle Debug Draw", debug_on)) {
-                self.editor_data.debug = !self.editor_data.debug;
+            let items = [
+                (ruler_on, "Toggle Ruler", "view.ruler"),
+                (debug_on, "Toggle Debug Draw", "view.debug_draw"),
+                (grid_on, "Toggle Grid", "view.grid"),
+                (dominators_on, "Toggle Dominator Arrows", "view.dominators"),
+            ];
+
+            for (on, label, id) in items {
+                self.command_menu_item(s, keymap, &format!("[{}] {}", on, label), id);
             }
+        }
 
-            if s.menu_item(format!("[{}] Toggle Grid", grid_on)) {
-                self.grid_visible = !self.grid_visible;
+        s.ui.separator();
+
+        if let Some(_menu_token) = s.ui.begin_menu("Queries") {
+            if self.show_queries_menu(s) {
+                return true;
             }
         }
 
         s.ui.separator();
         s.ui.spacing();
 
-        if s.ui.menu_item("Create new node") {
-            let pos: Vec2 = self.editor_data.popup_cursor;
-            self.create_new_object(pos - self.editor_data.window_offset - self.editor_data.pan);
-            return true;
-        }
+        self.command_menu_item(s, keymap, "Create new node", "document.create_node");
 
         s.ui.separator();
 
-        if !self.editor_data.selected.is_empty() && self.show_selection_menu(s) {
+        if !self.editor_data.selected.is_empty() && self.show_selection_menu(s, keymap) {
             let previous_selection = self.editor_data.selected.to_owned();
             self.trigger(EditorStateTrigger::ExitContextMenu);
 
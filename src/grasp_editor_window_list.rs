@@ -46,4 +46,14 @@ impl GraspEditorWindowList {
     pub fn get_by_id_mut(&mut self, id: usize) -> Option<&mut GraspEditorWindow> {
         self.windows.iter_mut().find(|w| w.window_tile.id == id)
     }
+
+    pub fn get_by_id_or_focused_mut(
+        &mut self,
+        id: Option<usize>,
+    ) -> Option<&mut GraspEditorWindow> {
+        match id {
+            Some(id) => self.get_by_id_mut(id),
+            None => self.get_focused_mut(),
+        }
+    }
 }
@@ -2,10 +2,7 @@ use grasp_proc_macros::GraspQueue;
 use itertools::Itertools;
 use mosaic::{
     capabilities::ArchetypeSubject,
-    internals::{
-        pars, void, ComponentValuesBuilderSetter, EntityId, MosaicCRUD, MosaicIO, Tile,
-        TileFieldEmptyQuery,
-    },
+    internals::{void, EntityId, MosaicCRUD, MosaicIO, Tile, TileFieldEmptyQuery},
     iterators::{
         component_selectors::ComponentSelectors, tile_deletion::TileDeletion,
         tile_getters::TileGetters,
@@ -16,11 +13,17 @@ use std::{collections::HashSet, vec::IntoIter};
 use crate::{
     core::{
         gui::windowing::gui_set_window_focus,
-        structures::grasp_queues::{self, dequeue, GraspQueue},
+        math::Vec2,
+        structures::{
+            errors::ErrorCapability,
+            grasp_queues::{self, dequeue, GraspQueue},
+            subgraph::{place_subgraph, Subgraph},
+        },
     },
     editor_state::{
         file_operations::SaveFileCapability,
         foundation::{GraspEditorState, TransformerState},
+        network::{register_networked, Networked},
     },
     editor_state_machine::{EditorStateTrigger, StateMachine},
     GuiState,
@@ -69,6 +72,11 @@ impl GraspEditorState {
         self.process_quadtree_queue();
         self.process_close_window_queue(ui);
         self.process_window_transformer_queue(ui);
+        self.process_network_request_queue();
+        self.process_api_request_queue();
+        self.process_collaboration();
+        self.prune_errors();
+        self.autosave();
     }
 
     fn process_delete_reaction_queue(&mut self) {
@@ -105,6 +113,7 @@ impl GraspEditorState {
 
         for window in to_update_quadtree {
             if let Some(window) = self.window_list.get_by_id_mut(window) {
+                window.mark_changed();
                 window.request_quadtree_update();
             }
         }
@@ -128,6 +137,7 @@ impl GraspEditorState {
             }
 
             if let Some(window) = self.window_list.get_by_id_mut(id) {
+                register_networked(window.document_mosaic.get_key(), &name);
                 window.name = name;
             }
 
@@ -153,6 +163,18 @@ impl GraspEditorState {
         while let Some(request) = grasp_queues::dequeue(NewWindowRequestQueue, &self.editor_mosaic)
         {
             self.new_window(None);
+
+            // extracted selections arrive as the content of the new window
+            if request.component.to_string() == "NewWindowRequest" {
+                if let Some(subgraph) = Subgraph::from_text(&request.get("self").as_str()) {
+                    let window = self.window_list.windows.front_mut().unwrap();
+                    window.editor_data.selected =
+                        place_subgraph(&window.document_mosaic, &subgraph, Vec2::ZERO);
+                    window.mark_changed();
+                    window.request_quadtree_update();
+                }
+            }
+
             request.iter().delete();
         }
     }
@@ -298,17 +320,15 @@ impl GraspEditorState {
                 _ => None,
             };
 
-            maybe_error.map(|err| {
+            if let Some(err) = maybe_error {
                 error_count += 1;
-                self.editor_mosaic.new_object(
-                    "Error",
-                    pars()
-                        .set("message", err)
-                        .set("target", instance.id as u64)
-                        .set("window", window_index)
-                        .ok(),
-                )
-            });
+                self.editor_mosaic.make_error(
+                    fn_name,
+                    &err,
+                    self.editor_mosaic.get(window_index as usize),
+                    Some(instance.clone()),
+                );
+            }
         }
 
         visited.push(template.id);
@@ -349,20 +369,15 @@ impl GraspEditorState {
             println!("\tis multiplicity correct?: {}", is_correct);
 
             if !is_correct {
-                self.editor_mosaic.new_object(
-                    "Error",
-                    pars()
-                        .set(
-                            "message",
-                            format!(
-                                "Multiplicity wrong for arrows going {} tile #{}",
-                                if is_outgoing { "from" } else { "into" },
-                                template.id
-                            ),
-                        )
-                        .set("target", instance.id as u64)
-                        .set("window", window_index)
-                        .ok(),
+                self.editor_mosaic.make_error(
+                    fn_name,
+                    &format!(
+                        "Multiplicity wrong for arrows going {} tile #{}",
+                        if is_outgoing { "from" } else { "into" },
+                        template.id
+                    ),
+                    self.editor_mosaic.get(window_index as usize),
+                    Some(instance.clone()),
                 );
             } else {
                 let instance_neighbors = if is_outgoing {
@@ -431,6 +446,8 @@ impl GraspEditorState {
                                     .iter()
                                     .delete();
                                 self.pending_transform_window_request = None;
+                                // transformers edit the document directly, cached results are stale now
+                                w.revision += 1;
                                 w.trigger(EditorStateTrigger::TransformerDone)
                             }
                         }
@@ -446,10 +463,13 @@ impl GraspEditorState {
                 .iter()
                 .find(|w| w.window_tile.id == window_index)
             {
+                // only the previous run of the same transformer is outdated
+                let transformer = request.get("transform").as_s32().to_string();
                 self.editor_mosaic
                     .get_all()
                     .include_component("Error")
                     .filter(|t| t.get("window").as_u64() as usize == window_index)
+                    .filter(|t| t.get("source").as_str() == transformer)
                     .delete();
 
                 self.pending_transform_window_request = Some(request);
@@ -14,6 +14,7 @@ use crate::editor_state_machine::StateMachine;
 
 use crate::grasp_transitions::query_position_recursive;
 
+use crate::utilities::ColorQuery;
 use crate::utilities::OffsetQuery;
 use crate::utilities::SelfLoopQuery;
 use crate::utilities::SelfText;
@@ -33,6 +34,10 @@ use mosaic::iterators::tile_getters::TileGetters;
 
 pub type GraspRenderer = fn(&mut GraspEditorWindow, &GuiState, &HashMap<String, ComponentRenderer>);
 
+// dominator results are drawn apart from the arrows of the graph itself
+const IDOM_COLOR: [f32; 4] = [1.0, 0.6, 0.2, 0.9];
+const IPOSTDOM_COLOR: [f32; 4] = [0.3, 0.8, 1.0, 0.9];
+
 fn gui_set_cursor_pos(x: f32, y: f32) {
     unsafe {
         imgui::sys::igSetCursorPos(ImVec2::new(x, y));
@@ -82,9 +87,17 @@ pub(crate) fn default_renderer_draw_object(
         [pos.x - window.rect.x, pos.y - window.rect.y],
         0.0,
         1.0,
-        None,
+        tile.get_component("Color")
+            .map(|_| ColorQuery(tile).query()),
     );
 
+    if tile.get_component("LoopHeader").is_some() {
+        painter
+            .add_circle([pos.x, pos.y], 14.0, ImColor32::from_rgb(255, 200, 0))
+            .thickness(2.0)
+            .build();
+    }
+
     draw_label("Label", "", window, tile, pos, painter, ImColor32::WHITE, s);
 }
 
@@ -129,6 +142,19 @@ pub(crate) fn default_renderer_draw_arrow(
     }
 
     draw_label("Label", "", window, tile, pos, painter, ImColor32::WHITE, s);
+
+    if let Some(flow) = tile.get_component("Flow") {
+        let capacity = tile
+            .get_component("Capacity")
+            .map(|c| format!(" / {}", c.get("self").as_f32()))
+            .unwrap_or_default();
+
+        painter.add_text(
+            [pos.x + 10.0, pos.y + 14.0],
+            ImColor32::from_rgb(120, 200, 255),
+            format!("{}{}", flow.get("self").as_f32(), capacity),
+        );
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -179,7 +205,7 @@ pub fn draw_label(
                             if window.editor_data.previous_text != *text {
                                 if let Some(mut label) = tile.clone().get_component(component) {
                                     label.set("self", t.to_string());
-                                    window.changed = true;
+                                    window.mark_changed();
                                     window.editor_mosaic.request_quadtree_update();
                                 } else {
                                     cancel = false;
@@ -218,6 +244,22 @@ pub fn draw_arrow(
     painter: &mut DrawListMut<'_>,
     arrow: &Tile,
     thickness: f32,
+) {
+    draw_colored_arrow(
+        window,
+        painter,
+        arrow,
+        thickness,
+        ImColor32::from_rgba_f32s(1.0, 1.0, 1.0, 0.8),
+    );
+}
+
+pub fn draw_colored_arrow(
+    window: &GraspEditorWindow,
+    painter: &mut DrawListMut<'_>,
+    arrow: &Tile,
+    thickness: f32,
+    color: ImColor32,
 ) {
     let target = arrow.target();
     let mut offset = OffsetQuery(arrow).query();
@@ -238,7 +280,7 @@ pub fn draw_arrow(
         let mid1 = mid + (d / dp.len()) * perp;
         let mid2 = mid - (d / dp.len()) * perp;
 
-        gui_draw_bezier(painter, [p1, mid1, p2], thickness, 32);
+        gui_draw_bezier(painter, [p1, mid1, p2], thickness, 32, color);
 
         gui_draw_bezier_arrow(
             painter,
@@ -247,7 +289,7 @@ pub fn draw_arrow(
             32,
             window.rect.min(),
             arrow_end_offset,
-            ImColor32::from_rgba_f32s(1.0, 1.0, 1.0, 0.8),
+            color,
         );
     } else {
         let p2 = window.get_position_with_offset_and_pan(query_position_recursive(&target));
@@ -260,7 +302,7 @@ pub fn draw_arrow(
             32,
             window.rect.min(),
             arrow_end_offset,
-            ImColor32::from_rgba_f32s(1.0, 1.0, 1.0, 0.8),
+            color,
         );
     }
 }
@@ -289,6 +331,28 @@ pub fn default_renderer_draw(
         draw_arrow(window, &mut painter, arrow, 2.0);
     }
 
+    if window.dominators_visible {
+        for arrow in window
+            .document_mosaic
+            .get_all()
+            .filter(|t| t.component.is("IDom") || t.component.is("IPostDom"))
+        {
+            let color = if arrow.component.is("IDom") {
+                IDOM_COLOR
+            } else {
+                IPOSTDOM_COLOR
+            };
+
+            draw_colored_arrow(
+                window,
+                &mut painter,
+                &arrow,
+                1.5,
+                ImColor32::from_rgba_f32s(color[0], color[1], color[2], color[3]),
+            );
+        }
+    }
+
     for arrow in &arrows {
         let p1 = window.get_position_with_offset_and_pan(query_position_recursive(&arrow.source()));
         let p2 = window.get_position_with_offset_and_pan(query_position_recursive(&arrow.target()));
@@ -54,7 +54,7 @@ impl StateMachine for GraspEditorWindow {
                 self.create_new_object(
                     self.editor_data.cursor - self.editor_data.window_offset - self.editor_data.pan,
                 );
-                self.changed = true;
+                self.mark_changed();
                 //all windows need to update their quadtrees
                 self.request_quadtree_update();
                 previous
@@ -115,7 +115,7 @@ impl StateMachine for GraspEditorWindow {
                     let mid_pos = src_pos.lerp(tgt_pos, 0.5);
 
                     self.create_new_arrow(&start, &tile, mid_pos);
-                    self.changed = true;
+                    self.mark_changed();
                 }
 
                 self.editor_data.link_start_pos = None;
@@ -127,7 +127,7 @@ impl StateMachine for GraspEditorWindow {
             }
             (EditorState::Move, EditorStateTrigger::EndDrag) => {
                 self.update_selected_positions_by(self.editor_data.cursor_delta);
-                self.changed = true;
+                self.mark_changed();
                 self.request_quadtree_update();
                 previous
             }
@@ -141,7 +141,7 @@ impl StateMachine for GraspEditorWindow {
                 self.editor_data.field_changing = None;
                 self.editor_data.previous_text.clear();
                 self.editor_data.text.clear();
-                self.changed = true;
+                self.mark_changed();
                 previous
             }
 
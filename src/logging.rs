@@ -0,0 +1,399 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use iso8601_timestamp::Timestamp;
+use lazy_static::lazy_static;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+lazy_static! {
+    static ref LOG_BUFFER: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogSource {
+    Grasp,
+    Mosaic,
+    Extern,
+}
+
+impl Display for LogSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogSource::Grasp => write!(f, "GRASP"),
+            LogSource::Mosaic => write!(f, "MOSAIC"),
+            LogSource::Extern => write!(f, "EXTERN"),
+        }
+    }
+}
+
+impl LogSource {
+    // relative paths are our own sources, dependencies are built from absolute ones
+    fn of(file: Option<&str>) -> Self {
+        match file {
+            Some(p) if !Path::new(p).is_absolute() => LogSource::Grasp,
+            Some(p) if p.contains("mosaic") => LogSource::Mosaic,
+            _ => LogSource::Extern,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: Level,
+    pub source: LogSource,
+    pub message: String,
+    pub file: String,
+    pub line: u32,
+}
+
+impl LogEntry {
+    fn from_record(record: &Record) -> Self {
+        LogEntry {
+            timestamp: Timestamp::now_utc().to_string(),
+            level: record.level(),
+            source: LogSource::of(record.file()),
+            message: record.args().to_string(),
+            file: record.file().unwrap_or_default().to_string(),
+            line: record.line().unwrap_or_default(),
+        }
+    }
+
+    pub fn to_clef(&self) -> serde_json::Value {
+        json!({
+            "@t": self.timestamp,
+            "@m": format!("[{}] {}", self.source, self.message),
+            "@l": self.level.to_string(),
+            "Line": self.line,
+            "Source": self.file,
+            "Origin": self.source.to_string(),
+            "User": whoami::username(),
+            "Platform": whoami::platform().to_string(),
+        })
+    }
+}
+
+// a copy, so the buffer is not locked while the entries are drawn
+pub fn log_entries(keep: impl Fn(&LogEntry) -> bool) -> Vec<LogEntry> {
+    LOG_BUFFER
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|e| keep(e))
+        .cloned()
+        .collect()
+}
+
+pub fn clear_log_entries() {
+    LOG_BUFFER.lock().unwrap().clear();
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct FileSinkOptions {
+    pub path: String,
+    pub max_bytes: u64,
+    pub max_files: usize,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct SeqSinkOptions {
+    pub url: String,
+    #[serde(default = "default_seq_buffer")]
+    pub buffer: usize,
+    #[serde(default = "default_seq_retry")]
+    pub retry_seconds: u64,
+}
+
+fn default_seq_buffer() -> usize {
+    10000
+}
+
+fn default_seq_retry() -> u64 {
+    5
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct LogOptions {
+    pub level: String,
+    #[serde(default)]
+    pub file: Option<FileSinkOptions>,
+    #[serde(default)]
+    pub stderr: bool,
+    #[serde(default)]
+    pub seq: Option<SeqSinkOptions>,
+    #[serde(default)]
+    pub buffer: usize,
+}
+
+impl Default for LogOptions {
+    fn default() -> Self {
+        let def = Self {
+            level: "warn".to_string(),
+            file: Some(FileSinkOptions {
+                path: "grasp.log".to_string(),
+                max_bytes: 5 * 1024 * 1024,
+                max_files: 3,
+            }),
+            stderr: true,
+            seq: None,
+            buffer: 2000,
+        };
+
+        if let Ok(content) = fs::read_to_string("env\\logging.ron") {
+            match ron::from_str::<LogOptions>(&content) {
+                Ok(options) => return options,
+                Err(error) => eprintln!("Cannot read env\\logging.ron: {}", error),
+            }
+        }
+
+        def
+    }
+}
+
+pub trait LogSink: Send {
+    fn write(&mut self, entry: &LogEntry);
+    fn flush(&mut self) {}
+}
+
+pub struct MemorySink {
+    capacity: usize,
+}
+
+impl LogSink for MemorySink {
+    fn write(&mut self, entry: &LogEntry) {
+        let mut buffer = LOG_BUFFER.lock().unwrap();
+        while buffer.len() >= self.capacity {
+            buffer.pop_front();
+        }
+        buffer.push_back(entry.clone());
+    }
+}
+
+pub struct StderrSink;
+
+impl LogSink for StderrSink {
+    fn write(&mut self, entry: &LogEntry) {
+        eprintln!(
+            "{} {:<5} [{}] {}",
+            entry.timestamp, entry.level, entry.source, entry.message
+        );
+    }
+}
+
+// CLEF lines, the current file is rolled to path.1 .. path.{max_files} once it grows too big
+pub struct RollingFileSink {
+    options: FileSinkOptions,
+    file: Option<File>,
+    written: u64,
+}
+
+impl RollingFileSink {
+    pub fn new(options: FileSinkOptions) -> Self {
+        let written = fs::metadata(&options.path).map(|m| m.len()).unwrap_or(0);
+        RollingFileSink {
+            file: OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&options.path)
+                .ok(),
+            options,
+            written,
+        }
+    }
+
+    fn roll(&mut self) {
+        self.file = None;
+        let path = &self.options.path;
+        for i in (1..self.options.max_files).rev() {
+            let _ = fs::rename(format!("{}.{}", path, i), format!("{}.{}", path, i + 1));
+        }
+
+        if self.options.max_files > 0 {
+            let _ = fs::rename(path, format!("{}.1", path));
+        }
+
+        self.file = File::create(path).ok();
+        self.written = 0;
+    }
+}
+
+impl LogSink for RollingFileSink {
+    fn write(&mut self, entry: &LogEntry) {
+        if self.written >= self.options.max_bytes {
+            self.roll();
+        }
+
+        let line = entry.to_clef().to_string() + "\n";
+        if let Some(file) = self.file.as_mut() {
+            if file.write_all(line.as_bytes()).is_ok() {
+                self.written += line.len() as u64;
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(file) = self.file.as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+const SEQ_THREAD: &str = "seq-log";
+
+// events are kept until Seq accepts them, the oldest are dropped once the buffer is full
+pub struct SeqSink {
+    sender: Sender<serde_json::Value>,
+}
+
+impl SeqSink {
+    pub fn new(options: SeqSinkOptions) -> Self {
+        let (sender, receiver) = mpsc::channel::<serde_json::Value>();
+
+        let spawned = thread::Builder::new()
+            .name(SEQ_THREAD.to_string())
+            .spawn(move || {
+                let client = reqwest::blocking::Client::new();
+                let url = format!("{}/api/events/raw?clef", options.url.trim_end_matches('/'));
+                let retry = Duration::from_secs(options.retry_seconds.max(1));
+                let mut pending: VecDeque<serde_json::Value> = VecDeque::new();
+
+                loop {
+                    let timeout = if pending.is_empty() {
+                        Duration::from_secs(3600)
+                    } else {
+                        retry
+                    };
+
+                    match receiver.recv_timeout(timeout) {
+                        Ok(event) => pending.push_back(event),
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+
+                    pending.extend(receiver.try_iter());
+                    while pending.len() > options.buffer {
+                        pending.pop_front();
+                    }
+
+                    if pending.is_empty() {
+                        continue;
+                    }
+
+                    let body = pending
+                        .iter()
+                        .map(|e| e.to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    let sent = client
+                        .post(&url)
+                        .header("Content-Type", "application/vnd.serilog.clef")
+                        .body(body)
+                        .send()
+                        .is_ok_and(|response| response.status().is_success());
+
+                    if sent {
+                        pending.clear();
+                    } else {
+                        thread::sleep(retry);
+                    }
+                }
+            });
+
+        if let Err(error) = spawned {
+            eprintln!("Cannot start the Seq log sink: {}", error);
+        }
+
+        SeqSink { sender }
+    }
+}
+
+impl LogSink for SeqSink {
+    fn write(&mut self, entry: &LogEntry) {
+        // records of the http client itself would otherwise feed back into Seq
+        if thread::current().name() == Some(SEQ_THREAD) {
+            return;
+        }
+
+        let _ = self.sender.send(entry.to_clef());
+    }
+}
+
+pub struct LogRouter {
+    level: LevelFilter,
+    sinks: Mutex<Vec<Box<dyn LogSink>>>,
+}
+
+impl LogRouter {
+    pub fn new(options: &LogOptions) -> Self {
+        let mut sinks: Vec<Box<dyn LogSink>> = vec![];
+
+        if options.buffer > 0 {
+            sinks.push(Box::new(MemorySink {
+                capacity: options.buffer,
+            }));
+        }
+
+        if options.stderr {
+            sinks.push(Box::new(StderrSink));
+        }
+
+        if let Some(file) = &options.file {
+            sinks.push(Box::new(RollingFileSink::new(file.clone())));
+        }
+
+        if let Some(seq) = &options.seq {
+            sinks.push(Box::new(SeqSink::new(seq.clone())));
+        }
+
+        LogRouter {
+            level: options.level.parse().unwrap_or(LevelFilter::Warn),
+            sinks: Mutex::new(sinks),
+        }
+    }
+}
+
+impl Log for LogRouter {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let entry = LogEntry::from_record(record);
+        for sink in self.sinks.lock().unwrap().iter_mut() {
+            sink.write(&entry);
+        }
+    }
+
+    fn flush(&self) {
+        for sink in self.sinks.lock().unwrap().iter_mut() {
+            sink.flush();
+        }
+    }
+}
+
+pub fn init_logging() {
+    let options = LogOptions::default();
+    let router = LogRouter::new(&options);
+    let level = router.level;
+
+    if log::set_boxed_logger(Box::new(router)).is_ok() {
+        log::set_max_level(level);
+    }
+}
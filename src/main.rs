@@ -1,8 +1,9 @@
-use core::gui::imgui_keys::ExtraKeyEvents;
+use cli::run_cli;
 use core::gui::windowing::run_main_forever;
 use editor_state::foundation::GraspEditorState;
-use imgui::{Key, Ui};
+use imgui::Ui;
 
+mod cli;
 mod core;
 mod editor_state;
 mod editor_state_machine;
@@ -14,8 +15,8 @@ mod grasp_render;
 mod grasp_transitions;
 mod grasp_update;
 pub mod importers;
+mod logging;
 mod querying;
-mod seq;
 mod transformers;
 mod utilities;
 
@@ -49,17 +50,14 @@ impl<'a> Deref for GuiState<'a> {
 
 #[tokio::main]
 async fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let Some(code) = run_cli(&args) {
+        std::process::exit(code);
+    }
+
     let mut grasp_editor_state = GraspEditorState::new();
 
     run_main_forever(|ui, quit| {
-        if ui.is_fkey_down(Key::F11) {
-            grasp_editor_state.snapshot_all("SNAPSHOT");
-        }
-
-        if ui.is_fkey_down(Key::F12) {
-            grasp_editor_state.update_snapshot_all("SNAPSHOT");
-        }
-
         let gui = GuiState::new(ui);
         grasp_editor_state.process_requests(&gui);
         grasp_editor_state.show(&gui);
@@ -1,7 +1,13 @@
 #![allow(dead_code)]
 pub mod collage;
+pub mod collage_parser;
+pub mod metrics;
+pub mod query_tiles;
 pub mod traversal;
 mod unit_tests;
 
 pub use collage::*;
+pub use collage_parser::*;
+pub use metrics::*;
+pub use query_tiles::*;
 pub use traversal::*;
@@ -1,6 +1,9 @@
+use std::collections::HashSet;
+
 use itertools::Itertools;
 use mosaic::{
-    internals::{Mosaic, MosaicIO, Tile},
+    capabilities::ArchetypeSubject,
+    internals::{Mosaic, MosaicIO, Tile, Value},
     iterators::{
         component_selectors::ComponentSelectors, tile_filters::TileFilters,
         tile_getters::TileGetters,
@@ -8,16 +11,32 @@ use mosaic::{
 };
 
 #[repr(u8)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Pick {
     Arrows,
     Descriptors,
     Extensions,
     Targets,
     Sources,
+    Reachable(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Equals(String),
+    Range { min: Option<f64>, max: Option<f64> },
+    Contains(String),
+    Matches(String),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Predicate {
+    pub component: String,
+    pub field: String,
+    pub condition: Condition,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Cut {
     Include(Vec<String>),
     Exclude(Vec<String>),
@@ -25,6 +44,7 @@ pub enum Cut {
     Arrows,
     Descriptors,
     Extensions,
+    Where(Predicate),
 }
 
 impl Cut {
@@ -36,14 +56,18 @@ impl Cut {
             Cut::Arrows => 3,
             Cut::Descriptors => 4,
             Cut::Extensions => 5,
+            Cut::Where(_) => 6,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum Collage {
     Tiles,
     CombineQueries(Vec<Collage>),
+    Intersect(Vec<Collage>),
+    Difference(Box<Collage>, Box<Collage>),
+    SymmetricDifference(Box<Collage>, Box<Collage>),
     Pick(Pick, Box<Collage>),
     Cut(Cut, Box<Collage>),
 }
@@ -97,15 +121,147 @@ pub fn take_objects(mq: Box<Collage>) -> Box<Collage> {
 pub fn gather(mqs: Vec<Collage>) -> Box<Collage> {
     Box::new(Collage::CombineQueries(mqs))
 }
+pub fn intersect(mqs: Vec<Collage>) -> Box<Collage> {
+    Box::new(Collage::Intersect(mqs))
+}
+pub fn difference(mq: Box<Collage>, without: Box<Collage>) -> Box<Collage> {
+    Box::new(Collage::Difference(mq, without))
+}
+pub fn symmetric_difference(a: Box<Collage>, b: Box<Collage>) -> Box<Collage> {
+    Box::new(Collage::SymmetricDifference(a, b))
+}
+pub fn reachable_from(steps: usize, mq: Box<Collage>) -> Box<Collage> {
+    Box::new(Collage::Pick(Pick::Reachable(steps), mq))
+}
+pub fn take_where(predicate: Predicate, mq: Box<Collage>) -> Box<Collage> {
+    Box::new(Collage::Cut(Cut::Where(predicate), mq))
+}
+
+// numbers compare in the type of their field, only f32 fields round the expected value
+fn equals_number(value: &Value, expected: &str) -> Option<bool> {
+    let float = expected.parse::<f64>().ok();
+    let signed = |v: i64| {
+        expected
+            .parse::<i64>()
+            .ok()
+            .map(|e| v == e)
+            .or_else(|| float.map(|e| v as f64 == e))
+    };
+    let unsigned = |v: u64| {
+        expected
+            .parse::<u64>()
+            .ok()
+            .map(|e| v == e)
+            .or_else(|| float.map(|e| v as f64 == e))
+    };
+
+    match value {
+        Value::I8(v) => signed(*v as i64),
+        Value::I16(v) => signed(*v as i64),
+        Value::I32(v) => signed(*v as i64),
+        Value::I64(v) => signed(*v),
+        Value::U8(v) => unsigned(*v as u64),
+        Value::U16(v) => unsigned(*v as u64),
+        Value::U32(v) => unsigned(*v as u64),
+        Value::U64(v) => unsigned(*v),
+        Value::F32(v) => float.map(|e| *v == e as f32),
+        Value::F64(v) => float.map(|e| *v == e),
+        _ => None,
+    }
+}
+
+impl Condition {
+    fn accepts(&self, value: &Value, regex: Option<&Regex>) -> bool {
+        match self {
+            Condition::Equals(expected) => equals_number(value, expected)
+                .unwrap_or_else(|| value_to_string(value) == *expected),
+            Condition::Range { min, max } => value_as_f64(value)
+                .map(|v| min.map_or(true, |m| v >= m) && max.map_or(true, |m| v <= m))
+                .unwrap_or(false),
+            Condition::Contains(text) => value_to_string(value).contains(text.as_str()),
+            Condition::Matches(_) => regex
+                .map(|r| r.is_match(&value_to_string(value)))
+                .unwrap_or(false),
+        }
+    }
+}
+
+impl Predicate {
+    fn regex(&self) -> Option<Regex> {
+        match &self.condition {
+            Condition::Matches(pattern) => Regex::new(pattern).ok(),
+            _ => None,
+        }
+    }
+
+    fn matches_with(&self, tile: &Tile, regex: Option<&Regex>) -> bool {
+        let mut candidates = tile.get_components(&self.component).collect_vec();
+        if tile.component.is(&self.component) {
+            candidates.push(tile.clone());
+        }
+
+        candidates
+            .iter()
+            .filter(|c| field_names(c).contains(&self.field))
+            .any(|c| self.condition.accepts(&c.get(&self.field), regex))
+    }
+
+    pub fn matches(&self, tile: &Tile) -> bool {
+        self.matches_with(tile, self.regex().as_ref())
+    }
+
+    pub fn filter<I: Iterator<Item = Tile>>(&self, tiles: I) -> std::vec::IntoIter<Tile> {
+        let regex = self.regex();
+        tiles
+            .filter(|t| self.matches_with(t, regex.as_ref()))
+            .collect_vec()
+            .into_iter()
+    }
+}
+
+fn reachable(
+    mosaic: Arc<Mosaic>,
+    traversal: Traversal,
+    start: Vec<Tile>,
+    steps: usize,
+) -> Vec<Tile> {
+    let op = mosaic.traverse(traversal);
+    let mut visited = HashSet::new();
+    let mut result = vec![];
+    let mut frontier = start;
+
+    for _ in 0..steps {
+        let next = frontier
+            .iter()
+            .flat_map(|t| op.get_forward_neighbors(t))
+            .filter(|t| visited.insert(t.id))
+            .collect_vec();
+
+        if next.is_empty() {
+            break;
+        }
+
+        result.extend(next.clone());
+        frontier = next;
+    }
+
+    result
+}
 
 use std::sync::Arc;
 
-use crate::querying::traversal::Traverse;
+use crate::{
+    querying::traversal::Traverse,
+    utilities::{field_names, value_as_f64, value_to_string},
+};
+use regex::Regex;
 
-use super::traversal::Traversal;
+use super::{query_tiles::is_query_tile, traversal::Traversal};
 impl MosaicCollage for Arc<Mosaic> {
     fn apply_collage(&self, mq: &Collage, tiles: Option<Vec<Tile>>) -> std::vec::IntoIter<Tile> {
-        let traversal: Traversal = tiles.unwrap_or(self.get_all().collect_vec()).into();
+        let traversal: Traversal = tiles
+            .unwrap_or_else(|| self.get_all().filter(|t| !is_query_tile(t)).collect_vec())
+            .into();
         mq.query(Arc::clone(self), traversal)
     }
 }
@@ -120,6 +276,12 @@ impl Collage {
             Collage::Pick(S::Extensions, b) => b.query(mosaic, traversal).get_extensions(),
             Collage::Pick(S::Targets, b) => b.query(mosaic, traversal).get_targets(),
             Collage::Pick(S::Sources, b) => b.query(mosaic, traversal).get_sources(),
+            Collage::Pick(S::Reachable(steps), b) => {
+                let start = b
+                    .query(Arc::clone(&mosaic), traversal.clone())
+                    .collect_vec();
+                reachable(mosaic, traversal, start, *steps).into_iter()
+            }
             Collage::Cut(F::Include(components), b) => {
                 b.query(mosaic, traversal).include_components(components)
             }
@@ -130,12 +292,52 @@ impl Collage {
             Collage::Cut(F::Objects, b) => b.query(mosaic, traversal).filter_objects(),
             Collage::Cut(F::Descriptors, b) => b.query(mosaic, traversal).filter_descriptors(),
             Collage::Cut(F::Extensions, b) => b.query(mosaic, traversal).filter_extensions(),
+            Collage::Cut(F::Where(predicate), b) => predicate.filter(b.query(mosaic, traversal)),
             Collage::CombineQueries(bs) => bs
                 .iter()
                 .map(|b| b.query(Arc::clone(&mosaic), traversal.clone()))
                 .fold(vec![].into_iter(), |all, next| {
                     all.chain(next).unique().collect_vec().into_iter()
                 }),
+            Collage::Intersect(bs) => {
+                let mut results = bs.iter().map(|b| {
+                    b.query(Arc::clone(&mosaic), traversal.clone())
+                        .collect_vec()
+                });
+
+                let first = results.next().unwrap_or_default();
+                results
+                    .fold(first, |all, next| {
+                        all.into_iter().filter(|t| next.contains(t)).collect_vec()
+                    })
+                    .into_iter()
+                    .unique()
+                    .collect_vec()
+                    .into_iter()
+            }
+            Collage::Difference(a, b) => {
+                let removed = b
+                    .query(Arc::clone(&mosaic), traversal.clone())
+                    .collect_vec();
+                a.query(mosaic, traversal)
+                    .filter(|t| !removed.contains(t))
+                    .unique()
+                    .collect_vec()
+                    .into_iter()
+            }
+            Collage::SymmetricDifference(a, b) => {
+                let left = a
+                    .query(Arc::clone(&mosaic), traversal.clone())
+                    .collect_vec();
+                let right = b.query(mosaic, traversal).collect_vec();
+                left.iter()
+                    .filter(|t| !right.contains(t))
+                    .chain(right.iter().filter(|t| !left.contains(t)))
+                    .unique()
+                    .cloned()
+                    .collect_vec()
+                    .into_iter()
+            }
         }
     }
 }
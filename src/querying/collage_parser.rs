@@ -0,0 +1,529 @@
+use std::fmt::Display;
+
+use itertools::Itertools;
+use regex::Regex;
+
+use super::collage::{Collage, Condition, Cut, Pick, Predicate};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (at {})", self.message, self.position)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Text(String),
+    Number(String),
+    Dot,
+    DotDot,
+    Equals,
+    Open,
+    Close,
+    OpenBracket,
+    CloseBracket,
+    Comma,
+    End,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word(w) => write!(f, "'{}'", w),
+            Token::Text(t) => write!(f, "\"{}\"", t),
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Dot => write!(f, "'.'"),
+            Token::DotDot => write!(f, "'..'"),
+            Token::Equals => write!(f, "'='"),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::OpenBracket => write!(f, "'['"),
+            Token::CloseBracket => write!(f, "']'"),
+            Token::Comma => write!(f, "','"),
+            Token::End => write!(f, "end of query"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let mut tokens = vec![];
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '[' => Token::OpenBracket,
+            ']' => Token::CloseBracket,
+            ',' => Token::Comma,
+            '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        // any other backslash is kept, so regex classes like \d need no doubling
+                        Some((_, '\\')) => match chars.peek() {
+                            Some(&(_, c @ ('"' | '\\'))) => {
+                                chars.next();
+                                text.push(c);
+                            }
+                            _ => text.push('\\'),
+                        },
+                        Some((_, c)) => text.push(c),
+                        None => {
+                            return Err(ParseError {
+                                message: "unterminated string".to_string(),
+                                position,
+                            })
+                        }
+                    }
+                }
+                tokens.push((Token::Text(text), position));
+                continue;
+            }
+            '=' => {
+                chars.next();
+                if let Some((_, '=')) = chars.peek() {
+                    chars.next();
+                }
+                tokens.push((Token::Equals, position));
+                continue;
+            }
+            '.' => {
+                chars.next();
+                if let Some((_, '.')) = chars.peek() {
+                    chars.next();
+                    tokens.push((Token::DotDot, position));
+                } else {
+                    tokens.push((Token::Dot, position));
+                }
+                continue;
+            }
+            c if c.is_ascii_digit()
+                || (c == '-'
+                    && input[position + 1..].starts_with(|n: char| n.is_ascii_digit())) =>
+            {
+                let mut number = String::from(c);
+                chars.next();
+                while let Some(&(_, c)) = chars.peek() {
+                    let fraction = c == '.'
+                        && input[position + number.len() + 1..]
+                            .starts_with(|n: char| n.is_ascii_digit());
+                    if c.is_ascii_digit() || fraction {
+                        number.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push((Token::Number(number), position));
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut word = String::new();
+                while let Some(&(_, c)) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        word.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push((Token::Word(word), position));
+                continue;
+            }
+            c => {
+                return Err(ParseError {
+                    message: format!("unexpected character '{}'", c),
+                    position,
+                })
+            }
+        };
+
+        chars.next();
+        tokens.push((token, position));
+    }
+
+    tokens.push((Token::End, input.len()));
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &(Token, usize) {
+        &self.tokens[self.index]
+    }
+
+    fn next(&mut self) -> (Token, usize) {
+        let token = self.tokens[self.index].clone();
+        if token.0 != Token::End {
+            self.index += 1;
+        }
+        token
+    }
+
+    fn error<T>(&self, message: String, position: usize) -> Result<T, ParseError> {
+        Err(ParseError { message, position })
+    }
+
+    fn expect(&mut self, expected: Token, context: &str) -> Result<(), ParseError> {
+        let (token, position) = self.next();
+        if token == expected {
+            Ok(())
+        } else {
+            self.error(
+                format!("expected {} {}, found {}", expected, context, token),
+                position,
+            )
+        }
+    }
+
+    fn components(&mut self, name: &str) -> Result<Vec<String>, ParseError> {
+        self.expect(Token::OpenBracket, &format!("after '{}'", name))?;
+        let mut components = vec![];
+
+        loop {
+            match self.next() {
+                (Token::Word(w), _) | (Token::Text(w), _) => components.push(w),
+                (Token::CloseBracket, _) if components.is_empty() => break,
+                (token, position) => {
+                    return self.error(
+                        format!("expected component name, found {}", token),
+                        position,
+                    )
+                }
+            }
+
+            match self.next() {
+                (Token::Comma, _) => continue,
+                (Token::CloseBracket, _) => break,
+                (token, position) => {
+                    return self.error(
+                        format!("expected ',' or ']' in component list, found {}", token),
+                        position,
+                    )
+                }
+            }
+        }
+
+        Ok(components)
+    }
+
+    fn argument(&mut self, name: &str) -> Result<Box<Collage>, ParseError> {
+        self.expect(Token::Open, &format!("after '{}'", name))?;
+        let inner = self.query()?;
+        self.expect(Token::Close, &format!("to close '{}'", name))?;
+        Ok(inner)
+    }
+
+    fn arguments(&mut self, name: &str) -> Result<Vec<Collage>, ParseError> {
+        self.expect(Token::Open, &format!("after '{}'", name))?;
+        let mut queries = vec![*self.query()?];
+
+        loop {
+            match self.next() {
+                (Token::Comma, _) => queries.push(*self.query()?),
+                (Token::Close, _) => break,
+                (token, position) => {
+                    return self.error(
+                        format!("expected ',' or ')' in '{}', found {}", name, token),
+                        position,
+                    )
+                }
+            }
+        }
+
+        Ok(queries)
+    }
+
+    fn pair(
+        &mut self,
+        name: &str,
+        position: usize,
+    ) -> Result<(Box<Collage>, Box<Collage>), ParseError> {
+        let mut queries = self.arguments(name)?;
+        if queries.len() != 2 {
+            return self.error(format!("'{}' takes exactly two queries", name), position);
+        }
+
+        let b = queries.pop().unwrap();
+        let a = queries.pop().unwrap();
+        Ok((Box::new(a), Box::new(b)))
+    }
+
+    fn steps(&mut self) -> Result<usize, ParseError> {
+        self.expect(Token::OpenBracket, "after 'reachable'")?;
+        let steps = match self.next() {
+            (Token::Number(n), position) => match n.parse::<usize>() {
+                Ok(steps) => steps,
+                Err(_) => {
+                    return self.error(format!("expected a step count, found {}", n), position)
+                }
+            },
+            (token, position) => {
+                return self.error(format!("expected a step count, found {}", token), position)
+            }
+        };
+        self.expect(Token::CloseBracket, "after the step count")?;
+        Ok(steps)
+    }
+
+    fn name(&mut self, what: &str) -> Result<String, ParseError> {
+        match self.next() {
+            (Token::Word(w), _) | (Token::Text(w), _) => Ok(w),
+            (token, position) => {
+                self.error(format!("expected {}, found {}", what, token), position)
+            }
+        }
+    }
+
+    fn bound(&mut self) -> Result<Option<f64>, ParseError> {
+        if let (Token::Number(n), position) = self.peek().clone() {
+            self.next();
+            match n.parse::<f64>() {
+                Ok(value) => Ok(Some(value)),
+                Err(_) => self.error(format!("invalid number {}", n), position),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn predicate(&mut self) -> Result<Predicate, ParseError> {
+        self.expect(Token::OpenBracket, "after 'where'")?;
+        let component = self.name("component name")?;
+        let field = if self.peek().0 == Token::Dot {
+            self.next();
+            self.name("field name")?
+        } else {
+            "self".to_string()
+        };
+
+        let condition = match self.next() {
+            (Token::Equals, _) => match self.next() {
+                (Token::Word(v), _) | (Token::Text(v), _) | (Token::Number(v), _) => {
+                    Condition::Equals(v)
+                }
+                (token, position) => {
+                    return self.error(format!("expected a value, found {}", token), position)
+                }
+            },
+            (Token::Word(op), _) if op == "in" => {
+                let min = self.bound()?;
+                self.expect(Token::DotDot, "in range")?;
+                let max = self.bound()?;
+                Condition::Range { min, max }
+            }
+            (Token::Word(op), _) if op == "contains" => Condition::Contains(self.name("text")?),
+            (Token::Word(op), _) if op == "matches" => {
+                let position = self.peek().1;
+                let pattern = self.name("pattern")?;
+                if let Err(e) = Regex::new(&pattern) {
+                    return self.error(format!("invalid pattern: {}", e), position);
+                }
+                Condition::Matches(pattern)
+            }
+            (token, position) => {
+                return self.error(
+                    format!(
+                        "expected '=', 'in', 'contains' or 'matches', found {}",
+                        token
+                    ),
+                    position,
+                )
+            }
+        };
+
+        self.expect(Token::CloseBracket, "to close 'where'")?;
+        Ok(Predicate {
+            component,
+            field,
+            condition,
+        })
+    }
+
+    fn query(&mut self) -> Result<Box<Collage>, ParseError> {
+        let (token, position) = self.next();
+        let name = match token {
+            Token::Word(name) => name,
+            token => return self.error(format!("expected a query, found {}", token), position),
+        };
+
+        let pick = |p: Pick, parser: &mut Parser| -> Result<Box<Collage>, ParseError> {
+            Ok(Box::new(Collage::Pick(p, parser.argument(&name)?)))
+        };
+
+        let cut = |c: Cut, parser: &mut Parser| -> Result<Box<Collage>, ParseError> {
+            Ok(Box::new(Collage::Cut(c, parser.argument(&name)?)))
+        };
+
+        match name.as_str() {
+            "all" | "tiles" => Ok(Box::new(Collage::Tiles)),
+            "arrows_from" => pick(Pick::Arrows, self),
+            "descriptors_from" => pick(Pick::Descriptors, self),
+            "extensions_from" => pick(Pick::Extensions, self),
+            "targets" | "targets_from" => pick(Pick::Targets, self),
+            "sources" | "sources_from" => pick(Pick::Sources, self),
+            "arrows" => cut(Cut::Arrows, self),
+            "objects" => cut(Cut::Objects, self),
+            "descriptors" => cut(Cut::Descriptors, self),
+            "extensions" => cut(Cut::Extensions, self),
+            "include" => {
+                let components = self.components(&name)?;
+                cut(Cut::Include(components), self)
+            }
+            "exclude" => {
+                let components = self.components(&name)?;
+                cut(Cut::Exclude(components), self)
+            }
+            "where" => {
+                let predicate = self.predicate()?;
+                cut(Cut::Where(predicate), self)
+            }
+            "reachable" => {
+                let steps = if self.peek().0 == Token::OpenBracket {
+                    self.steps()?
+                } else {
+                    usize::MAX
+                };
+                pick(Pick::Reachable(steps), self)
+            }
+            "gather" => Ok(Box::new(Collage::CombineQueries(self.arguments(&name)?))),
+            "intersect" => Ok(Box::new(Collage::Intersect(self.arguments(&name)?))),
+            "difference" => {
+                let (a, b) = self.pair(&name, position)?;
+                Ok(Box::new(Collage::Difference(a, b)))
+            }
+            "symmetric_difference" => {
+                let (a, b) = self.pair(&name, position)?;
+                Ok(Box::new(Collage::SymmetricDifference(a, b)))
+            }
+            _ => self.error(format!("unknown query '{}'", name), position),
+        }
+    }
+}
+
+pub fn parse_collage(input: &str) -> Result<Box<Collage>, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        index: 0,
+    };
+
+    let query = parser.query()?;
+    match parser.peek() {
+        (Token::End, _) => Ok(query),
+        (token, position) => parser.error(
+            format!("unexpected {} after the end of the query", token),
+            *position,
+        ),
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn format_name(name: &str) -> String {
+    let is_word = name.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+
+    if is_word {
+        name.to_string()
+    } else {
+        quote(name)
+    }
+}
+
+fn format_components(components: &[String]) -> String {
+    components.iter().map(|c| format_name(c)).join(", ")
+}
+
+impl Display for Predicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{} ",
+            format_name(&self.component),
+            format_name(&self.field)
+        )?;
+
+        match &self.condition {
+            Condition::Equals(value) => write!(f, "= {}", quote(value)),
+            Condition::Range { min, max } => write!(
+                f,
+                "in {}..{}",
+                min.map(|m| m.to_string()).unwrap_or_default(),
+                max.map(|m| m.to_string()).unwrap_or_default()
+            ),
+            Condition::Contains(text) => write!(f, "contains {}", quote(text)),
+            Condition::Matches(pattern) => write!(f, "matches {}", quote(pattern)),
+        }
+    }
+}
+
+impl Display for Collage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Collage::Tiles => write!(f, "all"),
+            Collage::Pick(pick, inner) => {
+                let name = match pick {
+                    Pick::Arrows => "arrows_from",
+                    Pick::Descriptors => "descriptors_from",
+                    Pick::Extensions => "extensions_from",
+                    Pick::Targets => "targets",
+                    Pick::Sources => "sources",
+                    Pick::Reachable(usize::MAX) => "reachable",
+                    Pick::Reachable(steps) => return write!(f, "reachable[{}]({})", steps, inner),
+                };
+                write!(f, "{}({})", name, inner)
+            }
+            Collage::Cut(cut, inner) => match cut {
+                Cut::Include(components) => {
+                    write!(f, "include[{}]({})", format_components(components), inner)
+                }
+                Cut::Exclude(components) => {
+                    write!(f, "exclude[{}]({})", format_components(components), inner)
+                }
+                Cut::Objects => write!(f, "objects({})", inner),
+                Cut::Arrows => write!(f, "arrows({})", inner),
+                Cut::Descriptors => write!(f, "descriptors({})", inner),
+                Cut::Extensions => write!(f, "extensions({})", inner),
+                Cut::Where(predicate) => write!(f, "where[{}]({})", predicate, inner),
+            },
+            Collage::CombineQueries(queries) => {
+                write!(
+                    f,
+                    "gather({})",
+                    queries.iter().map(|q| q.to_string()).join(", ")
+                )
+            }
+            Collage::Intersect(queries) => {
+                write!(
+                    f,
+                    "intersect({})",
+                    queries.iter().map(|q| q.to_string()).join(", ")
+                )
+            }
+            Collage::Difference(a, b) => write!(f, "difference({}, {})", a, b),
+            Collage::SymmetricDifference(a, b) => {
+                write!(f, "symmetric_difference({}, {})", a, b)
+            }
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use itertools::Itertools;
+use mosaic::internals::{EntityId, Tile};
+
+use super::traversal::TraversalOperator;
+
+const PAGERANK_DAMPING: f32 = 0.85;
+const PAGERANK_ITERATIONS: usize = 100;
+const PAGERANK_TOLERANCE: f32 = 1e-6;
+
+#[derive(Default, Clone)]
+pub struct GraphMetrics {
+    pub node_count: usize,
+    pub arrow_count: usize,
+    pub density: f32,
+    pub degree_distribution: BTreeMap<usize, usize>,
+    pub components: Vec<Vec<Tile>>,
+    pub degree: Vec<(Tile, f32)>,
+    pub betweenness: Vec<(Tile, f32)>,
+    pub pagerank: Vec<(Tile, f32)>,
+}
+
+fn ranked(nodes: &[Tile], scores: &HashMap<EntityId, f32>) -> Vec<(Tile, f32)> {
+    nodes
+        .iter()
+        .map(|n| (n.clone(), scores.get(&n.id).copied().unwrap_or_default()))
+        .sorted_by(|(a, x), (b, y)| y.total_cmp(x).then(a.id.cmp(&b.id)))
+        .collect_vec()
+}
+
+impl TraversalOperator<'_> {
+    fn forward_adjacency(&self, nodes: &[Tile]) -> HashMap<EntityId, Vec<EntityId>> {
+        let ids = nodes.iter().map(|n| n.id).collect::<HashSet<_>>();
+        nodes
+            .iter()
+            .map(|n| {
+                let targets = self
+                    .get_arrows_from(n)
+                    .filter(|a| !a.is_loop() && ids.contains(&a.target_id()))
+                    .map(|a| a.target_id())
+                    .collect_vec();
+                (n.id, targets)
+            })
+            .collect()
+    }
+
+    pub fn degree_centrality(&self) -> HashMap<EntityId, usize> {
+        let nodes = self.get_objects().collect_vec();
+        let mut degrees: HashMap<EntityId, usize> = nodes.iter().map(|n| (n.id, 0)).collect();
+
+        for (source, targets) in self.forward_adjacency(&nodes) {
+            *degrees.get_mut(&source).unwrap() += targets.len();
+            for target in targets {
+                *degrees.get_mut(&target).unwrap() += 1;
+            }
+        }
+
+        degrees
+    }
+
+    pub fn weakly_connected_components(&self) -> Vec<Vec<Tile>> {
+        let nodes = self.get_objects().sorted_by_key(|t| t.id).collect_vec();
+        let mut visited = HashSet::new();
+        let mut components = vec![];
+
+        for root in &nodes {
+            if visited.contains(&root.id) {
+                continue;
+            }
+
+            let mut component = vec![];
+            let mut queue = VecDeque::from([root.clone()]);
+            while let Some(current) = queue.pop_front() {
+                if !visited.insert(current.id) {
+                    continue;
+                }
+
+                for next in self.get_neighbors(&current) {
+                    if !visited.contains(&next.id) && nodes.contains(&next) {
+                        queue.push_back(next);
+                    }
+                }
+
+                component.push(current);
+            }
+
+            components.push(component);
+        }
+
+        components.sort_by_key(|c| std::cmp::Reverse(c.len()));
+        components
+    }
+
+    // Brandes' algorithm over unweighted, directed arrows
+    pub fn betweenness_centrality(&self) -> HashMap<EntityId, f32> {
+        let nodes = self.get_objects().collect_vec();
+        let adjacency = self.forward_adjacency(&nodes);
+        let mut centrality: HashMap<EntityId, f32> = nodes.iter().map(|n| (n.id, 0.0)).collect();
+
+        for source in &nodes {
+            let mut stack = vec![];
+            let mut predecessors: HashMap<EntityId, Vec<EntityId>> = HashMap::new();
+            let mut paths: HashMap<EntityId, f32> = HashMap::from([(source.id, 1.0)]);
+            let mut distance: HashMap<EntityId, usize> = HashMap::from([(source.id, 0)]);
+            let mut queue = VecDeque::from([source.id]);
+
+            while let Some(v) = queue.pop_front() {
+                stack.push(v);
+                let (dv, pv) = (distance[&v], paths[&v]);
+                for &w in &adjacency[&v] {
+                    if !distance.contains_key(&w) {
+                        distance.insert(w, dv + 1);
+                        queue.push_back(w);
+                    }
+
+                    if distance[&w] == dv + 1 {
+                        *paths.entry(w).or_default() += pv;
+                        predecessors.entry(w).or_default().push(v);
+                    }
+                }
+            }
+
+            let mut dependency: HashMap<EntityId, f32> = HashMap::new();
+            while let Some(w) = stack.pop() {
+                for &v in predecessors.get(&w).into_iter().flatten() {
+                    let delta = paths[&v] / paths[&w] * (1.0 + dependency.get(&w).unwrap_or(&0.0));
+                    *dependency.entry(v).or_default() += delta;
+                }
+
+                if w != source.id {
+                    *centrality.get_mut(&w).unwrap() += dependency.get(&w).unwrap_or(&0.0);
+                }
+            }
+        }
+
+        centrality
+    }
+
+    pub fn pagerank(&self) -> HashMap<EntityId, f32> {
+        let nodes = self.get_objects().collect_vec();
+        if nodes.is_empty() {
+            return HashMap::new();
+        }
+
+        let adjacency = self.forward_adjacency(&nodes);
+        let n = nodes.len() as f32;
+        let mut rank: HashMap<EntityId, f32> = nodes.iter().map(|t| (t.id, 1.0 / n)).collect();
+
+        for _ in 0..PAGERANK_ITERATIONS {
+            let dangling = nodes
+                .iter()
+                .filter(|t| adjacency[&t.id].is_empty())
+                .map(|t| rank[&t.id])
+                .sum::<f32>();
+
+            let base = (1.0 - PAGERANK_DAMPING) / n + PAGERANK_DAMPING * dangling / n;
+            let mut next: HashMap<EntityId, f32> = nodes.iter().map(|t| (t.id, base)).collect();
+
+            for node in &nodes {
+                let targets = &adjacency[&node.id];
+                for target in targets {
+                    *next.get_mut(target).unwrap() +=
+                        PAGERANK_DAMPING * rank[&node.id] / targets.len() as f32;
+                }
+            }
+
+            let delta = nodes
+                .iter()
+                .map(|t| (next[&t.id] - rank[&t.id]).abs())
+                .sum::<f32>();
+
+            rank = next;
+            if delta < PAGERANK_TOLERANCE {
+                break;
+            }
+        }
+
+        rank
+    }
+
+    pub fn metrics(&self) -> GraphMetrics {
+        let nodes = self.get_objects().sorted_by_key(|t| t.id).collect_vec();
+        let ids = nodes.iter().map(|n| n.id).collect::<HashSet<_>>();
+        let arrow_count = nodes
+            .iter()
+            .map(|n| {
+                self.get_arrows_from(n)
+                    .filter(|a| ids.contains(&a.target_id()))
+                    .count()
+            })
+            .sum::<usize>();
+
+        let node_count = nodes.len();
+        let density = if node_count > 1 {
+            arrow_count as f32 / (node_count * (node_count - 1)) as f32
+        } else {
+            0.0
+        };
+
+        let degrees = self.degree_centrality();
+        let mut degree_distribution = BTreeMap::new();
+        for degree in degrees.values() {
+            *degree_distribution.entry(*degree).or_default() += 1;
+        }
+
+        let degree_scores = degrees.into_iter().map(|(id, d)| (id, d as f32)).collect();
+
+        GraphMetrics {
+            node_count,
+            arrow_count,
+            density,
+            degree_distribution,
+            components: self.weakly_connected_components(),
+            degree: ranked(&nodes, &degree_scores),
+            betweenness: ranked(&nodes, &self.betweenness_centrality()),
+            pagerank: ranked(&nodes, &self.pagerank()),
+        }
+    }
+}
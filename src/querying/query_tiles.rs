@@ -0,0 +1,306 @@
+use std::sync::Arc;
+
+use itertools::Itertools;
+use mosaic::{
+    capabilities::ArchetypeSubject,
+    internals::{
+        par, pars, void, ComponentValuesBuilderSetter, Mosaic, MosaicCRUD, MosaicIO, Tile,
+        TileFieldEmptyQuery,
+    },
+    iterators::{
+        component_selectors::ComponentSelectors, tile_filters::TileFilters,
+        tile_getters::TileGetters,
+    },
+};
+
+use super::collage::{Collage, Condition, Cut, Pick, Predicate};
+
+// saved queries live in the document, but are not part of the graph they query
+pub const QUERY_COMPONENTS: [&str; 7] = [
+    "Query",
+    "QueryRoot",
+    "QueryNode",
+    "QueryOperand",
+    "QueryComponent",
+    "QueryPredicate",
+    "QueryHighlight",
+];
+
+pub fn is_query_tile(tile: &Tile) -> bool {
+    QUERY_COMPONENTS.iter().any(|c| tile.component.is(c))
+}
+
+pub trait QueryStorage {
+    fn make_query(&self, name: &str, collage: &Collage) -> QueryTile;
+    fn get_queries(&self) -> Vec<QueryTile>;
+}
+
+pub struct QueryTile(pub Tile);
+
+impl AsRef<Tile> for QueryTile {
+    fn as_ref(&self) -> &Tile {
+        &self.0
+    }
+}
+
+impl QueryTile {
+    pub fn get_name(&self) -> String {
+        self.0.get("self").as_str().to_string()
+    }
+
+    pub fn get_root(&self) -> Option<Tile> {
+        self.0
+            .iter()
+            .get_arrows_from()
+            .include_component("QueryRoot")
+            .get_targets()
+            .next()
+    }
+
+    pub fn get_collage(&self) -> Option<Box<Collage>> {
+        self.get_root()
+            .and_then(|root| load_node(&root))
+            .map(Box::new)
+    }
+
+    pub fn get_nodes(&self) -> Vec<Tile> {
+        let mut nodes = vec![];
+        let mut stack = self.get_root().into_iter().collect_vec();
+        while let Some(node) = stack.pop() {
+            stack.extend(operands(&node));
+            nodes.push(node);
+        }
+
+        nodes
+    }
+
+    pub fn is_highlighted(&self) -> bool {
+        self.0.get_component("QueryHighlight").is_some()
+    }
+
+    pub fn set_highlighted(&self, highlighted: bool) {
+        if highlighted && !self.is_highlighted() {
+            self.0.add_component("QueryHighlight", void());
+        } else if !highlighted {
+            self.0.remove_components("QueryHighlight");
+        }
+    }
+}
+
+fn kind(collage: &Collage) -> &'static str {
+    match collage {
+        Collage::Tiles => "all",
+        Collage::CombineQueries(_) => "gather",
+        Collage::Intersect(_) => "intersect",
+        Collage::Difference(_, _) => "difference",
+        Collage::SymmetricDifference(_, _) => "symmetric_difference",
+        Collage::Pick(Pick::Arrows, _) => "arrows_from",
+        Collage::Pick(Pick::Descriptors, _) => "descriptors_from",
+        Collage::Pick(Pick::Extensions, _) => "extensions_from",
+        Collage::Pick(Pick::Targets, _) => "targets",
+        Collage::Pick(Pick::Sources, _) => "sources",
+        Collage::Pick(Pick::Reachable(_), _) => "reachable",
+        Collage::Cut(Cut::Include(_), _) => "include",
+        Collage::Cut(Cut::Exclude(_), _) => "exclude",
+        Collage::Cut(Cut::Objects, _) => "objects",
+        Collage::Cut(Cut::Arrows, _) => "arrows",
+        Collage::Cut(Cut::Descriptors, _) => "descriptors",
+        Collage::Cut(Cut::Extensions, _) => "extensions",
+        Collage::Cut(Cut::Where(_), _) => "where",
+    }
+}
+
+fn condition_parts(condition: &Condition) -> (&'static str, String) {
+    match condition {
+        Condition::Equals(value) => ("equals", value.clone()),
+        Condition::Range { min, max } => (
+            "range",
+            format!(
+                "{}..{}",
+                min.map(|m| m.to_string()).unwrap_or_default(),
+                max.map(|m| m.to_string()).unwrap_or_default()
+            ),
+        ),
+        Condition::Contains(text) => ("contains", text.clone()),
+        Condition::Matches(pattern) => ("matches", pattern.clone()),
+    }
+}
+
+fn store_node(mosaic: &Arc<Mosaic>, collage: &Collage) -> Tile {
+    let steps = match collage {
+        Collage::Pick(Pick::Reachable(steps), _) => *steps as u64,
+        _ => 0u64,
+    };
+
+    let node = mosaic.new_object(
+        "QueryNode",
+        pars().set("kind", kind(collage)).set("steps", steps).ok(),
+    );
+
+    let children = match collage {
+        Collage::Tiles => vec![],
+        Collage::CombineQueries(queries) | Collage::Intersect(queries) => {
+            queries.iter().collect_vec()
+        }
+        Collage::Difference(a, b) | Collage::SymmetricDifference(a, b) => {
+            vec![a.as_ref(), b.as_ref()]
+        }
+        Collage::Pick(_, inner) | Collage::Cut(_, inner) => vec![inner.as_ref()],
+    };
+
+    match collage {
+        Collage::Cut(Cut::Include(components), _) | Collage::Cut(Cut::Exclude(components), _) => {
+            for (index, component) in components.iter().enumerate() {
+                mosaic.new_descriptor(
+                    &node,
+                    "QueryComponent",
+                    pars()
+                        .set("index", index as u64)
+                        .set("name", component.clone())
+                        .ok(),
+                );
+            }
+        }
+        Collage::Cut(Cut::Where(predicate), _) => {
+            let (condition, value) = condition_parts(&predicate.condition);
+            mosaic.new_descriptor(
+                &node,
+                "QueryPredicate",
+                pars()
+                    .set("component", predicate.component.clone())
+                    .set("field", predicate.field.clone())
+                    .set("condition", condition.to_string())
+                    .set("value", value)
+                    .ok(),
+            );
+        }
+        _ => {}
+    }
+
+    for (index, child) in children.into_iter().enumerate() {
+        let child = store_node(mosaic, child);
+        mosaic.new_arrow(&node, &child, "QueryOperand", par(index as u64));
+    }
+
+    node
+}
+
+fn operands(node: &Tile) -> Vec<Tile> {
+    node.iter()
+        .get_arrows_from()
+        .include_component("QueryOperand")
+        .sorted_by_key(|a| a.get("self").as_u64())
+        .get_targets()
+        .collect_vec()
+}
+
+fn load_predicate(node: &Tile) -> Option<Predicate> {
+    let descriptor = node
+        .iter()
+        .get_descriptors()
+        .include_component("QueryPredicate")
+        .next()?;
+
+    let value = descriptor.get("value").as_str().to_string();
+    let condition = match descriptor.get("condition").as_str().to_string().as_str() {
+        "equals" => Condition::Equals(value),
+        "range" => {
+            let (min, max) = value.split_once("..")?;
+            let bound = |b: &str| {
+                if b.is_empty() {
+                    Some(None)
+                } else {
+                    b.parse::<f64>().ok().map(Some)
+                }
+            };
+            Condition::Range {
+                min: bound(min)?,
+                max: bound(max)?,
+            }
+        }
+        "contains" => Condition::Contains(value),
+        "matches" => Condition::Matches(value),
+        _ => return None,
+    };
+
+    Some(Predicate {
+        component: descriptor.get("component").as_str().to_string(),
+        field: descriptor.get("field").as_str().to_string(),
+        condition,
+    })
+}
+
+fn load_node(node: &Tile) -> Option<Collage> {
+    let children = operands(node)
+        .iter()
+        .map(load_node)
+        .collect::<Option<Vec<_>>>()?;
+
+    let components = || {
+        node.iter()
+            .get_descriptors()
+            .include_component("QueryComponent")
+            .sorted_by_key(|c| c.get("index").as_u64())
+            .map(|c| c.get("name").as_str().to_string())
+            .collect_vec()
+    };
+
+    let kind = node.get("kind").as_s32().to_string();
+    let mut children = children.into_iter();
+
+    let collage = match kind.as_str() {
+        "all" => Collage::Tiles,
+        "gather" => Collage::CombineQueries(children.collect_vec()),
+        "intersect" => Collage::Intersect(children.collect_vec()),
+        "difference" | "symmetric_difference" => {
+            let a = Box::new(children.next()?);
+            let b = Box::new(children.next()?);
+            if kind == "difference" {
+                Collage::Difference(a, b)
+            } else {
+                Collage::SymmetricDifference(a, b)
+            }
+        }
+        _ => {
+            let inner = Box::new(children.next()?);
+            match kind.as_str() {
+                "arrows_from" => Collage::Pick(Pick::Arrows, inner),
+                "descriptors_from" => Collage::Pick(Pick::Descriptors, inner),
+                "extensions_from" => Collage::Pick(Pick::Extensions, inner),
+                "targets" => Collage::Pick(Pick::Targets, inner),
+                "sources" => Collage::Pick(Pick::Sources, inner),
+                "reachable" => {
+                    Collage::Pick(Pick::Reachable(node.get("steps").as_u64() as usize), inner)
+                }
+                "include" => Collage::Cut(Cut::Include(components()), inner),
+                "exclude" => Collage::Cut(Cut::Exclude(components()), inner),
+                "objects" => Collage::Cut(Cut::Objects, inner),
+                "arrows" => Collage::Cut(Cut::Arrows, inner),
+                "descriptors" => Collage::Cut(Cut::Descriptors, inner),
+                "extensions" => Collage::Cut(Cut::Extensions, inner),
+                "where" => Collage::Cut(Cut::Where(load_predicate(node)?), inner),
+                _ => return None,
+            }
+        }
+    };
+
+    Some(collage)
+}
+
+impl QueryStorage for Arc<Mosaic> {
+    fn make_query(&self, name: &str, collage: &Collage) -> QueryTile {
+        let query = self.new_object("Query", par(name.to_string()));
+        let root = store_node(self, collage);
+        self.new_arrow(&query, &root, "QueryRoot", void());
+        QueryTile(query)
+    }
+
+    fn get_queries(&self) -> Vec<QueryTile> {
+        self.get_all()
+            .include_component("Query")
+            .filter_objects()
+            .sorted_by_key(|q| q.id)
+            .map(QueryTile)
+            .collect_vec()
+    }
+}
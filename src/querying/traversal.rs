@@ -36,6 +36,11 @@ pub enum Traversal<'a> {
         tiles: Vec<Tile>,
         include_arrows: bool,
     },
+    // like Limited with arrows, leaving out the tiles of the given components
+    LimitedExcluding {
+        tiles: Vec<Tile>,
+        components: &'a [String],
+    },
     Default,
 }
 
@@ -68,19 +73,23 @@ impl TraversalOperator<'_> {
     }
 
     fn filter_traversal<I: Iterator<Item = Tile>>(&self, iter: I) -> Vec<Tile> {
+        let within = |tiles: &Vec<Tile>, t: &Tile| {
+            let mosaic = Arc::clone(&t.mosaic);
+            let s = mosaic.get(t.source_id()).unwrap();
+            let t = mosaic.get(t.target_id()).unwrap();
+            tiles.contains(&s) && tiles.contains(&t)
+        };
+
         match &self.traversal {
             Traversal::Exclude { components } => iter.exclude_components(components).collect_vec(),
             Traversal::Include { components } => iter.include_components(components).collect_vec(),
             Traversal::Limited {
                 tiles,
                 include_arrows: true,
-            } => iter
-                .filter(|t| {
-                    let mosaic = Arc::clone(&t.mosaic);
-                    let s = mosaic.get(t.source_id()).unwrap();
-                    let t = mosaic.get(t.target_id()).unwrap();
-                    tiles.contains(&s) && tiles.contains(&t)
-                })
+            } => iter.filter(|t| within(tiles, t)).collect_vec(),
+            Traversal::LimitedExcluding { tiles, components } => iter
+                .exclude_components(components)
+                .filter(|t| within(tiles, t))
                 .collect_vec(),
             Traversal::Limited {
                 tiles,
@@ -97,8 +106,13 @@ impl TraversalOperator<'_> {
 
     pub fn get_objects(&self) -> IntoIter<Tile> {
         match &self.traversal {
-            Traversal::Limited { tiles, .. } => tiles.clone().into_iter().filter_objects(),
-            _ => self.mosaic.get_all().filter_objects(),
+            Traversal::Limited { tiles, .. } | Traversal::LimitedExcluding { tiles, .. } => {
+                tiles.clone().into_iter().filter_objects()
+            }
+            Traversal::Default => self.mosaic.get_all().filter_objects(),
+            _ => self
+                .filter_traversal(self.mosaic.get_all().filter_objects())
+                .into_iter(),
         }
     }
 
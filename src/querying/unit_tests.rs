@@ -33,3 +33,466 @@ mod query_utility_tests {
         assert_eq!(vec![u.clone()], result);
     }
 }
+
+#[cfg(test)]
+mod metrics_tests {
+    use crate::querying::traversal::{Traversal, Traverse};
+
+    use itertools::Itertools;
+    use mosaic::internals::{void, Mosaic, MosaicCRUD, MosaicIO, MosaicTypelevelCRUD};
+
+    #[test]
+    fn metrics_on_path() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Arrow: unit;").unwrap();
+        let a = mosaic.new_object("void", void());
+        let b = mosaic.new_object("void", void());
+        let c = mosaic.new_object("void", void());
+        let d = mosaic.new_object("void", void());
+        mosaic.new_arrow(&a, &b, "Arrow", void());
+        mosaic.new_arrow(&b, &c, "Arrow", void());
+
+        let metrics = mosaic.traverse(vec![a, b.clone(), c, d].into()).metrics();
+
+        assert_eq!(4, metrics.node_count);
+        assert_eq!(2, metrics.arrow_count);
+        assert_eq!(2, metrics.components.len());
+        assert_eq!(b, metrics.degree[0].0);
+        assert_eq!(b, metrics.betweenness[0].0);
+        assert_eq!(1.0, metrics.betweenness[0].1);
+
+        let total_rank = metrics.pagerank.iter().map(|(_, r)| r).sum::<f32>();
+        assert!((total_rank - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn metrics_respect_exclude_filter() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Arrow: unit;").unwrap();
+        mosaic.new_type("Hidden: unit;").unwrap();
+        let a = mosaic.new_object("void", void());
+        let b = mosaic.new_object("void", void());
+        let c = mosaic.new_object("Hidden", void());
+        mosaic.new_arrow(&a, &b, "Arrow", void());
+        mosaic.new_arrow(&a, &b, "Hidden", void());
+        mosaic.new_arrow(&b, &c, "Arrow", void());
+
+        let excluded = ["Hidden".to_string()];
+        let op = mosaic.traverse(Traversal::Exclude {
+            components: &excluded,
+        });
+
+        let nodes = op.get_objects().collect_vec();
+        assert!(!nodes.contains(&c));
+        let metrics = op.metrics();
+        assert_eq!(2, metrics.node_count);
+        assert_eq!(1, metrics.arrow_count);
+    }
+}
+
+#[cfg(test)]
+mod collage_parser_tests {
+    use crate::querying::{
+        collage::{
+            gather, leave_components, take_arrows, take_components, targets_from, tiles,
+            MosaicCollage,
+        },
+        collage_parser::parse_collage,
+    };
+
+    use itertools::Itertools;
+    use mosaic::internals::{void, Mosaic, MosaicCRUD, MosaicIO};
+
+    #[test]
+    fn parse_nested_query() {
+        let parsed = parse_collage("targets(arrows(include[Label](all)))").unwrap();
+        assert_eq!(
+            targets_from(take_arrows(take_components(&["Label"], tiles()))),
+            parsed
+        );
+    }
+
+    #[test]
+    fn parse_gather_and_exclude() {
+        let parsed = parse_collage("gather(all, exclude[A, \"B C\"](tiles))").unwrap();
+        assert_eq!(
+            gather(vec![*tiles(), *leave_components(&["A", "B C"], tiles())]),
+            parsed
+        );
+    }
+
+    #[test]
+    fn printed_query_parses_back() {
+        let query = "gather(targets(arrows(all)), include[Label, \"Two words\"](objects(all)))";
+        let parsed = parse_collage(query).unwrap();
+        assert_eq!(query, parsed.to_string());
+        assert_eq!(parsed, parse_collage(&parsed.to_string()).unwrap());
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_problem() {
+        let error = parse_collage("targets(arrows(all)").unwrap_err();
+        assert_eq!(19, error.position);
+
+        let error = parse_collage("targets(nothing(all))").unwrap_err();
+        assert_eq!(8, error.position);
+        assert!(error.message.contains("nothing"));
+
+        let error = parse_collage("include(all)").unwrap_err();
+        assert_eq!(7, error.position);
+
+        let error = parse_collage("all)").unwrap_err();
+        assert_eq!(3, error.position);
+    }
+
+    #[test]
+    fn parsed_query_runs() {
+        let mosaic = Mosaic::new();
+        let t = mosaic.new_object("void", void());
+        let u = mosaic.new_object("void", void());
+        mosaic.new_arrow(&t, &u, "void", void());
+
+        let query = parse_collage("targets(arrows(all))").unwrap();
+        let result = mosaic.apply_collage(&query, None).collect_vec();
+        assert_eq!(vec![u], result);
+    }
+}
+
+#[cfg(test)]
+mod collage_set_tests {
+    use crate::querying::{
+        collage::{
+            difference, intersect, reachable_from, sources_from, symmetric_difference, take_arrows,
+            take_objects, take_where, targets_from, tiles, Condition, MosaicCollage, Predicate,
+        },
+        collage_parser::parse_collage,
+    };
+
+    use itertools::Itertools;
+    use mosaic::{
+        capabilities::ArchetypeSubject,
+        internals::{par, void, Mosaic, MosaicCRUD, MosaicIO, MosaicTypelevelCRUD},
+    };
+
+    fn predicate(component: &str, field: &str, condition: Condition) -> Predicate {
+        Predicate {
+            component: component.to_string(),
+            field: field.to_string(),
+            condition,
+        }
+    }
+
+    #[test]
+    fn set_operations() {
+        let mosaic = Mosaic::new();
+        let t = mosaic.new_object("void", void());
+        let u = mosaic.new_object("void", void());
+        let v = mosaic.new_object("void", void());
+        mosaic.new_arrow(&t, &u, "void", void());
+        mosaic.new_arrow(&u, &v, "void", void());
+
+        let run = |mq| mosaic.apply_collage(&mq, None).sorted().collect_vec();
+
+        assert_eq!(
+            vec![u.clone(), v.clone()],
+            run(intersect(vec![
+                *take_objects(tiles()),
+                *targets_from(take_arrows(tiles()))
+            ]))
+        );
+        assert_eq!(
+            vec![t.clone()],
+            run(difference(
+                take_objects(tiles()),
+                targets_from(take_arrows(tiles()))
+            ))
+        );
+        assert_eq!(
+            vec![t, v],
+            run(symmetric_difference(
+                targets_from(take_arrows(tiles())),
+                sources_from(take_arrows(tiles()))
+            ))
+        );
+    }
+
+    #[test]
+    fn where_predicates() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Label: str;").unwrap();
+        mosaic.new_type("Weight: f32;").unwrap();
+        let a = mosaic.new_object("void", void());
+        let b = mosaic.new_object("void", void());
+        let c = mosaic.new_object("void", void());
+        a.add_component("Label", par("start node".to_string()));
+        b.add_component("Label", par("end".to_string()));
+        a.add_component("Weight", par(1.5f32));
+        b.add_component("Weight", par(4.0f32));
+        c.add_component("Weight", par(10.0f32));
+
+        let run = |p: Predicate| {
+            mosaic
+                .apply_collage(&take_where(p, take_objects(tiles())), None)
+                .sorted()
+                .collect_vec()
+        };
+
+        assert_eq!(
+            vec![b.clone()],
+            run(predicate(
+                "Label",
+                "self",
+                Condition::Equals("end".to_string())
+            ))
+        );
+        assert_eq!(
+            vec![a.clone()],
+            run(predicate(
+                "Weight",
+                "self",
+                Condition::Equals("1.5".to_string())
+            ))
+        );
+        assert_eq!(
+            vec![a.clone(), b.clone()],
+            run(predicate(
+                "Weight",
+                "self",
+                Condition::Range {
+                    min: None,
+                    max: Some(5.0)
+                }
+            ))
+        );
+        assert_eq!(
+            vec![b.clone(), c.clone()],
+            run(predicate(
+                "Weight",
+                "self",
+                Condition::Range {
+                    min: Some(2.0),
+                    max: None
+                }
+            ))
+        );
+        assert_eq!(
+            vec![a.clone()],
+            run(predicate(
+                "Label",
+                "self",
+                Condition::Contains("node".to_string())
+            ))
+        );
+        assert_eq!(
+            vec![a.clone(), b.clone()],
+            run(predicate(
+                "Label",
+                "self",
+                Condition::Matches("^(start|end)".to_string())
+            ))
+        );
+        assert!(run(predicate(
+            "Label",
+            "missing",
+            Condition::Contains("".to_string())
+        ))
+        .is_empty());
+    }
+
+    #[test]
+    fn where_equals_keeps_u64_precision() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Ref: u64;").unwrap();
+        let a = mosaic.new_object("void", void());
+        let b = mosaic.new_object("void", void());
+        a.add_component("Ref", par(16_777_216u64));
+        b.add_component("Ref", par(16_777_217u64));
+
+        let found = mosaic
+            .apply_collage(
+                &take_where(
+                    predicate("Ref", "self", Condition::Equals("16777217".to_string())),
+                    take_objects(tiles()),
+                ),
+                None,
+            )
+            .collect_vec();
+        assert_eq!(vec![b], found);
+    }
+
+    #[test]
+    fn reachable_within_steps() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Label: str;").unwrap();
+        let a = mosaic.new_object("void", void());
+        let b = mosaic.new_object("void", void());
+        let c = mosaic.new_object("void", void());
+        let d = mosaic.new_object("void", void());
+        a.add_component("Label", par("a".to_string()));
+        mosaic.new_arrow(&a, &b, "void", void());
+        mosaic.new_arrow(&b, &c, "void", void());
+        mosaic.new_arrow(&c, &d, "void", void());
+
+        let start = || {
+            take_where(
+                predicate("Label", "self", Condition::Equals("a".to_string())),
+                take_objects(tiles()),
+            )
+        };
+        let run = |steps| {
+            mosaic
+                .apply_collage(&reachable_from(steps, start()), None)
+                .sorted()
+                .collect_vec()
+        };
+
+        assert!(run(0).is_empty());
+        assert_eq!(vec![b.clone()], run(1));
+        assert_eq!(vec![b.clone(), c.clone()], run(2));
+        assert_eq!(vec![b.clone(), c.clone(), d.clone()], run(usize::MAX));
+
+        let scoped = mosaic
+            .apply_collage(
+                &reachable_from(usize::MAX, start()),
+                Some(vec![a, b.clone()]),
+            )
+            .collect_vec();
+        assert_eq!(vec![b], scoped);
+    }
+
+    #[test]
+    fn new_syntax_round_trips() {
+        for query in [
+            "intersect(all, objects(all))",
+            "difference(all, arrows(all))",
+            "symmetric_difference(objects(all), targets(arrows(all)))",
+            "reachable(all)",
+            "reachable[3](include[Node](all))",
+            "where[Label.self = \"a b\"](all)",
+            "where[Weight.self in 1.5..](all)",
+            "where[Weight.self in -2..10](all)",
+            "where[Label.self contains \"x\"](all)",
+            "where[Label.self matches \"^a.*\"](all)",
+        ] {
+            let parsed = parse_collage(query).unwrap();
+            assert_eq!(query, parsed.to_string());
+        }
+
+        assert_eq!(
+            parse_collage("where[Weight in ..4](all)").unwrap(),
+            take_where(
+                predicate(
+                    "Weight",
+                    "self",
+                    Condition::Range {
+                        min: None,
+                        max: Some(4.0)
+                    }
+                ),
+                tiles()
+            )
+        );
+
+        let error = parse_collage("where[Label matches \"(\"](all)").unwrap_err();
+        assert_eq!(20, error.position);
+
+        let error = parse_collage("difference(all)").unwrap_err();
+        assert_eq!(0, error.position);
+    }
+
+    #[test]
+    fn quotes_and_backslashes_round_trip() {
+        let query = take_where(
+            predicate(
+                "Say \"hi\"",
+                "self",
+                Condition::Equals("a \"b\" \\ c".to_string()),
+            ),
+            tiles(),
+        );
+        let printed = query.to_string();
+        assert_eq!(r#"where["Say \"hi\"".self = "a \"b\" \\ c"](all)"#, printed);
+        assert_eq!(query, parse_collage(&printed).unwrap());
+
+        assert_eq!(
+            parse_collage(r#"where[Label matches "\d+"](all)"#).unwrap(),
+            take_where(
+                predicate("Label", "self", Condition::Matches(r"\d+".to_string())),
+                tiles()
+            )
+        );
+    }
+}
+
+#[cfg(test)]
+mod query_tiles_tests {
+    use crate::querying::{
+        collage::MosaicCollage, collage_parser::parse_collage, query_tiles::QueryStorage,
+    };
+
+    use itertools::Itertools;
+    use mosaic::internals::{void, Mosaic, MosaicIO, MosaicTypelevelCRUD};
+
+    fn query_mosaic() -> std::sync::Arc<Mosaic> {
+        let mosaic = Mosaic::new();
+        for definition in [
+            "Query: str;",
+            "QueryRoot: unit;",
+            "QueryNode: { kind: s32, steps: u64 };",
+            "QueryOperand: u64;",
+            "QueryComponent: { index: u64, name: str };",
+            "QueryPredicate: { component: str, field: str, condition: str, value: str };",
+            "QueryHighlight: unit;",
+        ] {
+            mosaic.new_type(definition).unwrap();
+        }
+        mosaic
+    }
+
+    #[test]
+    fn stored_queries_round_trip() {
+        let mosaic = query_mosaic();
+        let queries = [
+            "all",
+            "targets(arrows(include[Label, \"Two words\"](all)))",
+            "gather(objects(all), descriptors_from(all), extensions(all))",
+            "difference(exclude[Node](all), symmetric_difference(sources(all), arrows_from(all)))",
+            "intersect(reachable(all), reachable[2](descriptors(all)))",
+            "where[Weight.self in -1.5..](where[Label.self matches \"^a|b$\"](all))",
+            "where[Label.self = \"x\"](where[Label.self contains \"y\"](where[W.self in ..3](all)))",
+        ];
+
+        for text in queries {
+            let collage = parse_collage(text).unwrap();
+            let query = mosaic.make_query(text, &collage);
+            assert_eq!(Some(collage), query.get_collage());
+            assert_eq!(text, query.get_name());
+        }
+
+        assert_eq!(queries.len(), mosaic.get_queries().len());
+    }
+
+    #[test]
+    fn stored_query_highlight_toggle() {
+        let mosaic = query_mosaic();
+        let query = mosaic.make_query("q", &parse_collage("objects(all)").unwrap());
+        assert!(!query.is_highlighted());
+        query.set_highlighted(true);
+        query.set_highlighted(true);
+        assert!(query.is_highlighted());
+        query.set_highlighted(false);
+        assert!(!query.is_highlighted());
+        assert_eq!(2, query.get_nodes().len());
+    }
+
+    #[test]
+    fn stored_queries_stay_out_of_results() {
+        let mosaic = query_mosaic();
+        let node = mosaic.new_object("void", void());
+        let collage = parse_collage("objects(all)").unwrap();
+        mosaic.make_query("q", &collage);
+
+        let tiles = mosaic.apply_collage(&collage, None).collect_vec();
+        assert_eq!(vec![node], tiles);
+    }
+}
@@ -1,27 +1,47 @@
+pub mod coloring;
+pub mod compare;
+pub mod control_flow;
+pub mod extract;
 #[allow(dead_code)]
 pub mod finite_state;
 #[allow(dead_code)]
 pub mod generate_enum;
+pub mod load;
 #[allow(dead_code)]
 pub mod pattern_match;
 #[allow(dead_code)]
 pub mod procedures;
 #[allow(dead_code)]
 pub mod select;
+pub mod transitive;
+pub mod weighted;
 
 use std::sync::Arc;
 
+pub use coloring::*;
+pub use compare::*;
+pub use control_flow::*;
 pub use generate_enum::*;
 
+pub use extract::*;
 pub use finite_state::*;
+use itertools::Itertools;
+pub use load::*;
 use mosaic::{
-    internals::{Mosaic, MosaicIO},
+    internals::{Mosaic, MosaicIO, Tile},
     iterators::component_selectors::ComponentSelectors,
 };
 pub use pattern_match::*;
 pub use procedures::*;
 
 pub use select::*;
+pub use transitive::*;
+pub use weighted::*;
+
+use crate::{
+    editor_state::{selection::SelectionTile, windows::GraspEditorWindow},
+    querying::traversal::Traversal,
+};
 
 pub trait TransformerUtilities {
     fn is_transformer_pending(&self) -> bool;
@@ -35,3 +55,46 @@ impl TransformerUtilities for Arc<Mosaic> {
             > 0
     }
 }
+
+pub fn transformer_scope(window: &GraspEditorWindow, initial_state: &[Tile]) -> Vec<Tile> {
+    let selected = initial_state
+        .iter()
+        .filter(|t| t.is_object())
+        .cloned()
+        .unique()
+        .collect_vec();
+
+    if selected.len() > 1 {
+        selected
+    } else {
+        window
+            .document_mosaic
+            .get_all()
+            .include_component("Node")
+            .collect_vec()
+    }
+}
+
+// the arrows the dominator transformers write back are results, not edges of the analysed graph
+pub fn derived_arrow_components() -> Vec<String> {
+    vec!["IDom".to_string(), "IPostDom".to_string()]
+}
+
+pub fn graph_traversal<'a>(scope: Vec<Tile>, derived: &'a [String]) -> Traversal<'a> {
+    Traversal::LimitedExcluding {
+        tiles: scope,
+        components: derived,
+    }
+}
+
+pub fn picked_node(mosaic: &Arc<Mosaic>, pick: &str) -> Option<Tile> {
+    mosaic
+        .get_all()
+        .include_component(pick)
+        .next()
+        .and_then(|p| {
+            SelectionTile::from_tile(p.target())
+                .iter()
+                .find(|t| t.is_object())
+        })
+}
@@ -0,0 +1,300 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use itertools::Itertools;
+use mosaic::{
+    capabilities::ArchetypeSubject,
+    internals::{pars, ComponentValuesBuilderSetter, EntityId, MosaicIO, Tile, TileFieldSetter},
+};
+
+use crate::{
+    core::structures::errors::ErrorCapability,
+    editor_state::{foundation::TransformerState, windows::GraspEditorWindow},
+    querying::traversal::{TraversalOperator, Traverse},
+    GuiState,
+};
+
+use super::{derived_arrow_components, graph_traversal, make_colored_selection, transformer_scope};
+
+pub const PALETTE: [[f32; 4]; 10] = [
+    [0.90, 0.30, 0.24, 1.0],
+    [0.20, 0.60, 0.86, 1.0],
+    [0.18, 0.80, 0.44, 1.0],
+    [0.95, 0.77, 0.06, 1.0],
+    [0.61, 0.35, 0.71, 1.0],
+    [0.90, 0.49, 0.13, 1.0],
+    [0.10, 0.74, 0.61, 1.0],
+    [0.91, 0.26, 0.58, 1.0],
+    [0.58, 0.65, 0.65, 1.0],
+    [0.55, 0.43, 0.29, 1.0],
+];
+
+const CYCLE_COLOR: [f32; 4] = [1.0, 0.2, 0.2, 0.5];
+
+pub fn palette_color(index: usize) -> [f32; 4] {
+    PALETTE[index % PALETTE.len()]
+}
+
+fn neighborhoods(op: &TraversalOperator, nodes: &[Tile]) -> HashMap<EntityId, Vec<Tile>> {
+    nodes
+        .iter()
+        .map(|n| {
+            let neighbors = op
+                .get_neighbors(n)
+                .filter(|m| m.id != n.id && nodes.contains(m))
+                .unique()
+                .collect_vec();
+            (n.id, neighbors)
+        })
+        .collect()
+}
+
+pub fn dsatur_coloring(op: &TraversalOperator) -> HashMap<EntityId, usize> {
+    let nodes = op.get_objects().sorted_by_key(|t| t.id).collect_vec();
+    let neighbors = neighborhoods(op, &nodes);
+    let mut coloring: HashMap<EntityId, usize> = HashMap::new();
+
+    while coloring.len() < nodes.len() {
+        let next = nodes
+            .iter()
+            .filter(|n| !coloring.contains_key(&n.id))
+            .max_by_key(|n| {
+                let adjacent = &neighbors[&n.id];
+                let saturation = adjacent
+                    .iter()
+                    .filter_map(|m| coloring.get(&m.id))
+                    .unique()
+                    .count();
+                // ties go to the highest degree, then to the lowest id
+                (saturation, adjacent.len(), std::cmp::Reverse(n.id))
+            })
+            .unwrap();
+
+        let used = neighbors[&next.id]
+            .iter()
+            .filter_map(|m| coloring.get(&m.id))
+            .collect::<HashSet<_>>();
+
+        let color = (0..).find(|c| !used.contains(c)).unwrap();
+        coloring.insert(next.id, color);
+    }
+
+    coloring
+}
+
+fn path_to_root(parents: &HashMap<EntityId, Option<Tile>>, from: &Tile) -> Vec<Tile> {
+    let mut path = vec![from.clone()];
+    while let Some(Some(parent)) = parents.get(&path.last().unwrap().id) {
+        path.push(parent.clone());
+    }
+    path
+}
+
+pub fn bipartition(op: &TraversalOperator) -> Result<HashMap<EntityId, usize>, Vec<Tile>> {
+    let nodes = op.get_objects().sorted_by_key(|t| t.id).collect_vec();
+    let neighbors = neighborhoods(op, &nodes);
+    let mut sides: HashMap<EntityId, usize> = HashMap::new();
+    let mut parents: HashMap<EntityId, Option<Tile>> = HashMap::new();
+
+    for root in &nodes {
+        if sides.contains_key(&root.id) {
+            continue;
+        }
+
+        sides.insert(root.id, 0);
+        parents.insert(root.id, None);
+        let mut queue = VecDeque::from([root.clone()]);
+
+        while let Some(current) = queue.pop_front() {
+            let side = sides[&current.id];
+
+            for next in &neighbors[&current.id] {
+                match sides.get(&next.id) {
+                    None => {
+                        sides.insert(next.id, 1 - side);
+                        parents.insert(next.id, Some(current.clone()));
+                        queue.push_back(next.clone());
+                    }
+                    Some(&other) if other == side => {
+                        let mut left = path_to_root(&parents, &current);
+                        let right = path_to_root(&parents, next);
+                        let meet = left.iter().position(|t| right.contains(t)).unwrap();
+                        left.truncate(meet + 1);
+                        let back = right.iter().position(|t| *t == left[meet]).unwrap();
+                        left.extend(right[..back].iter().rev().cloned());
+                        return Err(left);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    Ok(sides)
+}
+
+pub fn set_color(tile: &Tile, color: [f32; 4]) {
+    if let Some(mut component) = tile.get_component("Color") {
+        component.set("r", color[0]);
+        component.set("g", color[1]);
+        component.set("b", color[2]);
+        component.set("a", color[3]);
+    } else {
+        tile.add_component(
+            "Color",
+            pars()
+                .set("r", color[0])
+                .set("g", color[1])
+                .set("b", color[2])
+                .set("a", color[3])
+                .ok(),
+        );
+    }
+}
+
+fn apply_coloring(window: &mut GraspEditorWindow, coloring: &HashMap<EntityId, usize>) {
+    for (id, color) in coloring {
+        if let Some(tile) = window.document_mosaic.get(*id) {
+            set_color(&tile, palette_color(*color));
+        }
+    }
+
+    window.mark_changed();
+}
+
+pub fn color_graph(
+    window: &mut GraspEditorWindow,
+    _ui: &GuiState,
+    initial_state: &[Tile],
+    _tile: &Tile,
+) -> TransformerState {
+    let scope = transformer_scope(window, initial_state);
+    let derived = derived_arrow_components();
+    let op = window
+        .document_mosaic
+        .traverse(graph_traversal(scope, &derived));
+    let coloring = dsatur_coloring(&op);
+    apply_coloring(window, &coloring);
+
+    TransformerState::Done
+}
+
+pub fn bipartition_graph(
+    window: &mut GraspEditorWindow,
+    _ui: &GuiState,
+    initial_state: &[Tile],
+    _tile: &Tile,
+) -> TransformerState {
+    let scope = transformer_scope(window, initial_state);
+    let derived = derived_arrow_components();
+    let op = window
+        .document_mosaic
+        .traverse(graph_traversal(scope, &derived));
+
+    match bipartition(&op) {
+        Ok(sides) => apply_coloring(window, &sides),
+        Err(cycle) => {
+            let mut highlighted = cycle.clone();
+            for (a, b) in cycle.iter().circular_tuple_windows() {
+                if let Some(arrow) = op
+                    .get_arrows_from(a)
+                    .find(|x| x.target_id() == b.id)
+                    .or_else(|| op.get_arrows_from(b).find(|x| x.target_id() == a.id))
+                {
+                    highlighted.push(arrow);
+                }
+            }
+
+            let selection = make_colored_selection(&highlighted, CYCLE_COLOR);
+            window.editor_data.selected = highlighted;
+            window.editor_mosaic.make_warning(
+                "[Graph] Bipartition",
+                &format!(
+                    "Graph is not bipartite: found an odd cycle of length {}",
+                    cycle.len()
+                ),
+                Some(window.window_tile.clone()),
+                selection,
+            );
+        }
+    }
+
+    TransformerState::Done
+}
+
+#[cfg(test)]
+mod coloring_tests {
+    use itertools::Itertools;
+    use mosaic::internals::{void, Mosaic, MosaicCRUD, MosaicIO, MosaicTypelevelCRUD};
+
+    use crate::querying::traversal::Traverse;
+
+    use super::{bipartition, dsatur_coloring};
+
+    #[test]
+    fn test_dsatur_colors_triangle_with_three_colors() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Arrow: unit;").unwrap();
+        let a = mosaic.new_object("void", void());
+        let b = mosaic.new_object("void", void());
+        let c = mosaic.new_object("void", void());
+        mosaic.new_arrow(&a, &b, "Arrow", void());
+        mosaic.new_arrow(&b, &c, "Arrow", void());
+        mosaic.new_arrow(&c, &a, "Arrow", void());
+
+        let op = mosaic.traverse(vec![a, b, c].into());
+        let coloring = dsatur_coloring(&op);
+        assert_eq!(3, coloring.values().unique().count());
+    }
+
+    #[test]
+    fn test_dsatur_colors_even_cycle_with_two_colors() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Arrow: unit;").unwrap();
+        let nodes = (0..4)
+            .map(|_| mosaic.new_object("void", void()))
+            .collect_vec();
+        for (a, b) in nodes.iter().circular_tuple_windows() {
+            mosaic.new_arrow(a, b, "Arrow", void());
+        }
+
+        let op = mosaic.traverse(nodes.into());
+        let coloring = dsatur_coloring(&op);
+        assert_eq!(2, coloring.values().unique().count());
+    }
+
+    #[test]
+    fn test_bipartition_reports_odd_cycle() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Arrow: unit;").unwrap();
+        let nodes = (0..5)
+            .map(|_| mosaic.new_object("void", void()))
+            .collect_vec();
+        for (a, b) in nodes.iter().circular_tuple_windows() {
+            mosaic.new_arrow(a, b, "Arrow", void());
+        }
+        let tail = mosaic.new_object("void", void());
+        mosaic.new_arrow(&nodes[0], &tail, "Arrow", void());
+
+        let mut scope = nodes.clone();
+        scope.push(tail);
+        let op = mosaic.traverse(scope.into());
+        let cycle = bipartition(&op).unwrap_err();
+        assert_eq!(5, cycle.len());
+        assert!(cycle.iter().all(|t| nodes.contains(t)));
+    }
+
+    #[test]
+    fn test_bipartition_splits_tree() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Arrow: unit;").unwrap();
+        let a = mosaic.new_object("void", void());
+        let b = mosaic.new_object("void", void());
+        let c = mosaic.new_object("void", void());
+        mosaic.new_arrow(&a, &b, "Arrow", void());
+        mosaic.new_arrow(&a, &c, "Arrow", void());
+
+        let op = mosaic.traverse(vec![a.clone(), b.clone(), c].into());
+        let sides = bipartition(&op).unwrap();
+        assert_ne!(sides[&a.id], sides[&b.id]);
+    }
+}
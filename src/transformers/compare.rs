@@ -0,0 +1,71 @@
+use std::env;
+
+use mosaic::internals::Tile;
+
+use crate::{
+    core::structures::{
+        diff::{diff_documents, document_tiles},
+        errors::{ErrorCapability, Severity},
+        subgraph::load_document,
+    },
+    editor_state::{diff::DiffOverlay, foundation::TransformerState, windows::GraspEditorWindow},
+    GuiState,
+};
+
+// cancelling the file dialog takes down the overlay of an earlier comparison
+fn compare_with(window: &mut GraspEditorWindow, by_label: bool, source: &str) -> TransformerState {
+    let Some(file) = rfd::FileDialog::new()
+        .add_filter("Mosaic", &["mos"])
+        .set_directory(env::current_dir().unwrap())
+        .pick_file()
+    else {
+        window.diff_overlay = None;
+        return TransformerState::Cancelled;
+    };
+
+    let before = match load_document(&file) {
+        Ok(mosaic) => document_tiles(&mosaic),
+        Err(error) => {
+            window
+                .editor_mosaic
+                .make_error(source, &error, Some(window.window_tile.clone()), None);
+            return TransformerState::Done;
+        }
+    };
+
+    let name = file
+        .file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or_default()
+        .to_string();
+    let diff = diff_documents(before, document_tiles(&window.document_mosaic), by_label);
+
+    window.editor_mosaic.make_diagnostic(
+        Severity::Info,
+        source,
+        &format!("Compared with {}: {}", name, diff.summary()),
+        Some(window.window_tile.clone()),
+        None,
+    );
+
+    window.diff_overlay = Some(DiffOverlay::new(name, diff));
+    TransformerState::Done
+}
+
+pub fn compare_transformer(
+    window: &mut GraspEditorWindow,
+    _ui: &GuiState,
+    _initial_state: &[Tile],
+    _tile: &Tile,
+) -> TransformerState {
+    compare_with(window, false, "[Document] Compare With...")
+}
+
+pub fn compare_by_label_transformer(
+    window: &mut GraspEditorWindow,
+    _ui: &GuiState,
+    _initial_state: &[Tile],
+    _tile: &Tile,
+) -> TransformerState {
+    compare_with(window, true, "[Document] Compare By Label...")
+}
@@ -0,0 +1,379 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use itertools::Itertools;
+use mosaic::{
+    capabilities::ArchetypeSubject,
+    internals::{void, EntityId, MosaicCRUD, MosaicIO, Tile},
+    iterators::{component_selectors::ComponentSelectors, tile_deletion::TileDeletion},
+};
+
+use crate::{
+    core::structures::errors::ErrorCapability,
+    editor_state::{foundation::TransformerState, windows::GraspEditorWindow},
+    querying::traversal::{Traversal, TraversalDirection, TraversalOperator, Traverse},
+    GuiState,
+};
+
+use super::{make_colored_selection, palette_color, picked_node};
+
+fn successors(op: &TraversalOperator, tile: &Tile, direction: &TraversalDirection) -> Vec<Tile> {
+    match direction {
+        TraversalDirection::Backward => op.get_backward_neighbors(tile),
+        _ => op.get_forward_neighbors(tile),
+    }
+    .unique()
+    .collect_vec()
+}
+
+fn predecessors(op: &TraversalOperator, tile: &Tile, direction: &TraversalDirection) -> Vec<Tile> {
+    match direction {
+        TraversalDirection::Backward => op.get_forward_neighbors(tile),
+        _ => op.get_backward_neighbors(tile),
+    }
+    .unique()
+    .collect_vec()
+}
+
+fn postorder(op: &TraversalOperator, entry: &Tile, direction: &TraversalDirection) -> Vec<Tile> {
+    let mut order = vec![];
+    let mut visited = HashSet::from([entry.id]);
+    let mut stack = vec![(entry.clone(), successors(op, entry, direction).into_iter())];
+
+    while let Some((_, children)) = stack.last_mut() {
+        match children.next() {
+            Some(child) => {
+                if visited.insert(child.id) {
+                    let grandchildren = successors(op, &child, direction).into_iter();
+                    stack.push((child, grandchildren));
+                }
+            }
+            None => {
+                let (tile, _) = stack.pop().unwrap();
+                order.push(tile);
+            }
+        }
+    }
+
+    order
+}
+
+// Cooper, Harvey and Kennedy, "A Simple, Fast Dominance Algorithm"
+pub fn immediate_dominators(
+    op: &TraversalOperator,
+    entry: &Tile,
+    direction: TraversalDirection,
+) -> HashMap<EntityId, Tile> {
+    let order = postorder(op, entry, &direction);
+    let number: HashMap<EntityId, usize> =
+        order.iter().enumerate().map(|(i, t)| (t.id, i)).collect();
+    let tiles: HashMap<EntityId, Tile> = order.iter().map(|t| (t.id, t.clone())).collect();
+    let mut idom: HashMap<EntityId, EntityId> = HashMap::from([(entry.id, entry.id)]);
+
+    let intersect = |idom: &HashMap<EntityId, EntityId>, mut a: EntityId, mut b: EntityId| {
+        while a != b {
+            while number[&a] < number[&b] {
+                a = idom[&a];
+            }
+            while number[&b] < number[&a] {
+                b = idom[&b];
+            }
+        }
+        a
+    };
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for tile in order.iter().rev().filter(|t| t.id != entry.id) {
+            let processed = predecessors(op, tile, &direction)
+                .into_iter()
+                .filter(|p| idom.contains_key(&p.id))
+                .map(|p| p.id)
+                .collect_vec();
+
+            if let Some((&first, rest)) = processed.split_first() {
+                let new_idom = rest
+                    .iter()
+                    .fold(first, |current, &p| intersect(&idom, p, current));
+
+                if idom.get(&tile.id) != Some(&new_idom) {
+                    idom.insert(tile.id, new_idom);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    idom.into_iter()
+        .filter(|(node, _)| *node != entry.id)
+        .map(|(node, dominator)| (node, tiles[&dominator].clone()))
+        .collect()
+}
+
+fn dominates(idom: &HashMap<EntityId, Tile>, dominator: EntityId, mut node: EntityId) -> bool {
+    loop {
+        if node == dominator {
+            return true;
+        }
+
+        match idom.get(&node) {
+            Some(parent) => node = parent.id,
+            None => return false,
+        }
+    }
+}
+
+pub fn natural_loops(op: &TraversalOperator, entry: &Tile) -> Vec<(Tile, Vec<Tile>)> {
+    let reachable = postorder(op, entry, &TraversalDirection::Forward);
+    let idom = immediate_dominators(op, entry, TraversalDirection::Forward);
+    let mut loops: BTreeMap<EntityId, (Tile, HashSet<Tile>)> = BTreeMap::new();
+
+    for tail in &reachable {
+        for header in successors(op, tail, &TraversalDirection::Forward) {
+            if !dominates(&idom, header.id, tail.id) {
+                continue;
+            }
+
+            let (_, body) = loops
+                .entry(header.id)
+                .or_insert_with(|| (header.clone(), HashSet::from([header.clone()])));
+
+            let mut stack = vec![tail.clone()];
+            while let Some(current) = stack.pop() {
+                if body.insert(current.clone()) {
+                    stack.extend(
+                        predecessors(op, &current, &TraversalDirection::Forward)
+                            .into_iter()
+                            .filter(|p| reachable.contains(p)),
+                    );
+                }
+            }
+        }
+
+        if op.get_self_loops(tail).len() > 0 {
+            loops
+                .entry(tail.id)
+                .or_insert_with(|| (tail.clone(), HashSet::from([tail.clone()])));
+        }
+    }
+
+    loops
+        .into_values()
+        .map(|(header, body)| (header, body.into_iter().sorted().collect_vec()))
+        .collect_vec()
+}
+
+fn cfg_components() -> Vec<String> {
+    vec!["Node".to_string(), "Arrow".to_string()]
+}
+
+fn report(window: &GraspEditorWindow, source: &str, message: &str) {
+    window
+        .editor_mosaic
+        .make_error(source, message, Some(window.window_tile.clone()), None);
+}
+
+fn write_dominator_arrows(
+    window: &mut GraspEditorWindow,
+    component: &str,
+    idom: &HashMap<EntityId, Tile>,
+) {
+    window
+        .document_mosaic
+        .get_all()
+        .include_component(component)
+        .delete();
+
+    for (node, dominator) in idom.iter().sorted_by_key(|(node, _)| **node) {
+        if let Some(node) = window.document_mosaic.get(*node) {
+            window
+                .document_mosaic
+                .new_arrow(dominator, &node, component, void());
+        }
+    }
+
+    window.dominators_visible = true;
+    window.mark_changed();
+}
+
+pub fn dominators_transformer(
+    window: &mut GraspEditorWindow,
+    _ui: &GuiState,
+    _initial_state: &[Tile],
+    _tile: &Tile,
+) -> TransformerState {
+    let entry = match picked_node(&window.document_mosaic, "Pick1") {
+        Some(entry) => entry,
+        None => {
+            report(
+                window,
+                "[CFG] Dominators",
+                "Dominators need an entry node picked with Ctrl+1",
+            );
+            return TransformerState::Done;
+        }
+    };
+
+    let components = cfg_components();
+    let op = window.document_mosaic.traverse(Traversal::Include {
+        components: &components,
+    });
+
+    let idom = immediate_dominators(&op, &entry, TraversalDirection::Forward);
+    write_dominator_arrows(window, "IDom", &idom);
+
+    TransformerState::Done
+}
+
+pub fn post_dominators_transformer(
+    window: &mut GraspEditorWindow,
+    _ui: &GuiState,
+    _initial_state: &[Tile],
+    _tile: &Tile,
+) -> TransformerState {
+    let components = cfg_components();
+    let op = window.document_mosaic.traverse(Traversal::Include {
+        components: &components,
+    });
+
+    let exit = picked_node(&window.document_mosaic, "Pick2").or_else(|| {
+        let exits = op
+            .get_objects()
+            .filter(|t| op.get_forward_neighbors(t).len() == 0)
+            .collect_vec();
+
+        if exits.len() == 1 {
+            exits.first().cloned()
+        } else {
+            None
+        }
+    });
+
+    let exit = match exit {
+        Some(exit) => exit,
+        None => {
+            report(
+                window,
+                "[CFG] Post-dominators",
+                "Post-dominators need a single exit node, pick one with Ctrl+2",
+            );
+            return TransformerState::Done;
+        }
+    };
+
+    let ipdom = immediate_dominators(&op, &exit, TraversalDirection::Backward);
+    write_dominator_arrows(window, "IPostDom", &ipdom);
+
+    TransformerState::Done
+}
+
+pub fn natural_loops_transformer(
+    window: &mut GraspEditorWindow,
+    _ui: &GuiState,
+    _initial_state: &[Tile],
+    _tile: &Tile,
+) -> TransformerState {
+    let entry = match picked_node(&window.document_mosaic, "Pick1") {
+        Some(entry) => entry,
+        None => {
+            report(
+                window,
+                "[CFG] Natural Loops",
+                "Natural loops need an entry node picked with Ctrl+1",
+            );
+            return TransformerState::Done;
+        }
+    };
+
+    let components = cfg_components();
+    let op = window.document_mosaic.traverse(Traversal::Include {
+        components: &components,
+    });
+
+    window
+        .document_mosaic
+        .get_all()
+        .include_component("LoopHeader")
+        .delete();
+
+    // a tile belongs to only one selection, so outer loops go first and inner loops claim their nodes
+    let loops = natural_loops(&op, &entry)
+        .into_iter()
+        .sorted_by_key(|(_, body)| std::cmp::Reverse(body.len()))
+        .collect_vec();
+
+    for (i, (header, body)) in loops.iter().enumerate() {
+        header.add_component("LoopHeader", void());
+
+        let mut color = palette_color(i);
+        color[3] = 0.5;
+        make_colored_selection(body, color);
+    }
+
+    window.mark_changed();
+    TransformerState::Done
+}
+
+#[cfg(test)]
+mod control_flow_tests {
+    use mosaic::internals::{void, Mosaic, MosaicCRUD, MosaicIO, MosaicTypelevelCRUD};
+
+    use crate::querying::traversal::{TraversalDirection, Traverse};
+
+    use super::{immediate_dominators, natural_loops};
+
+    #[test]
+    fn test_dominators_of_diamond_with_loop() {
+        // a -> b -> c -> e, a -> d -> e, e -> b
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Arrow: unit;").unwrap();
+        let a = mosaic.new_object("void", void());
+        let b = mosaic.new_object("void", void());
+        let c = mosaic.new_object("void", void());
+        let d = mosaic.new_object("void", void());
+        let e = mosaic.new_object("void", void());
+        mosaic.new_arrow(&a, &b, "Arrow", void());
+        mosaic.new_arrow(&b, &c, "Arrow", void());
+        mosaic.new_arrow(&c, &e, "Arrow", void());
+        mosaic.new_arrow(&a, &d, "Arrow", void());
+        mosaic.new_arrow(&d, &e, "Arrow", void());
+        mosaic.new_arrow(&e, &b, "Arrow", void());
+
+        let op =
+            mosaic.traverse(vec![a.clone(), b.clone(), c.clone(), d.clone(), e.clone()].into());
+        let idom = immediate_dominators(&op, &a, TraversalDirection::Forward);
+
+        assert_eq!(4, idom.len());
+        assert_eq!(a, idom[&b.id]);
+        assert_eq!(b, idom[&c.id]);
+        assert_eq!(a, idom[&d.id]);
+        assert_eq!(a, idom[&e.id]);
+
+        let ipdom = immediate_dominators(&op, &e, TraversalDirection::Backward);
+        assert_eq!(e, ipdom[&c.id]);
+        assert_eq!(e, ipdom[&a.id]);
+        assert_eq!(c, ipdom[&b.id]);
+    }
+
+    #[test]
+    fn test_natural_loop_body() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Arrow: unit;").unwrap();
+        let entry = mosaic.new_object("void", void());
+        let header = mosaic.new_object("void", void());
+        let body = mosaic.new_object("void", void());
+        let exit = mosaic.new_object("void", void());
+        mosaic.new_arrow(&entry, &header, "Arrow", void());
+        mosaic.new_arrow(&header, &body, "Arrow", void());
+        mosaic.new_arrow(&body, &header, "Arrow", void());
+        mosaic.new_arrow(&header, &exit, "Arrow", void());
+
+        let op = mosaic.traverse(vec![entry.clone(), header.clone(), body.clone(), exit].into());
+        let loops = natural_loops(&op, &entry);
+
+        assert_eq!(1, loops.len());
+        assert_eq!(header, loops[0].0);
+        assert_eq!(vec![header, body], loops[0].1);
+    }
+}
@@ -0,0 +1,28 @@
+use mosaic::internals::{par, MosaicCRUD, Tile};
+
+use crate::{
+    core::structures::grasp_queues,
+    editor_state::{foundation::TransformerState, windows::GraspEditorWindow},
+    grasp_queues::NewWindowRequestQueue,
+    GuiState,
+};
+
+// opens the selected objects, the arrows among them and their components in a new window
+pub fn extract_selection_transformer(
+    window: &mut GraspEditorWindow,
+    _ui: &GuiState,
+    initial_state: &[Tile],
+    _tile: &Tile,
+) -> TransformerState {
+    if let Some(subgraph) = window.extract_selection(initial_state, "[Document] Extract Selection")
+    {
+        grasp_queues::enqueue(
+            NewWindowRequestQueue,
+            window
+                .editor_mosaic
+                .new_object("NewWindowRequest", par(subgraph.to_text())),
+        );
+    }
+
+    TransformerState::Done
+}
@@ -54,6 +54,7 @@ pub fn finite_state_transformer(
 
         if nodes.len() < objects_len {
             window.mosaic.make_error(
+                "[FSM] Compile",
                 "Not all nodes have a distinct name in this FSM.",
                 Some(window.clone()),
                 Some(initial_state.clone()),
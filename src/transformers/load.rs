@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use itertools::Itertools;
+use mosaic::{
+    capabilities::ArchetypeSubject,
+    internals::{MosaicIO, Tile},
+    iterators::component_selectors::ComponentSelectors,
+};
+
+use crate::{
+    core::{
+        math::Vec2,
+        structures::{errors::ErrorCapability, owners::TileOwner},
+    },
+    editor_state::{foundation::TransformerState, windows::GraspEditorWindow},
+    GuiState,
+};
+
+const LOAD_SOURCE: &str = "[Document] Load";
+const LOAD_MARGIN: f32 = 100.0;
+
+// relative paths are looked up next to the document holding the Load component
+fn resolve_load_path(window: &GraspEditorWindow, path: &str) -> PathBuf {
+    let path = PathBuf::from(path);
+    match window.path.as_ref().and_then(|p| p.parent()) {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path,
+    }
+}
+
+// inserts the document named by each Load component under its owner, grouped by the `var` name
+pub fn load_transformer(
+    window: &mut GraspEditorWindow,
+    _ui: &GuiState,
+    initial_state: &[Tile],
+    _tile: &Tile,
+) -> TransformerState {
+    let mut loads = initial_state
+        .iter()
+        .filter_map(|t| t.get_component("Load"))
+        .collect_vec();
+
+    if loads.is_empty() {
+        loads = window
+            .document_mosaic
+            .get_all()
+            .include_component("Load")
+            .collect_vec();
+    }
+
+    if loads.is_empty() {
+        window.editor_mosaic.make_warning(
+            LOAD_SOURCE,
+            "Neither the selection nor the document has a Load component",
+            Some(window.window_tile.clone()),
+            None,
+        );
+        return TransformerState::Done;
+    }
+
+    let mut loaded = vec![];
+    for load in loads {
+        let path = resolve_load_path(window, &load.get("path").as_str());
+        let var = load.get("var").as_s32().to_string();
+        let at = load
+            .mosaic
+            .get(load.owner_id())
+            .and_then(|owner| owner.get_component("Position"))
+            .map(|p| Vec2::new(p.get("x").as_f32(), p.get("y").as_f32() + LOAD_MARGIN));
+
+        let group = Some(var.as_str()).filter(|v| !v.is_empty());
+        loaded.extend(window.insert_file(&path, LOAD_SOURCE, at, group));
+    }
+
+    window.editor_data.selected = loaded;
+    TransformerState::Done
+}
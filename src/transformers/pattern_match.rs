@@ -618,6 +618,7 @@ pub fn pattern_match_tool(
                     Err(e) => {
                         warn!("PATTERN MATCH ERROR: {:?}!", e.to_string());
                         window.editor_mosaic.make_error(
+                            "[Pattern] Match",
                             &e.to_string(),
                             Some(window.window_tile.clone()),
                             Some(p.0),
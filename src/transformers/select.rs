@@ -26,7 +26,12 @@ pub fn deselect(
     initial_state: &[Tile],
     _tile: &Tile,
 ) -> TransformerState {
-    for selected in initial_state {
+    remove_from_selections(initial_state);
+    TransformerState::Done
+}
+
+fn remove_from_selections(tiles: &[Tile]) {
+    for selected in tiles {
         if let Some(previous_selection) = find_selection_owner(selected) {
             previous_selection.remove(selected);
 
@@ -35,8 +40,6 @@ pub fn deselect(
             }
         }
     }
-
-    TransformerState::Done
 }
 
 pub fn on_selected_delete(window: &mut GraspEditorWindow, comp: String, selected: &Tile) {
@@ -51,41 +54,54 @@ pub fn on_selected_delete(window: &mut GraspEditorWindow, comp: String, selected
     }
 }
 
+pub fn make_colored_selection(tiles: &[Tile], color: [f32; 4]) -> Option<Tile> {
+    let node = tiles.first()?;
+    let mosaic = Arc::clone(&node.mosaic);
+    let selection = mosaic.make_selection(tiles);
+
+    selection.add_component(
+        "Color",
+        pars()
+            .set("r", color[0])
+            .set("g", color[1])
+            .set("b", color[2])
+            .set("a", color[3])
+            .ok(),
+    );
+
+    remove_from_selections(tiles);
+
+    for selected in tiles {
+        selected.add_component("Selected", par(selection.id as u64));
+    }
+
+    Some(selection)
+}
+
 pub fn select(
-    window: &mut GraspEditorWindow,
-    ui: &GuiState,
+    _window: &mut GraspEditorWindow,
+    _ui: &GuiState,
     initial_state: &[Tile],
-    tile: &Tile,
+    _tile: &Tile,
 ) -> TransformerState {
-    if let Some(node) = initial_state.first() {
-        let mosaic = Arc::clone(&node.mosaic);
-        let selection = mosaic.make_selection(initial_state);
-
-        use random_color::color_dictionary::ColorDictionary;
-        use random_color::{Luminosity, RandomColor};
-
-        let color = RandomColor::new()
-            .luminosity(Luminosity::Light)
-            .alpha(0.5)
-            .dictionary(ColorDictionary::new())
-            .to_rgb_array();
-
-        selection.add_component(
-            "Color",
-            pars()
-                .set("r", color[0] as f32 / 255.0f32)
-                .set("g", color[1] as f32 / 255.0f32)
-                .set("b", color[2] as f32 / 255.0f32)
-                .set("a", 0.5f32)
-                .ok(),
-        );
-
-        deselect(window, ui, initial_state, tile);
-
-        for selected in initial_state {
-            selected.add_component("Selected", par(selection.id as u64));
-        }
-    }
+    use random_color::color_dictionary::ColorDictionary;
+    use random_color::{Luminosity, RandomColor};
+
+    let color = RandomColor::new()
+        .luminosity(Luminosity::Light)
+        .alpha(0.5)
+        .dictionary(ColorDictionary::new())
+        .to_rgb_array();
+
+    make_colored_selection(
+        initial_state,
+        [
+            color[0] as f32 / 255.0f32,
+            color[1] as f32 / 255.0f32,
+            color[2] as f32 / 255.0f32,
+            0.5f32,
+        ],
+    );
 
     TransformerState::Done
 }
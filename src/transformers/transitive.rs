@@ -0,0 +1,276 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use itertools::Itertools;
+use mosaic::{
+    capabilities::ArchetypeSubject,
+    internals::{void, EntityId, MosaicIO, Tile},
+    iterators::{component_selectors::ComponentSelectors, tile_getters::TileGetters},
+};
+
+use crate::{
+    editor_state::{foundation::TransformerState, windows::GraspEditorWindow},
+    grasp_transitions::query_position_recursive,
+    querying::traversal::{TraversalOperator, Traverse},
+    GuiState,
+};
+
+use super::{derived_arrow_components, graph_traversal, transformer_scope};
+
+fn is_reachable_without(
+    adjacency: &HashMap<EntityId, Vec<Tile>>,
+    removed: &HashSet<EntityId>,
+    skipped: EntityId,
+    from: EntityId,
+    to: EntityId,
+) -> bool {
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::from([from]);
+
+    while let Some(current) = queue.pop_front() {
+        if !visited.insert(current) {
+            continue;
+        }
+
+        for arrow in adjacency.get(&current).into_iter().flatten() {
+            if arrow.id == skipped || removed.contains(&arrow.id) {
+                continue;
+            }
+
+            if arrow.target_id() == to {
+                return true;
+            }
+
+            queue.push_back(arrow.target_id());
+        }
+    }
+
+    false
+}
+
+pub fn find_redundant_arrows(op: &TraversalOperator) -> Vec<Tile> {
+    let mut adjacency = HashMap::new();
+    let mut arrows = vec![];
+
+    for node in op.get_objects() {
+        let outgoing = op
+            .get_arrows_from(&node)
+            .filter(|a| a.component.is("Arrow") && !a.is_loop())
+            .collect_vec();
+
+        arrows.extend(outgoing.clone());
+        adjacency.insert(node.id, outgoing);
+    }
+
+    // implied arrows go first so that the arrows drawn by hand survive the reduction
+    let arrows = arrows
+        .into_iter()
+        .sorted_by_key(|a| (a.get_component("Implied").is_none(), a.id))
+        .collect_vec();
+
+    let mut removed = HashSet::new();
+    for arrow in &arrows {
+        if is_reachable_without(
+            &adjacency,
+            &removed,
+            arrow.id,
+            arrow.source_id(),
+            arrow.target_id(),
+        ) {
+            removed.insert(arrow.id);
+        }
+    }
+
+    arrows
+        .into_iter()
+        .filter(|a| removed.contains(&a.id))
+        .collect_vec()
+}
+
+pub fn find_implied_pairs(op: &TraversalOperator) -> Vec<(Tile, Tile)> {
+    let matrix = op.as_matrix();
+    let nodes = op.get_objects().sorted_by_key(|t| t.id).collect_vec();
+    let mut pairs = vec![];
+
+    for source in &nodes {
+        for target in &nodes {
+            if source == target {
+                continue;
+            }
+
+            if matrix.are_reachable(source.id, target.id)
+                && !matrix.are_adjacent(source.id, target.id)
+            {
+                pairs.push((source.clone(), target.clone()));
+            }
+        }
+    }
+
+    pairs
+}
+
+pub fn transitive_reduction(
+    window: &mut GraspEditorWindow,
+    _ui: &GuiState,
+    initial_state: &[Tile],
+    _tile: &Tile,
+) -> TransformerState {
+    let scope = transformer_scope(window, initial_state);
+    let derived = derived_arrow_components();
+    let op = window
+        .document_mosaic
+        .traverse(graph_traversal(scope, &derived));
+    let redundant = find_redundant_arrows(&op);
+
+    if !redundant.is_empty() {
+        window
+            .editor_data
+            .selected
+            .retain(|t| !redundant.contains(t));
+        window.delete_tiles(&redundant);
+        window.mark_changed();
+        window.request_quadtree_update();
+    }
+
+    TransformerState::Done
+}
+
+pub fn transitive_closure(
+    window: &mut GraspEditorWindow,
+    _ui: &GuiState,
+    initial_state: &[Tile],
+    _tile: &Tile,
+) -> TransformerState {
+    let scope = transformer_scope(window, initial_state);
+    let derived = derived_arrow_components();
+    let op = window
+        .document_mosaic
+        .traverse(graph_traversal(scope, &derived));
+    let previous_selection = window.editor_data.selected.clone();
+
+    for (source, target) in find_implied_pairs(&op) {
+        let mid_pos =
+            query_position_recursive(&source).lerp(query_position_recursive(&target), 0.5);
+        let arrow = window.create_new_arrow(&source, &target, mid_pos);
+        arrow.add_component("Implied", void());
+        window.mark_changed();
+    }
+
+    window.editor_data.selected = previous_selection;
+    window.request_quadtree_update();
+
+    TransformerState::Done
+}
+
+pub fn remove_implied_arrows(
+    window: &mut GraspEditorWindow,
+    _ui: &GuiState,
+    _initial_state: &[Tile],
+    _tile: &Tile,
+) -> TransformerState {
+    let implied = window
+        .document_mosaic
+        .get_all()
+        .include_component("Implied")
+        .get_targets()
+        .collect_vec();
+
+    if !implied.is_empty() {
+        window.editor_data.selected.retain(|t| !implied.contains(t));
+        window.delete_tiles(&implied);
+        window.mark_changed();
+        window.request_quadtree_update();
+    }
+
+    TransformerState::Done
+}
+
+#[cfg(test)]
+mod transitive_tests {
+    use itertools::Itertools;
+    use mosaic::internals::{void, Mosaic, MosaicCRUD, MosaicIO, MosaicTypelevelCRUD};
+
+    use crate::{
+        querying::traversal::Traverse,
+        transformers::{derived_arrow_components, graph_traversal},
+    };
+
+    use super::{find_implied_pairs, find_redundant_arrows};
+
+    #[test]
+    fn test_reduction_removes_shortcut() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Arrow: unit;").unwrap();
+        let a = mosaic.new_object("void", void());
+        let b = mosaic.new_object("void", void());
+        let c = mosaic.new_object("void", void());
+        mosaic.new_arrow(&a, &b, "Arrow", void());
+        mosaic.new_arrow(&b, &c, "Arrow", void());
+        let shortcut = mosaic.new_arrow(&a, &c, "Arrow", void());
+
+        let op = mosaic.traverse(vec![a, b, c].into());
+        assert_eq!(vec![shortcut], find_redundant_arrows(&op));
+    }
+
+    #[test]
+    fn test_reduction_keeps_one_of_parallel_arrows() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Arrow: unit;").unwrap();
+        let a = mosaic.new_object("void", void());
+        let b = mosaic.new_object("void", void());
+        mosaic.new_arrow(&a, &b, "Arrow", void());
+        mosaic.new_arrow(&a, &b, "Arrow", void());
+
+        let op = mosaic.traverse(vec![a, b].into());
+        assert_eq!(1, find_redundant_arrows(&op).len());
+    }
+
+    #[test]
+    fn test_reduction_keeps_cycles_reachable() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Arrow: unit;").unwrap();
+        let a = mosaic.new_object("void", void());
+        let b = mosaic.new_object("void", void());
+        let c = mosaic.new_object("void", void());
+        mosaic.new_arrow(&a, &b, "Arrow", void());
+        mosaic.new_arrow(&b, &c, "Arrow", void());
+        mosaic.new_arrow(&c, &a, "Arrow", void());
+
+        let op = mosaic.traverse(vec![a, b, c].into());
+        assert!(find_redundant_arrows(&op).is_empty());
+    }
+
+    #[test]
+    fn test_reduction_ignores_dominator_arrows() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Arrow: unit;").unwrap();
+        mosaic.new_type("IDom: unit;").unwrap();
+        let a = mosaic.new_object("void", void());
+        let b = mosaic.new_object("void", void());
+        let c = mosaic.new_object("void", void());
+        mosaic.new_arrow(&a, &b, "Arrow", void());
+        mosaic.new_arrow(&b, &c, "Arrow", void());
+        mosaic.new_arrow(&a, &c, "IDom", void());
+
+        let derived = derived_arrow_components();
+        let op = mosaic.traverse(graph_traversal(vec![a, b, c], &derived));
+        assert!(find_redundant_arrows(&op).is_empty());
+    }
+
+    #[test]
+    fn test_closure_adds_missing_pairs() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Arrow: unit;").unwrap();
+        let a = mosaic.new_object("void", void());
+        let b = mosaic.new_object("void", void());
+        let c = mosaic.new_object("void", void());
+        mosaic.new_arrow(&a, &b, "Arrow", void());
+        mosaic.new_arrow(&b, &c, "Arrow", void());
+
+        let op = mosaic.traverse(vec![a.clone(), b, c.clone()].into());
+        let pairs = find_implied_pairs(&op)
+            .into_iter()
+            .map(|(s, t)| (s.id, t.id))
+            .collect_vec();
+        assert_eq!(vec![(a.id, c.id)], pairs);
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use itertools::Itertools;
+use mosaic::{
+    capabilities::ArchetypeSubject,
+    internals::{par, EntityId, Tile, TileFieldSetter},
+};
+
+use crate::{
+    core::structures::errors::ErrorCapability,
+    editor_state::{foundation::TransformerState, windows::GraspEditorWindow},
+    querying::traversal::{TraversalOperator, Traverse},
+    GuiState,
+};
+
+use super::{
+    derived_arrow_components, graph_traversal, make_colored_selection, picked_node,
+    transformer_scope,
+};
+
+const TREE_COLOR: [f32; 4] = [0.2, 0.8, 0.4, 0.5];
+const CUT_COLOR: [f32; 4] = [1.0, 0.4, 0.1, 0.5];
+
+pub struct MaxFlow {
+    pub flows: Vec<(Tile, f32)>,
+    pub cut: Vec<Tile>,
+}
+
+// arrows without the component count as unit weight/capacity
+pub fn arrow_number(arrow: &Tile, component: &str) -> f32 {
+    arrow
+        .get_component(component)
+        .map(|c| c.get("self").as_f32())
+        .unwrap_or(1.0)
+}
+
+fn scoped_arrows(op: &TraversalOperator, nodes: &[Tile]) -> Vec<Tile> {
+    nodes
+        .iter()
+        .flat_map(|n| op.get_arrows_from(n))
+        .filter(|a| !a.is_loop() && a.component.is("Arrow") && nodes.contains(&a.target()))
+        .sorted_by_key(|a| a.id)
+        .collect_vec()
+}
+
+fn find_root(parents: &mut HashMap<EntityId, EntityId>, id: EntityId) -> EntityId {
+    let parent = parents[&id];
+    if parent == id {
+        id
+    } else {
+        let root = find_root(parents, parent);
+        parents.insert(id, root);
+        root
+    }
+}
+
+pub fn minimum_spanning_tree(op: &TraversalOperator) -> Vec<Tile> {
+    let nodes = op.get_objects().collect_vec();
+    let mut parents: HashMap<EntityId, EntityId> = nodes.iter().map(|n| (n.id, n.id)).collect();
+    let mut tree = vec![];
+
+    for arrow in scoped_arrows(op, &nodes)
+        .into_iter()
+        .sorted_by(|a, b| arrow_number(a, "Weight").total_cmp(&arrow_number(b, "Weight")))
+    {
+        let source = find_root(&mut parents, arrow.source_id());
+        let target = find_root(&mut parents, arrow.target_id());
+        if source != target {
+            parents.insert(source, target);
+            tree.push(arrow);
+        }
+    }
+
+    tree
+}
+
+pub fn max_flow(op: &TraversalOperator, source: &Tile, sink: &Tile) -> MaxFlow {
+    let nodes = op.get_objects().collect_vec();
+    let arrows = scoped_arrows(op, &nodes);
+    let capacities = arrows
+        .iter()
+        .map(|a| arrow_number(a, "Capacity").max(0.0))
+        .collect_vec();
+    let mut flows = vec![0.0f32; arrows.len()];
+
+    // every arrow can be walked forward on spare capacity and backward on its current flow
+    let mut residual: HashMap<EntityId, Vec<(usize, bool)>> = HashMap::new();
+    for (i, arrow) in arrows.iter().enumerate() {
+        residual
+            .entry(arrow.source_id())
+            .or_default()
+            .push((i, true));
+        residual
+            .entry(arrow.target_id())
+            .or_default()
+            .push((i, false));
+    }
+
+    let spare = |flows: &[f32], i: usize, forward: bool| {
+        if forward {
+            capacities[i] - flows[i]
+        } else {
+            flows[i]
+        }
+    };
+
+    let reachable = loop {
+        let mut came_from: HashMap<EntityId, (usize, bool)> = HashMap::new();
+        let mut visited = HashSet::from([source.id]);
+        let mut queue = VecDeque::from([source.id]);
+
+        while let Some(current) = queue.pop_front() {
+            if current == sink.id {
+                break;
+            }
+
+            for &(i, forward) in residual.get(&current).into_iter().flatten() {
+                let next = if forward {
+                    arrows[i].target_id()
+                } else {
+                    arrows[i].source_id()
+                };
+
+                if spare(&flows, i, forward) > f32::EPSILON && visited.insert(next) {
+                    came_from.insert(next, (i, forward));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        if !came_from.contains_key(&sink.id) {
+            break visited;
+        }
+
+        let mut path = vec![];
+        let mut current = sink.id;
+        while current != source.id {
+            let (i, forward) = came_from[&current];
+            path.push((i, forward));
+            current = if forward {
+                arrows[i].source_id()
+            } else {
+                arrows[i].target_id()
+            };
+        }
+
+        let bottleneck = path
+            .iter()
+            .map(|&(i, forward)| spare(&flows, i, forward))
+            .fold(f32::INFINITY, f32::min);
+
+        for (i, forward) in path {
+            if forward {
+                flows[i] += bottleneck;
+            } else {
+                flows[i] -= bottleneck;
+            }
+        }
+    };
+
+    let cut = arrows
+        .iter()
+        .filter(|a| reachable.contains(&a.source_id()) && !reachable.contains(&a.target_id()))
+        .cloned()
+        .collect_vec();
+
+    MaxFlow {
+        flows: arrows.into_iter().zip(flows).collect_vec(),
+        cut,
+    }
+}
+
+pub fn minimum_spanning_tree_transformer(
+    window: &mut GraspEditorWindow,
+    _ui: &GuiState,
+    initial_state: &[Tile],
+    _tile: &Tile,
+) -> TransformerState {
+    let scope = transformer_scope(window, initial_state);
+    let derived = derived_arrow_components();
+    let op = window
+        .document_mosaic
+        .traverse(graph_traversal(scope, &derived));
+    let tree = minimum_spanning_tree(&op);
+
+    if !tree.is_empty() {
+        make_colored_selection(&tree, TREE_COLOR);
+        window.editor_data.selected = tree;
+        window.mark_changed();
+    }
+
+    TransformerState::Done
+}
+
+pub fn max_flow_transformer(
+    window: &mut GraspEditorWindow,
+    _ui: &GuiState,
+    initial_state: &[Tile],
+    _tile: &Tile,
+) -> TransformerState {
+    let source = picked_node(&window.document_mosaic, "Pick1");
+    let sink = picked_node(&window.document_mosaic, "Pick2");
+
+    let (source, sink) = match (source, sink) {
+        (Some(source), Some(sink)) if source != sink => (source, sink),
+        _ => {
+            window.editor_mosaic.make_error(
+                "[Graph] Max Flow / Min Cut",
+                "Max flow needs a source picked with Ctrl+1 and a different sink picked with Ctrl+2",
+                Some(window.window_tile.clone()),
+                None,
+            );
+            return TransformerState::Done;
+        }
+    };
+
+    let mut scope = transformer_scope(window, initial_state);
+    for node in [&source, &sink] {
+        if !scope.contains(node) {
+            scope.push(node.clone());
+        }
+    }
+
+    let derived = derived_arrow_components();
+    let op = window
+        .document_mosaic
+        .traverse(graph_traversal(scope, &derived));
+    let result = max_flow(&op, &source, &sink);
+
+    for (arrow, flow) in &result.flows {
+        if let Some(mut component) = arrow.get_component("Flow") {
+            component.set("self", *flow);
+        } else {
+            arrow.add_component("Flow", par(*flow));
+        }
+    }
+
+    if !result.cut.is_empty() {
+        make_colored_selection(&result.cut, CUT_COLOR);
+        window.editor_data.selected = result.cut;
+    }
+
+    window.mark_changed();
+    TransformerState::Done
+}
+
+#[cfg(test)]
+mod weighted_tests {
+    use mosaic::capabilities::ArchetypeSubject;
+    use mosaic::internals::{par, void, Mosaic, MosaicCRUD, MosaicIO, MosaicTypelevelCRUD};
+
+    use crate::querying::traversal::Traverse;
+
+    use super::{max_flow, minimum_spanning_tree};
+
+    #[test]
+    fn test_spanning_tree_skips_heaviest_cycle_arrow() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Arrow: unit;").unwrap();
+        mosaic.new_type("Weight: f32;").unwrap();
+        let a = mosaic.new_object("void", void());
+        let b = mosaic.new_object("void", void());
+        let c = mosaic.new_object("void", void());
+        let ab = mosaic.new_arrow(&a, &b, "Arrow", void());
+        let bc = mosaic.new_arrow(&b, &c, "Arrow", void());
+        let ca = mosaic.new_arrow(&c, &a, "Arrow", void());
+        ab.add_component("Weight", par(1.0f32));
+        bc.add_component("Weight", par(2.0f32));
+        ca.add_component("Weight", par(5.0f32));
+
+        let op = mosaic.traverse(vec![a, b, c].into());
+        assert_eq!(vec![ab, bc], minimum_spanning_tree(&op));
+    }
+
+    #[test]
+    fn test_max_flow_matches_min_cut() {
+        let mosaic = Mosaic::new();
+        mosaic.new_type("Arrow: unit;").unwrap();
+        mosaic.new_type("Capacity: f32;").unwrap();
+        let s = mosaic.new_object("void", void());
+        let a = mosaic.new_object("void", void());
+        let b = mosaic.new_object("void", void());
+        let t = mosaic.new_object("void", void());
+
+        for (from, to, capacity) in [
+            (&s, &a, 3.0f32),
+            (&s, &b, 2.0),
+            (&a, &b, 1.0),
+            (&a, &t, 2.0),
+            (&b, &t, 3.0),
+        ] {
+            let arrow = mosaic.new_arrow(from, to, "Arrow", void());
+            arrow.add_component("Capacity", par(capacity));
+        }
+
+        let op = mosaic.traverse(vec![s.clone(), a, b, t.clone()].into());
+        let result = max_flow(&op, &s, &t);
+        let value = result
+            .flows
+            .iter()
+            .filter(|(a, _)| a.source_id() == s.id)
+            .map(|(_, f)| f)
+            .sum::<f32>();
+        assert_eq!(5.0, value);
+
+        let cut_capacity = result
+            .cut
+            .iter()
+            .map(|a| super::arrow_number(a, "Capacity"))
+            .sum::<f32>();
+        assert_eq!(value, cut_capacity);
+    }
+}
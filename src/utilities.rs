@@ -139,3 +139,88 @@ impl QuadTreeFetch for Vec<usize> {
         mosaic.get(*self.first().unwrap()).unwrap()
     }
 }
+
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::UNIT => "".to_string(),
+        Value::I8(v) => v.to_string(),
+        Value::I16(v) => v.to_string(),
+        Value::I32(v) => v.to_string(),
+        Value::I64(v) => v.to_string(),
+        Value::U8(v) => v.to_string(),
+        Value::U16(v) => v.to_string(),
+        Value::U32(v) => v.to_string(),
+        Value::U64(v) => v.to_string(),
+        Value::F32(v) => v.to_string(),
+        Value::F64(v) => v.to_string(),
+        Value::S32(v) => v.to_string(),
+        Value::STR(v) => v.clone(),
+        Value::BOOL(v) => v.to_string(),
+    }
+}
+
+pub fn value_as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::I8(v) => Some(*v as f64),
+        Value::I16(v) => Some(*v as f64),
+        Value::I32(v) => Some(*v as f64),
+        Value::I64(v) => Some(*v as f64),
+        Value::U8(v) => Some(*v as f64),
+        Value::U16(v) => Some(*v as f64),
+        Value::U32(v) => Some(*v as f64),
+        Value::U64(v) => Some(*v as f64),
+        Value::F32(v) => Some(*v as f64),
+        Value::F64(v) => Some(*v),
+        _ => None,
+    }
+}
+
+pub fn field_names(tile: &Tile) -> Vec<String> {
+    tile.mosaic
+        .component_registry
+        .get_component_type(tile.component)
+        .into_iter()
+        .flat_map(|comp| {
+            if comp.is_alias() {
+                vec!["self".to_string()]
+            } else {
+                comp.get_fields()
+                    .iter()
+                    .map(|f| f.name.to_string())
+                    .collect_vec()
+            }
+        })
+        .collect_vec()
+}
+
+pub fn set_field_from_str(tile: &mut Tile, field: &str, text: &str) -> Result<(), String> {
+    let component = tile.component.to_string();
+    if !field_names(tile).iter().any(|f| f == field) {
+        return Err(format!("{} has no field '{}'", component, field));
+    }
+
+    let invalid = |_| {
+        format!(
+            "'{}' is not a valid value for {}.{}",
+            text, component, field
+        )
+    };
+    match tile.get(field) {
+        Value::UNIT => return Err(format!("{}.{} holds no value", component, field)),
+        Value::I8(_) => tile.set(field, text.parse::<i8>().map_err(invalid)?),
+        Value::I16(_) => tile.set(field, text.parse::<i16>().map_err(invalid)?),
+        Value::I32(_) => tile.set(field, text.parse::<i32>().map_err(invalid)?),
+        Value::I64(_) => tile.set(field, text.parse::<i64>().map_err(invalid)?),
+        Value::U8(_) => tile.set(field, text.parse::<u8>().map_err(invalid)?),
+        Value::U16(_) => tile.set(field, text.parse::<u16>().map_err(invalid)?),
+        Value::U32(_) => tile.set(field, text.parse::<u32>().map_err(invalid)?),
+        Value::U64(_) => tile.set(field, text.parse::<u64>().map_err(invalid)?),
+        Value::F32(_) => tile.set(field, text.parse::<f32>().map_err(invalid)?),
+        Value::F64(_) => tile.set(field, text.parse::<f64>().map_err(invalid)?),
+        Value::S32(_) => tile.set(field, text),
+        Value::STR(_) => tile.set(field, text.to_string()),
+        Value::BOOL(_) => tile.set(field, text.parse::<bool>().map_err(invalid)?),
+    }
+
+    Ok(())
+}